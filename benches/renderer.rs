@@ -1,52 +1,53 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gabalah::memory::RAM_START;
 use gabalah::renderer;
 
 fn make_ram() -> Vec<u8> {
-    let mut ram = vec![0u8; 65536];
+    let mut ram = vec![0u8; 0x10000 - RAM_START];
     // LCD on, BG + OBJ enabled, unsigned tile data (0x8000)
-    ram[0xFF40] = 0x93;
+    ram[0xFF40 - RAM_START] = 0x93;
     // Identity palette
-    ram[0xFF47] = 0xE4;
-    ram[0xFF48] = 0xE4;
+    ram[0xFF47 - RAM_START] = 0xE4;
+    ram[0xFF48 - RAM_START] = 0xE4;
     // Mild scroll so tile fetches aren't all tile 0
-    ram[0xFF42] = 17; // SCY
-    ram[0xFF43] = 23; // SCX
-                      // Populate a few tiles with non-zero data so rendering isn't trivially empty
+    ram[0xFF42 - RAM_START] = 17; // SCY
+    ram[0xFF43 - RAM_START] = 23; // SCX
+                                  // Populate a few tiles with non-zero data so rendering isn't trivially empty
     for i in 0..16usize {
         let addr = 0x8000 + i * 16;
         for row in 0..8usize {
-            ram[addr + row * 2] = (i as u8).wrapping_mul(17);
-            ram[addr + row * 2 + 1] = (i as u8).wrapping_mul(31);
+            ram[addr + row * 2 - RAM_START] = (i as u8).wrapping_mul(17);
+            ram[addr + row * 2 + 1 - RAM_START] = (i as u8).wrapping_mul(31);
         }
     }
     // Scatter tile indices across the BG map
     for i in 0..32usize {
         for j in 0..32usize {
-            ram[0x9800 + i * 32 + j] = ((i + j) % 16) as u8;
+            ram[0x9800 + i * 32 + j - RAM_START] = ((i + j) % 16) as u8;
         }
     }
     // One sprite on-screen
-    ram[0xFE00] = 24; // Y: screen row 8
-    ram[0xFE01] = 16; // X: screen col 8
-    ram[0xFE02] = 1; // tile index 1
+    ram[0xFE00 - RAM_START] = 24; // Y: screen row 8
+    ram[0xFE01 - RAM_START] = 16; // X: screen col 8
+    ram[0xFE02 - RAM_START] = 1; // tile index 1
     ram
 }
 
 fn make_priority_ram() -> Vec<u8> {
     let mut ram = make_ram();
-    ram[0xFE03] = 0x80; // sprite priority behind non-zero BG
+    ram[0xFE03 - RAM_START] = 0x80; // sprite priority behind non-zero BG
     ram
 }
 
 fn make_uniform_latches(ram: &[u8]) -> [renderer::ScanlineRegs; renderer::HEIGHT as usize] {
     let mut latches = [renderer::ScanlineRegs::default(); renderer::HEIGHT as usize];
     let regs = renderer::ScanlineRegs {
-        lcdc: ram[0xFF40],
-        scy: ram[0xFF42],
-        scx: ram[0xFF43],
-        bgp: ram[0xFF47],
-        wy: ram[0xFF4A],
-        wx: ram[0xFF4B],
+        lcdc: ram[0xFF40 - RAM_START],
+        scy: ram[0xFF42 - RAM_START],
+        scx: ram[0xFF43 - RAM_START],
+        bgp: ram[0xFF47 - RAM_START],
+        wy: ram[0xFF4A - RAM_START],
+        wx: ram[0xFF4B - RAM_START],
     };
     latches.fill(regs);
     latches
@@ -82,12 +83,15 @@ fn bench_render_frame_reuse(c: &mut Criterion) {
     let ram = make_ram();
     let mut screen = vec![0u8; renderer::WIDTH as usize * renderer::HEIGHT as usize * 4];
     let mut bg_opaque = vec![false; renderer::WIDTH as usize * renderer::HEIGHT as usize];
+    let mut tile_cache = renderer::TileCache::new();
     c.bench_function("render_frame_reuse", |b| {
         b.iter(|| {
             renderer::render_frame_with_bg_opaque(
                 black_box(&ram),
                 black_box(&mut screen),
                 &mut bg_opaque,
+                &renderer::Palette::DMG_GREEN,
+                &mut tile_cache,
             )
         })
     });
@@ -97,12 +101,15 @@ fn bench_render_frame_reuse_priority(c: &mut Criterion) {
     let ram = make_priority_ram();
     let mut screen = vec![0u8; renderer::WIDTH as usize * renderer::HEIGHT as usize * 4];
     let mut bg_opaque = vec![false; renderer::WIDTH as usize * renderer::HEIGHT as usize];
+    let mut tile_cache = renderer::TileCache::new();
     c.bench_function("render_frame_reuse_priority", |b| {
         b.iter(|| {
             renderer::render_frame_with_bg_opaque(
                 black_box(&ram),
                 black_box(&mut screen),
                 &mut bg_opaque,
+                &renderer::Palette::DMG_GREEN,
+                &mut tile_cache,
             )
         })
     });
@@ -113,6 +120,7 @@ fn bench_render_frame_latched_uniform(c: &mut Criterion) {
     let latches = make_uniform_latches(&ram);
     let mut screen = vec![0u8; renderer::WIDTH as usize * renderer::HEIGHT as usize * 4];
     let mut bg_opaque = vec![false; renderer::WIDTH as usize * renderer::HEIGHT as usize];
+    let mut tile_cache = renderer::TileCache::new();
     c.bench_function("render_frame_latched_uniform", |b| {
         b.iter(|| {
             renderer::render_frame_with_scanline_latches(
@@ -120,6 +128,8 @@ fn bench_render_frame_latched_uniform(c: &mut Criterion) {
                 black_box(&mut screen),
                 &mut bg_opaque,
                 black_box(&latches),
+                &renderer::Palette::DMG_GREEN,
+                &mut tile_cache,
             )
         })
     });
@@ -130,6 +140,7 @@ fn bench_render_frame_latched_split(c: &mut Criterion) {
     let latches = make_split_latches(&ram);
     let mut screen = vec![0u8; renderer::WIDTH as usize * renderer::HEIGHT as usize * 4];
     let mut bg_opaque = vec![false; renderer::WIDTH as usize * renderer::HEIGHT as usize];
+    let mut tile_cache = renderer::TileCache::new();
     c.bench_function("render_frame_latched_split", |b| {
         b.iter(|| {
             renderer::render_frame_with_scanline_latches(
@@ -137,6 +148,8 @@ fn bench_render_frame_latched_split(c: &mut Criterion) {
                 black_box(&mut screen),
                 &mut bg_opaque,
                 black_box(&latches),
+                &renderer::Palette::DMG_GREEN,
+                &mut tile_cache,
             )
         })
     });
@@ -1,17 +1,18 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
-use super::renderer;
+use super::renderer::{FrameBlender, LayerFlags, Palette};
 use crate::config;
-use crate::config::{Controls, DebugDumpSettings};
+use crate::config::{Controls, DebugDumpSettings, WindowSettings};
 use crate::cpu::Cpu;
-use crate::memory::Addr;
+use crate::memory::{io, Addr, Button, RAM_START};
+use crate::png;
 use crate::ui::{self, GraphicsBackendKind, GraphicsOptions};
 use log::{debug, error, warn};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use winit::{
     dpi::LogicalSize,
     event::{Event, WindowEvent},
@@ -30,8 +31,13 @@ const WINDOW_ICON_RGBA: &[u8] = include_bytes!(concat!(
 ));
 // ~70,224 cycles per frame at 4.194304 MHz / 59.7275 fps
 const CYCLES_PER_FRAME: usize = 70224;
-const FRAME_DURATION: Duration = Duration::from_nanos(16_742_706); // 70224 / 4_194_304 s
-const INTERRUPT_SERVICE_CYCLES: usize = 20;
+const CPU_CLOCK_HZ: u64 = 4_194_304;
+// ~59.7275 Hz
+const FRAME_DURATION: Duration = frame_duration(CYCLES_PER_FRAME as u64, CPU_CLOCK_HZ);
+// Emulated frames run per displayed frame while the turbo key is held.
+const TURBO_MULTIPLIER: usize = 4;
+// Wall-clock speeds stepped through by the cycle-speed key.
+const SPEED_PRESETS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
 const SHADER_NAME_OVERLAY_DURATION: Duration = Duration::from_secs(3);
 const FALLBACK_SHADER_NAME: &str = "builtin-crt";
 
@@ -39,7 +45,7 @@ pub fn run_loop(
     cpu: Cpu,
     backend_kind: GraphicsBackendKind,
     backend_options: GraphicsOptions,
-    window_scale: f64,
+    window_settings: WindowSettings,
     controls: Controls,
     debug_dump_settings: DebugDumpSettings,
     save_path: Option<PathBuf>,
@@ -48,12 +54,13 @@ pub fn run_loop(
     let event_loop = EventLoop::new().unwrap();
     let mut input = WinitInputHelper::new();
 
+    let title = window_title(cpu.cartridge_header().map(|header| header.title.as_str()));
     let window = {
         let size = LogicalSize::new(WIDTH as f64, HEIGHT as f64);
-        let scaled_size =
-            LogicalSize::new(WIDTH as f64 * window_scale, HEIGHT as f64 * window_scale);
+        let scale = window_settings.scale;
+        let scaled_size = LogicalSize::new(WIDTH as f64 * scale, HEIGHT as f64 * scale);
         WindowBuilder::new()
-            .with_title("Gabalah")
+            .with_title(title)
             .with_inner_size(scaled_size)
             .with_min_inner_size(size)
             .with_window_icon(load_window_icon())
@@ -65,11 +72,20 @@ pub fn run_loop(
     debug!("Using graphics backend '{}'", backend_kind.as_str());
 
     let mut emulator = Emulator::new(cpu, debug_dump_settings);
+    emulator.blender = FrameBlender::new(window_settings.frame_blend);
     let mut last_frame = Instant::now();
+    let mut fast_forward = false;
+    let mut turbo = false;
+    let mut paused = false;
     let mut shader_overlay = ShaderOverlay::default();
 
     let res = event_loop.run(|event, elwt| {
-        elwt.set_control_flow(ControlFlow::WaitUntil(last_frame + FRAME_DURATION));
+        let frame_time = paced_frame_duration(emulator.cpu.speed());
+        if fast_forward || turbo {
+            elwt.set_control_flow(ControlFlow::Poll);
+        } else {
+            elwt.set_control_flow(ControlFlow::WaitUntil(last_frame + frame_time));
+        }
 
         if let Event::WindowEvent {
             event: WindowEvent::RedrawRequested,
@@ -95,39 +111,66 @@ pub fn run_loop(
                 return;
             }
 
-            // Joypad: (key, is_action_group, bit)
-            // Direction bits: 0=Right, 1=Left, 2=Up, 3=Down
-            // Action bits:    0=A,     1=B,    2=Select, 3=Start
-            let buttons: [(KeyCode, bool, u8); 8] = [
-                (controls.joypad.right, false, 0x01),
-                (controls.joypad.left, false, 0x02),
-                (controls.joypad.up, false, 0x04),
-                (controls.joypad.down, false, 0x08),
-                (controls.joypad.a, true, 0x01),
-                (controls.joypad.b, true, 0x02),
-                (controls.joypad.select, true, 0x04),
-                (controls.joypad.start, true, 0x08),
+            let buttons: [(KeyCode, Button); 8] = [
+                (controls.joypad.right, Button::Right),
+                (controls.joypad.left, Button::Left),
+                (controls.joypad.up, Button::Up),
+                (controls.joypad.down, Button::Down),
+                (controls.joypad.a, Button::A),
+                (controls.joypad.b, Button::B),
+                (controls.joypad.select, Button::Select),
+                (controls.joypad.start, Button::Start),
             ];
-            let mut any_newly_pressed = false;
-            for (key, is_action, bit) in buttons {
+            for (key, button) in buttons {
                 if input.key_pressed(key) {
-                    if is_action {
-                        emulator.cpu.set_action_button_pressed(bit, true);
-                    } else {
-                        emulator.cpu.set_direction_button_pressed(bit, true);
-                    }
-                    any_newly_pressed = true;
+                    emulator.cpu.set_button(button, true);
                 }
                 if input.key_released(key) {
-                    if is_action {
-                        emulator.cpu.set_action_button_pressed(bit, false);
-                    } else {
-                        emulator.cpu.set_direction_button_pressed(bit, false);
-                    }
+                    emulator.cpu.set_button(button, false);
                 }
             }
-            if any_newly_pressed {
-                emulator.cpu.raise_if(0x10);
+            if input.key_pressed(controls.hotkeys.fast_forward) {
+                fast_forward = !fast_forward;
+                debug!("Fast-forward {}", if fast_forward { "on" } else { "off" });
+            }
+            turbo = input.key_held(controls.hotkeys.turbo);
+            if input.key_pressed(controls.hotkeys.cycle_speed) {
+                let speed = next_speed_preset(emulator.cpu.speed());
+                emulator.cpu.set_speed(speed);
+                debug!("Speed {speed}x");
+            }
+            if input.key_pressed(controls.hotkeys.pause) {
+                paused = !paused;
+                debug!("{}", if paused { "Paused" } else { "Resumed" });
+            }
+            if paused && input.key_pressed(controls.hotkeys.frame_step) {
+                // Always stops at VBlank, so the image shown is one complete frame.
+                emulator.cpu.step_frame();
+                window.request_redraw();
+            }
+            if input.key_pressed(controls.hotkeys.next_palette) {
+                debug!("Palette: {}", emulator.cycle_palette());
+                window.request_redraw();
+            }
+            let mut layers = emulator.layers;
+            if input.key_pressed(controls.hotkeys.toggle_background) {
+                layers.background = !layers.background;
+            }
+            if input.key_pressed(controls.hotkeys.toggle_window) {
+                layers.window = !layers.window;
+            }
+            if input.key_pressed(controls.hotkeys.toggle_sprites) {
+                layers.sprites = !layers.sprites;
+            }
+            if layers != emulator.layers {
+                emulator.set_layers(layers);
+                debug!("Layers: {layers:?}");
+            }
+            if input.key_pressed(controls.hotkeys.screenshot) {
+                match save_screenshot(emulator.cpu.frame_buffer()) {
+                    Ok(path) => debug!("Saved screenshot {}", path.display()),
+                    Err(err) => error!("screenshot failed: {err}"),
+                }
             }
             if input.key_pressed(controls.hotkeys.debug_frame_dump) {
                 emulator.request_dump();
@@ -233,12 +276,25 @@ pub fn run_loop(
             }
 
             let mut stepped = false;
-            while last_frame.elapsed() >= FRAME_DURATION {
-                last_frame += FRAME_DURATION;
-                emulator.step_frame();
+            let mut frame_ready = false;
+            if paused {
+                // Resume pacing from the moment of unpausing rather than catching up.
+                last_frame = Instant::now();
+            } else if fast_forward || turbo {
+                // Uncapped: step once per event-loop pass, then resume pacing from now.
+                let multiplier = if turbo { TURBO_MULTIPLIER } else { 1 };
+                frame_ready |= emulator.step_cycles(frame_cycle_budget(multiplier));
                 stepped = true;
+                last_frame = Instant::now();
+            } else {
+                while last_frame.elapsed() >= frame_time {
+                    last_frame += frame_time;
+                    frame_ready |= emulator.step_frame();
+                    stepped = true;
+                }
             }
-            if stepped {
+            // With the LCD off the PPU never completes a frame; keep presenting the blank screen.
+            if frame_ready || (stepped && !emulator.lcd_enabled()) {
                 window.request_redraw();
             }
         }
@@ -254,6 +310,64 @@ pub fn run_headless(cpu: Cpu, frames: usize) -> Vec<u8> {
     emulator.cpu.serial_output().to_vec()
 }
 
+/// Wall-clock time one emulated frame of `cycles_per_frame` cycles takes at `clock_hz`.
+const fn frame_duration(cycles_per_frame: u64, clock_hz: u64) -> Duration {
+    Duration::from_nanos(cycles_per_frame * 1_000_000_000 / clock_hz)
+}
+
+/// Wall-clock time one emulated frame takes at `speed` times real time.
+fn paced_frame_duration(speed: f32) -> Duration {
+    Duration::from_nanos((FRAME_DURATION.as_nanos() as f64 / f64::from(speed)) as u64)
+}
+
+/// The preset after `speed`, wrapping back to the slowest after the fastest.
+fn next_speed_preset(speed: f32) -> f32 {
+    SPEED_PRESETS
+        .into_iter()
+        .find(|&preset| preset > speed)
+        .unwrap_or(SPEED_PRESETS[0])
+}
+
+/// Cycles to run per displayed frame when `turbo_multiplier` emulated frames are shown as one.
+fn frame_cycle_budget(turbo_multiplier: usize) -> usize {
+    CYCLES_PER_FRAME * turbo_multiplier.max(1)
+}
+
+/// Writes `frame` as a PNG in the working directory and returns its path.
+fn save_screenshot(frame: &[u8]) -> std::io::Result<PathBuf> {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = screenshot_path(Path::new("."), since_epoch);
+    fs::write(&path, png::encode_rgba(WIDTH, HEIGHT, frame))?;
+    Ok(path)
+}
+
+/// `gabalah-<seconds>-<millis>.png` in `dir`, with a `-<n>` suffix if that file already exists
+/// (e.g. two screenshots within the same millisecond).
+fn screenshot_path(dir: &Path, since_epoch: Duration) -> PathBuf {
+    let stem = format!(
+        "gabalah-{}-{:03}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    );
+    let mut path = dir.join(format!("{stem}.png"));
+    let mut suffix = 1;
+    while path.exists() {
+        path = dir.join(format!("{stem}-{suffix}.png"));
+        suffix += 1;
+    }
+    path
+}
+
+/// "Gabalah — <game title>", or just "Gabalah" without a usable cartridge title.
+fn window_title(game_title: Option<&str>) -> String {
+    match game_title.map(str::trim) {
+        Some(title) if !title.is_empty() => format!("Gabalah — {title}"),
+        _ => "Gabalah".to_string(),
+    }
+}
+
 fn load_window_icon() -> Option<Icon> {
     Icon::from_rgba(
         WINDOW_ICON_RGBA.to_vec(),
@@ -459,10 +573,9 @@ fn glyph_5x7(ch: char) -> [u8; 7] {
 
 struct Emulator {
     cpu: Cpu,
-    ppu_line_cycles: usize,
-    bg_opaque: Vec<bool>,
-    scanline_latches: [renderer::ScanlineRegs; HEIGHT as usize],
-    scanline_latched: [bool; HEIGHT as usize],
+    palette_index: usize,
+    layers: LayerFlags,
+    blender: FrameBlender,
     dump_next_frame: bool,
     dump_index: usize,
     debug_dump_settings: DebugDumpSettings,
@@ -472,10 +585,9 @@ impl Emulator {
     fn new(cpu: Cpu, debug_dump_settings: DebugDumpSettings) -> Self {
         Self {
             cpu,
-            ppu_line_cycles: 0,
-            bg_opaque: vec![false; (WIDTH * HEIGHT) as usize],
-            scanline_latches: [renderer::ScanlineRegs::default(); HEIGHT as usize],
-            scanline_latched: [false; HEIGHT as usize],
+            palette_index: 0,
+            layers: LayerFlags::default(),
+            blender: FrameBlender::default(),
             dump_next_frame: false,
             dump_index: 0,
             debug_dump_settings,
@@ -483,177 +595,41 @@ impl Emulator {
     }
 
     /// Runs the CPU for approximately one frame's worth of cycles.
-    fn step_frame(&mut self) {
-        self.step_cycles(CYCLES_PER_FRAME);
+    /// Returns true if the PPU completed a frame along the way.
+    fn step_frame(&mut self) -> bool {
+        self.step_cycles(CYCLES_PER_FRAME)
     }
 
-    fn step_cycles(&mut self, cycle_budget: usize) {
+    fn step_cycles(&mut self, cycle_budget: usize) -> bool {
         let mut cycles_this_step = 0;
+        let mut frame_ready = false;
         while cycles_this_step < cycle_budget {
-            let cycles = self.cpu.step();
-            cycles_this_step += cycles;
-            self.tick_lcd(cycles);
-
-            if self.cpu.tick_timers(cycles as u32) {
-                self.cpu.raise_if(0x04);
-            }
-
-            if self.is_interrupt_pending() {
-                let interrupt_cycles = self.interrupt();
-                cycles_this_step += interrupt_cycles;
-                self.tick_lcd(interrupt_cycles);
-
-                if self.cpu.tick_timers(interrupt_cycles as u32) {
-                    self.cpu.raise_if(0x04);
-                }
-            }
+            cycles_this_step += self.cpu.step();
+            frame_ready |= self.cpu.frame_ready();
         }
+        frame_ready
     }
 
-    fn tick_lcd(&mut self, cycles: usize) {
-        let lcdc = self.cpu.read_byte(Addr(0xFF40));
-        if (lcdc & 0x80) == 0 {
-            self.ppu_line_cycles = 0;
-            self.cpu.set_ly_raw(0);
-            self.scanline_latched.fill(false);
-            self.update_stat(0, false, false);
-            return;
-        }
-
-        self.ppu_line_cycles += cycles;
-        while self.ppu_line_cycles >= 456 {
-            self.ppu_line_cycles -= 456;
-            let ly = self.cpu.read_byte(Addr(0xFF44));
-            let new_ly = if ly >= 153 { 0 } else { ly + 1 };
-            self.cpu.set_ly_raw(new_ly);
-            if new_ly == 0 {
-                self.scanline_latched.fill(false);
-            }
-            if new_ly == 144 {
-                self.cpu.raise_if(0x01);
-            }
-        }
-
-        let ly = self.cpu.read_byte(Addr(0xFF44));
-        let mode = if ly >= 144 {
-            1
-        } else if self.ppu_line_cycles < 80 {
-            2
-        } else if self.ppu_line_cycles < 252 {
-            3
-        } else {
-            0
-        };
-        let lyc = self.cpu.read_byte(Addr(0xFF45));
-        self.update_stat(mode, ly == lyc, true);
-        self.maybe_latch_scanline(ly, mode);
-    }
-
-    fn update_stat(&mut self, mode: u8, coincidence: bool, allow_interrupt: bool) {
-        let old_stat = self.cpu.read_byte(Addr(0xFF41));
-        let old_mode = old_stat & 0x03;
-        let old_coincidence = (old_stat & 0x04) != 0;
-        let mut new_stat = (old_stat & 0x78) | (mode & 0x03);
-        if coincidence {
-            new_stat |= 0x04;
-        }
-        self.cpu.set_stat_raw(new_stat);
-
-        if !allow_interrupt {
-            return;
-        }
-
-        let mode_changed = mode != old_mode;
-        let mode_irq = match mode {
-            0 => (new_stat & 0x08) != 0,
-            1 => (new_stat & 0x10) != 0,
-            2 => (new_stat & 0x20) != 0,
-            _ => false,
-        };
-        let lyc_irq = coincidence && !old_coincidence && (new_stat & 0x40) != 0;
-        if (mode_changed && mode_irq) || lyc_irq {
-            self.cpu.raise_if(0x02);
-        }
-    }
-
-    fn is_interrupt_pending(&self) -> bool {
-        self.cpu.registers.ime && (self.cpu.get_ie() & self.cpu.get_if()) != 0
-    }
-
-    fn interrupt(&mut self) -> usize {
-        self.cpu.halted = false;
-        self.cpu.registers.ime = false;
-        let if_contents = self.cpu.get_if();
-        let ie_contents = self.cpu.get_ie();
-        let pending = if_contents & ie_contents;
-        for bit in 0..5u8 {
-            if pending & (1 << bit) != 0 {
-                self.cpu.clear_if(1 << bit);
-                let vector = 0x0040u16 + (bit as u16) * 8;
-                self.call(vector);
-                self.cpu.total_cycles += INTERRUPT_SERVICE_CYCLES as u64;
-                return INTERRUPT_SERVICE_CYCLES;
-            }
-        }
-        0
-    }
-
-    fn call(&mut self, vector: u16) {
-        self.cpu.write_word(
-            Addr(self.cpu.registers.sp.wrapping_sub(2)),
-            self.cpu.registers.pc,
-        );
-        self.cpu.registers.sp = self.cpu.registers.sp.wrapping_sub(2);
-        self.cpu.registers.pc = vector;
+    fn lcd_enabled(&self) -> bool {
+        (self.cpu.read_byte(Addr(io::LCDC)) & 0x80) != 0
     }
 
-    /// Renders the current emulator state into the pixel buffer.
+    /// Copies the PPU's latest completed frame into the pixel buffer, blended with the frame
+    /// before it when frame blending is enabled.
     fn draw(&mut self, screen: &mut [u8]) {
-        let mut latches = self.scanline_latches;
-        if self.scanline_latched.iter().any(|latched| !latched) {
-            let ram = self.cpu.memory_slice();
-            let fallback = renderer::ScanlineRegs {
-                lcdc: ram[0xFF40],
-                scy: ram[0xFF42],
-                scx: ram[0xFF43],
-                bgp: ram[0xFF47],
-                wy: ram[0xFF4A],
-                wx: ram[0xFF4B],
-            };
-            for (line, latched) in self.scanline_latched.iter().enumerate() {
-                if !latched {
-                    latches[line] = fallback;
-                }
-            }
-        }
-
-        renderer::render_frame_with_scanline_latches(
-            self.cpu.memory_slice(),
-            screen,
-            &mut self.bg_opaque,
-            &latches,
-        );
+        self.blender.blend(self.cpu.frame_buffer(), screen);
     }
 
-    fn maybe_latch_scanline(&mut self, ly: u8, mode: u8) {
-        if mode != 3 || ly >= HEIGHT as u8 {
-            return;
-        }
-        let line = ly as usize;
-        if self.scanline_latched[line] {
-            return;
-        }
+    fn cycle_palette(&mut self) -> &'static str {
+        self.palette_index = (self.palette_index + 1) % Palette::PRESETS.len();
+        let palette = Palette::PRESETS[self.palette_index];
+        self.cpu.set_palette(palette);
+        palette.name
+    }
 
-        let ram = self.cpu.memory_slice();
-        self.scanline_latches[line] = renderer::ScanlineRegs {
-            lcdc: ram[0xFF40],
-            scy: ram[0xFF42],
-            scx: ram[0xFF43],
-            bgp: ram[0xFF47],
-            wy: ram[0xFF4A],
-            wx: ram[0xFF4B],
-        };
-        self.scanline_latched[line] = true;
+    fn set_layers(&mut self, layers: LayerFlags) {
+        self.layers = layers;
+        self.cpu.set_layers(layers);
     }
 
     fn request_dump(&mut self) {
@@ -700,24 +676,29 @@ impl Emulator {
         }
 
         let ram = self.cpu.memory_slice();
-        fs::write(&vram_path, &ram[0x8000..0xA000])?;
-        fs::write(&oam_path, &ram[0xFE00..0xFEA0])?;
+        fs::write(&vram_path, &ram[0x8000 - RAM_START..0xA000 - RAM_START])?;
+        fs::write(&oam_path, &ram[0xFE00 - RAM_START..0xFEA0 - RAM_START])?;
 
         let mut txt = File::create(&txt_path)?;
         writeln!(txt, "total_cycles={}", self.cpu.total_cycles)?;
-        writeln!(txt, "FF40_LCDC={:02X}", ram[0xFF40])?;
-        writeln!(txt, "FF41_STAT={:02X}", ram[0xFF41])?;
-        writeln!(txt, "FF42_SCY={:02X}", ram[0xFF42])?;
-        writeln!(txt, "FF43_SCX={:02X}", ram[0xFF43])?;
-        writeln!(txt, "FF44_LY={:02X}", ram[0xFF44])?;
-        writeln!(txt, "FF45_LYC={:02X}", ram[0xFF45])?;
-        writeln!(txt, "FF47_BGP={:02X}", ram[0xFF47])?;
-        writeln!(txt, "FF48_OBP0={:02X}", ram[0xFF48])?;
-        writeln!(txt, "FF49_OBP1={:02X}", ram[0xFF49])?;
-        writeln!(txt, "FF4A_WY={:02X}", ram[0xFF4A])?;
-        writeln!(txt, "FF4B_WX={:02X}", ram[0xFF4B])?;
-        writeln!(txt, "FF0F_IF={:02X}", ram[0xFF0F])?;
-        writeln!(txt, "FFFF_IE={:02X}", ram[0xFFFF])?;
+        writeln!(
+            txt,
+            "instructions_executed={}",
+            self.cpu.instructions_executed()
+        )?;
+        writeln!(txt, "FF40_LCDC={:02X}", ram[io::LCDC as usize - RAM_START])?;
+        writeln!(txt, "FF41_STAT={:02X}", ram[io::STAT as usize - RAM_START])?;
+        writeln!(txt, "FF42_SCY={:02X}", ram[io::SCY as usize - RAM_START])?;
+        writeln!(txt, "FF43_SCX={:02X}", ram[io::SCX as usize - RAM_START])?;
+        writeln!(txt, "FF44_LY={:02X}", ram[io::LY as usize - RAM_START])?;
+        writeln!(txt, "FF45_LYC={:02X}", ram[io::LYC as usize - RAM_START])?;
+        writeln!(txt, "FF47_BGP={:02X}", ram[io::BGP as usize - RAM_START])?;
+        writeln!(txt, "FF48_OBP0={:02X}", ram[io::OBP0 as usize - RAM_START])?;
+        writeln!(txt, "FF49_OBP1={:02X}", ram[io::OBP1 as usize - RAM_START])?;
+        writeln!(txt, "FF4A_WY={:02X}", ram[io::WY as usize - RAM_START])?;
+        writeln!(txt, "FF4B_WX={:02X}", ram[io::WX as usize - RAM_START])?;
+        writeln!(txt, "FF0F_IF={:02X}", ram[io::IF as usize - RAM_START])?;
+        writeln!(txt, "FFFF_IE={:02X}", ram[io::IE as usize - RAM_START])?;
         debug!(
             "Wrote debug dump: {}, {}, {}, {}",
             ppm_path.display(),
@@ -733,93 +714,92 @@ impl Emulator {
 mod tests {
     use super::*;
 
-    #[test]
-    fn interrupt_services_pending_request_with_20_cycles() {
-        let mut cpu = Cpu::new();
-        cpu.registers.pc = 0x1234;
-        cpu.registers.sp = 0xFFFE;
-        cpu.registers.ime = true;
-        cpu.write_byte(Addr(0xFFFF), 0x04); // IE: timer
-        cpu.raise_if(0x04); // IF: timer pending
-
-        let mut emulator = Emulator::new(cpu, DebugDumpSettings::default());
-        let cycles = emulator.interrupt();
-
-        assert_eq!(cycles, INTERRUPT_SERVICE_CYCLES);
-        assert_eq!(emulator.cpu.total_cycles, INTERRUPT_SERVICE_CYCLES as u64);
-        assert!(!emulator.cpu.registers.ime);
-        assert_eq!(emulator.cpu.registers.pc, 0x0050);
-        assert_eq!(emulator.cpu.registers.sp, 0xFFFC);
-        assert_eq!(emulator.cpu.read_word(Addr(0xFFFC)), 0x1234);
-        assert_eq!(emulator.cpu.get_if() & 0x04, 0);
-    }
-
     #[test]
     fn bounded_step_counts_interrupt_cycles_for_timer_and_ppu() {
         let mut cpu = Cpu::new();
         cpu.registers.ime = true;
-        cpu.write_byte(Addr(0xFFFF), 0x01); // IE: vblank
+        cpu.write_byte(Addr(io::IE), 0x01); // IE: vblank
         cpu.raise_if(0x01); // IF: vblank pending
-        cpu.write_byte(Addr(0xFF07), 0x05); // TAC: enabled, 16-cycle timer
+        cpu.write_byte(Addr(io::DIV), 0); // DIV: reset so TIMA ticks at cycle 16
+        cpu.write_byte(Addr(io::TAC), 0x05); // TAC: enabled, 16-cycle timer
 
         let mut emulator = Emulator::new(cpu, DebugDumpSettings::default());
         emulator.step_cycles(4);
 
         assert_eq!(emulator.cpu.total_cycles, 24);
-        assert_eq!(emulator.cpu.read_byte(Addr(0xFF05)), 1);
-        assert_eq!(emulator.ppu_line_cycles, 24);
+        assert_eq!(emulator.cpu.read_byte(Addr(io::TIMA)), 1);
+        assert_eq!(emulator.cpu.ppu_line_cycles(), 24);
     }
 
     #[test]
-    fn maybe_latch_scanline_captures_registers_once_per_line() {
-        let mut cpu = Cpu::new();
-        cpu.write_byte(Addr(0xFF40), 0xB1);
-        cpu.write_byte(Addr(0xFF42), 0x22);
-        cpu.write_byte(Addr(0xFF43), 0x11);
-        cpu.write_byte(Addr(0xFF47), 0xE4);
-        cpu.write_byte(Addr(0xFF4A), 0x05);
-        cpu.write_byte(Addr(0xFF4B), 0x10);
+    fn window_title_includes_game_title() {
+        assert_eq!(window_title(Some("TETRIS")), "Gabalah — TETRIS");
+    }
 
-        let mut emulator = Emulator::new(cpu, DebugDumpSettings::default());
-        emulator.maybe_latch_scanline(12, 3);
-
-        assert!(emulator.scanline_latched[12]);
-        let first = emulator.scanline_latches[12];
-        assert_eq!(first.lcdc, 0xB1);
-        assert_eq!(first.scy, 0x22);
-        assert_eq!(first.scx, 0x11);
-        assert_eq!(first.bgp, 0xE4);
-        assert_eq!(first.wy, 0x05);
-        assert_eq!(first.wx, 0x10);
-
-        emulator.cpu.write_byte(Addr(0xFF42), 0x99);
-        emulator.cpu.write_byte(Addr(0xFF43), 0x88);
-        emulator.maybe_latch_scanline(12, 3);
-        let second = emulator.scanline_latches[12];
+    #[test]
+    fn window_title_falls_back_without_game_title() {
+        assert_eq!(window_title(None), "Gabalah");
+        assert_eq!(window_title(Some("")), "Gabalah");
+        assert_eq!(window_title(Some("   ")), "Gabalah");
+    }
+
+    #[test]
+    fn screenshot_path_uses_timestamp_and_avoids_collisions() {
+        let dir = std::env::temp_dir().join(format!("gabalah-screenshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let at = Duration::from_millis(1_700_000_000_042);
+
+        let first = screenshot_path(&dir, at);
+        assert_eq!(first, dir.join("gabalah-1700000000-042.png"));
 
+        fs::write(&first, b"").unwrap();
         assert_eq!(
-            first.scy, second.scy,
-            "line latch should be stable after first capture"
+            screenshot_path(&dir, at),
+            dir.join("gabalah-1700000000-042-1.png")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn frame_duration_matches_dmg_refresh_rate() {
+        assert_eq!(FRAME_DURATION, Duration::from_nanos(16_742_706));
+        let fps = 1.0 / FRAME_DURATION.as_secs_f64();
+        assert!(
+            (fps - 59.7275).abs() < 0.0001,
+            "unexpected frame rate {fps}"
         );
         assert_eq!(
-            first.scx, second.scx,
-            "line latch should be stable after first capture"
+            frame_duration(CPU_CLOCK_HZ, CPU_CLOCK_HZ),
+            Duration::from_secs(1)
         );
     }
 
     #[test]
-    fn tick_lcd_clears_scanline_latches_on_frame_wrap() {
-        let cpu = Cpu::new();
-        let mut emulator = Emulator::new(cpu, DebugDumpSettings::default());
+    fn frame_cycle_budget_scales_with_turbo_multiplier() {
+        assert_eq!(frame_cycle_budget(1), CYCLES_PER_FRAME);
+        assert_eq!(frame_cycle_budget(TURBO_MULTIPLIER), 4 * CYCLES_PER_FRAME);
+        assert_eq!(
+            frame_cycle_budget(0),
+            CYCLES_PER_FRAME,
+            "a zero multiplier should still run one frame"
+        );
+    }
 
-        emulator.scanline_latched.fill(true);
-        emulator.cpu.set_ly_raw(153);
-        emulator.ppu_line_cycles = 0;
-        emulator.tick_lcd(456);
+    #[test]
+    fn paced_frame_duration_scales_inversely_with_speed() {
+        assert_eq!(paced_frame_duration(1.0), FRAME_DURATION);
+        assert_eq!(paced_frame_duration(0.25), FRAME_DURATION * 4);
+        assert_eq!(paced_frame_duration(4.0), FRAME_DURATION / 4);
+    }
 
-        assert!(
-            emulator.scanline_latched.iter().all(|latched| !latched),
-            "all scanline latches should reset when LY wraps to 0"
+    #[test]
+    fn next_speed_preset_cycles_and_wraps() {
+        assert_eq!(next_speed_preset(1.0), 2.0);
+        assert_eq!(next_speed_preset(4.0), 0.25);
+        assert_eq!(
+            next_speed_preset(0.75),
+            1.0,
+            "off-preset speeds move to the next preset up"
         );
     }
 
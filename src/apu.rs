@@ -0,0 +1,115 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use crate::memory::{io, RAM_START};
+
+/// The sound channels' pitch settings as decoded from the NRxx registers. There is no audio
+/// output yet; this only interprets what games write, for an on-screen audio debugger.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Apu {
+    /// 11-bit period values of channels 1-3
+    periods: [u16; 3],
+    /// NR43: clock shift in bits 4-7, clock divider code in bits 0-2
+    nr43: u8,
+}
+
+impl Apu {
+    /// Captures the sound registers from `ram` (0x8000-0xFFFF, as `Cpu::memory_slice` returns).
+    pub fn from_ram(ram: &[u8]) -> Apu {
+        let register = |address: u16| ram[address as usize - RAM_START];
+        let period =
+            |lo: u16, hi: u16| u16::from(register(hi) & 0x07) << 8 | u16::from(register(lo));
+        Apu {
+            periods: [
+                period(io::NR13, io::NR14),
+                period(io::NR23, io::NR24),
+                period(io::NR33, io::NR34),
+            ],
+            nr43: register(io::NR43),
+        }
+    }
+
+    /// The effective frequency of channel `ch` (1-4) in Hz: the tone of the pulse channels
+    /// (131072 / (2048 - period)) and of the wave channel (65536 / (2048 - period), as one
+    /// waveform spans 32 samples), and the LFSR clock of the noise channel
+    /// (262144 / (divider * 2^shift), divider code 0 counting as 0.5). Other channels give 0.
+    pub fn channel_frequency(&self, ch: usize) -> f32 {
+        match ch {
+            1 | 2 => 131_072.0 / f32::from(2048 - self.periods[ch - 1]),
+            3 => 65_536.0 / f32::from(2048 - self.periods[2]),
+            4 => {
+                let divider = match self.nr43 & 0x07 {
+                    0 => 0.5,
+                    code => f32::from(code),
+                };
+                let shift = self.nr43 >> 4;
+                262_144.0 / (divider * (1u32 << shift) as f32)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::Cpu;
+    use crate::memory::Addr;
+    use alloc::vec;
+
+    fn apu_with(registers: &[(u16, u8)]) -> Apu {
+        let mut ram = vec![0u8; 0x10000 - RAM_START];
+        for &(address, value) in registers {
+            ram[address as usize - RAM_START] = value;
+        }
+        Apu::from_ram(&ram)
+    }
+
+    #[test]
+    fn pulse_channels_use_131072_over_2048_minus_period() {
+        // Period 0x783 (1923) is A4-ish: 131072 / 125 = 1048.576 Hz.
+        let apu = apu_with(&[(io::NR13, 0x83), (io::NR14, 0x87), (io::NR24, 0x07)]);
+        assert_eq!(apu.channel_frequency(1), 1048.576);
+        // Period 0x700: 131072 / 256.
+        assert_eq!(apu.channel_frequency(2), 512.0);
+        assert_eq!(
+            apu_with(&[]).channel_frequency(1),
+            64.0,
+            "period 0 is the lowest tone"
+        );
+    }
+
+    #[test]
+    fn wave_channel_is_an_octave_below_the_same_pulse_period() {
+        let apu = apu_with(&[(io::NR33, 0x00), (io::NR34, 0xC7)]);
+        assert_eq!(apu.channel_frequency(3), 256.0);
+    }
+
+    #[test]
+    fn noise_channel_rate_comes_from_divider_and_shift() {
+        assert_eq!(
+            apu_with(&[]).channel_frequency(4),
+            524_288.0,
+            "r=0 counts as 0.5"
+        );
+        assert_eq!(
+            apu_with(&[(io::NR43, 0x32)]).channel_frequency(4),
+            16_384.0,
+            "262144 / (2 * 2^3)"
+        );
+        assert_eq!(apu_with(&[]).channel_frequency(5), 0.0);
+    }
+
+    #[test]
+    fn reads_registers_written_through_the_cpu() {
+        let mut cpu = Cpu::new();
+        cpu.write_byte(Addr(io::NR13), 0x00);
+        cpu.write_byte(Addr(io::NR14), 0x87);
+        cpu.write_byte(Addr(io::NR43), 0x32);
+
+        let apu = Apu::from_ram(cpu.memory_slice());
+
+        assert_eq!(apu.channel_frequency(1), 512.0);
+        assert_eq!(apu.channel_frequency(4), 16_384.0);
+    }
+}
@@ -0,0 +1,249 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use std::env;
+use std::path::Path;
+
+use gabalah::cpu::Cpu;
+use gabalah::debugger::{self, Debugger, StopReason};
+use gabalah::memory::Addr;
+use gabalah::rom_loader;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+// One second of emulation per continue, so the UI stays responsive in long loops.
+const CONTINUE_CYCLES: usize = 4_194_304;
+const STEP_OVER_CYCLES: usize = CONTINUE_CYCLES;
+const DISASSEMBLY_LINES: usize = 16;
+const MEMORY_ROWS: usize = 8;
+const HELP: &str = "s step  n step over  c continue  b toggle breakpoint at PC  \
+                    :b/:d/:m <hex> break/delete/memory  q quit";
+
+struct DebuggerUi {
+    cpu: Cpu,
+    debugger: Debugger,
+    memory_start: u16,
+    /// The command being typed after `:`, if any.
+    command: Option<String>,
+    status: String,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    let [_, rom_path] = args.as_slice() else {
+        eprintln!("Usage: gabalah-dbg <rom file>");
+        std::process::exit(1);
+    };
+    let rom = rom_loader::load_rom_from_path(Path::new(rom_path), None)?;
+    let cpu = Cpu::from_rom(rom)?;
+
+    let mut ui = DebuggerUi {
+        cpu,
+        debugger: Debugger::new(),
+        memory_start: 0xC000,
+        command: None,
+        status: String::from("ready"),
+    };
+    let mut terminal = ratatui::init();
+    let result = ui.run(&mut terminal);
+    ratatui::restore();
+    Ok(result?)
+}
+
+impl DebuggerUi {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(command) = &mut self.command {
+                match key.code {
+                    KeyCode::Enter => {
+                        let command = self.command.take().unwrap_or_default();
+                        self.status = self.run_command(&command);
+                    }
+                    KeyCode::Esc => self.command = None,
+                    KeyCode::Backspace => {
+                        command.pop();
+                    }
+                    KeyCode::Char(ch) => command.push(ch),
+                    _ => {}
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('s') => {
+                    let reason = self.debugger.step(&mut self.cpu);
+                    self.report(reason);
+                }
+                KeyCode::Char('n') => {
+                    let reason = self.debugger.step_over(&mut self.cpu, STEP_OVER_CYCLES);
+                    self.report(reason);
+                }
+                KeyCode::Char('c') => {
+                    let reason = self
+                        .debugger
+                        .run_until_break(&mut self.cpu, CONTINUE_CYCLES);
+                    self.report(reason);
+                }
+                KeyCode::Char('b') => {
+                    let pc = self.cpu.registers.pc;
+                    self.status = self.toggle_breakpoint(pc);
+                }
+                KeyCode::Char(':') => self.command = Some(String::new()),
+                KeyCode::PageUp => self.memory_start = self.memory_start.wrapping_sub(0x80),
+                KeyCode::PageDown => self.memory_start = self.memory_start.wrapping_add(0x80),
+                _ => {}
+            }
+        }
+    }
+
+    fn report(&mut self, reason: StopReason) {
+        self.status = match reason {
+            StopReason::Step => format!("stepped to {:04X}", self.cpu.registers.pc),
+            StopReason::Breakpoint(address) => format!("breakpoint at {address:04X}"),
+            StopReason::CycleLimit => format!("paused at {:04X}", self.cpu.registers.pc),
+        };
+    }
+
+    fn toggle_breakpoint(&mut self, address: u16) -> String {
+        if self.debugger.remove_breakpoint(address) {
+            format!("removed breakpoint at {address:04X}")
+        } else {
+            self.debugger.add_breakpoint(address);
+            format!("breakpoint set at {address:04X}")
+        }
+    }
+
+    /// Runs a `:` command: `b <addr>`, `d <addr>` or `m <addr>`, addresses in hex.
+    fn run_command(&mut self, command: &str) -> String {
+        let mut parts = command.split_whitespace();
+        let (Some(name), Some(raw_address), None) = (parts.next(), parts.next(), parts.next())
+        else {
+            return format!("expected `<command> <hex address>`, got `{command}`");
+        };
+        let Ok(address) = u16::from_str_radix(raw_address.trim_start_matches("0x"), 16) else {
+            return format!("invalid address `{raw_address}`");
+        };
+        match name {
+            "b" | "break" => {
+                self.debugger.add_breakpoint(address);
+                format!("breakpoint set at {address:04X}")
+            }
+            "d" | "delete" => {
+                if self.debugger.remove_breakpoint(address) {
+                    format!("removed breakpoint at {address:04X}")
+                } else {
+                    format!("no breakpoint at {address:04X}")
+                }
+            }
+            "m" | "mem" => {
+                self.memory_start = address & 0xFFF0;
+                format!("memory at {:04X}", self.memory_start)
+            }
+            _ => format!("unknown command `{name}`"),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [top, memory, status] = Layout::vertical([
+            Constraint::Min(DISASSEMBLY_LINES as u16 + 2),
+            Constraint::Length(MEMORY_ROWS as u16 + 2),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+        let [registers, disassembly] =
+            Layout::horizontal([Constraint::Length(24), Constraint::Min(40)]).areas(top);
+
+        frame.render_widget(
+            Paragraph::new(self.register_lines()).block(Block::bordered().title("Registers")),
+            registers,
+        );
+        frame.render_widget(
+            Paragraph::new(self.disassembly_lines()).block(Block::bordered().title("Disassembly")),
+            disassembly,
+        );
+
+        let bytes: Vec<u8> = (0..MEMORY_ROWS * 16)
+            .map(|offset| {
+                self.cpu
+                    .peek(Addr(self.memory_start.wrapping_add(offset as u16)))
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(debugger::hex_dump(self.memory_start, &bytes))
+                .block(Block::bordered().title("Memory")),
+            memory,
+        );
+
+        let prompt = match &self.command {
+            Some(command) => format!(":{command}"),
+            None => self.status.clone(),
+        };
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(prompt), Line::from(HELP).dim()]),
+            status,
+        );
+    }
+
+    fn register_lines(&self) -> Vec<Line<'static>> {
+        let r = &self.cpu.registers;
+        let flags = r.flags();
+        let flag = |set: bool, name: char| if set { name } else { '-' };
+        vec![
+            Line::from(format!("AF {:04X}", r.af())),
+            Line::from(format!("BC {:04X}", r.bc())),
+            Line::from(format!("DE {:04X}", r.de())),
+            Line::from(format!("HL {:04X}", r.hl())),
+            Line::from(format!("SP {:04X}", r.sp)),
+            Line::from(format!("PC {:04X}", r.pc)),
+            Line::from(format!(
+                "F  {}{}{}{}",
+                flag(flags.zero, 'Z'),
+                flag(flags.subtraction, 'N'),
+                flag(flags.half_carry, 'H'),
+                flag(flags.carry, 'C')
+            )),
+            Line::from(format!("IME {}", if r.ime { "on" } else { "off" })),
+            Line::from(if self.cpu.halted { "HALTED" } else { "" }),
+        ]
+    }
+
+    fn disassembly_lines(&self) -> Vec<Line<'static>> {
+        let pc = self.cpu.registers.pc;
+        debugger::disassemble(&self.cpu, pc, DISASSEMBLY_LINES)
+            .into_iter()
+            .map(|(address, instruction)| {
+                let bytes: Vec<String> = (0..instruction.bytes as u16)
+                    .map(|offset| {
+                        format!("{:02X}", self.cpu.peek(Addr(address.wrapping_add(offset))))
+                    })
+                    .collect();
+                let marker = if self.debugger.has_breakpoint(address) {
+                    '*'
+                } else {
+                    ' '
+                };
+                let line = Line::from(format!(
+                    "{marker}{address:04X}  {:<9} {:?}",
+                    bytes.join(" "),
+                    instruction.mnemonic
+                ));
+                if address == pc {
+                    line.style(Style::new().reversed())
+                } else {
+                    line
+                }
+            })
+            .collect()
+    }
+}
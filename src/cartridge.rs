@@ -1,5 +1,9 @@
-use std::fmt;
-use std::str;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str;
 
 use log::warn;
 
@@ -41,36 +45,38 @@ impl fmt::Display for CgbMode {
     }
 }
 
+/// Discriminants are the header byte at 0x0147.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
 pub enum CartridgeType {
-    Rom,
-    Mbc1,
-    Mbc1Ram,
-    Mbc1RamBattery,
-    Mbc2,
-    Mbc2Battery,
-    RomRam11,
-    RomRamBattery11,
-    Mmm01,
-    Mmm01Ram,
-    Mmm01RamBattery,
-    Mbc3TimerBattery,
-    Mbc3TimerRamBattery12,
-    Mbc3,
-    Mbc3Ram12,
-    Mbc3RamBattery12,
-    Mbc5,
-    Mbc5Ram,
-    Mbc5RamBattery,
-    Mbc5Rumble,
-    Mbc5RumbleRam,
-    Mbc5RumbleRamBattery,
-    Mbc6,
-    Mbc7SensorRumbleRamBattery,
-    PocketCamera,
-    BandaiTama5,
-    HuC3,
-    HuC1RamBattery,
+    Rom = 0x00,
+    Mbc1 = 0x01,
+    Mbc1Ram = 0x02,
+    Mbc1RamBattery = 0x03,
+    Mbc2 = 0x05,
+    Mbc2Battery = 0x06,
+    RomRam11 = 0x08,
+    RomRamBattery11 = 0x09,
+    Mmm01 = 0x0B,
+    Mmm01Ram = 0x0C,
+    Mmm01RamBattery = 0x0D,
+    Mbc3TimerBattery = 0x0F,
+    Mbc3TimerRamBattery12 = 0x10,
+    Mbc3 = 0x11,
+    Mbc3Ram12 = 0x12,
+    Mbc3RamBattery12 = 0x13,
+    Mbc5 = 0x19,
+    Mbc5Ram = 0x1A,
+    Mbc5RamBattery = 0x1B,
+    Mbc5Rumble = 0x1C,
+    Mbc5RumbleRam = 0x1D,
+    Mbc5RumbleRamBattery = 0x1E,
+    Mbc6 = 0x20,
+    Mbc7SensorRumbleRamBattery = 0x22,
+    PocketCamera = 0xFC,
+    BandaiTama5 = 0xFD,
+    HuC3 = 0xFE,
+    HuC1RamBattery = 0xFF,
 }
 
 impl fmt::Display for CartridgeType {
@@ -149,9 +155,7 @@ impl CartridgeHeader {
             );
         }
 
-        let title = String::from_utf8_lossy(&buffer[0x0134..0x0143])
-            .trim_end_matches('\0')
-            .to_string();
+        let title = Self::read_title(&buffer[0x0134..0x0143]);
         let cgb_mode = CgbMode::from_byte(buffer[0x0143])
             .ok_or_else(|| format!("unsupported CGB flag: 0x{:02X}", buffer[0x0143]))?;
         let licensee = Self::read_licensee(buffer)?;
@@ -256,6 +260,23 @@ impl CartridgeHeader {
         })
     }
 
+    /// The title ends at the first NUL; bytes outside printable ASCII show as '?'.
+    fn read_title(bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '?'
+                }
+            })
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
     fn read_licensee(buffer: &[u8]) -> Result<String, String> {
         let licensee = match buffer[0x14B] {
             0x00 => "None",
@@ -487,6 +508,69 @@ impl CartridgeHeader {
     }
 }
 
+/// Memory bank controllers this crate emulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mbc {
+    /// No controller: at most 32 KiB of ROM, optionally with 8 KiB of RAM.
+    None,
+    Mbc1,
+}
+
+/// Why a cartridge cannot be run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartridgeError {
+    /// The header names a memory bank controller that is not emulated; carries the cartridge
+    /// type byte.
+    UnsupportedMbc(u8),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CartridgeError::UnsupportedMbc(code) => write!(
+                f,
+                "unsupported memory bank controller (cartridge type 0x{code:02X})"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for CartridgeError {}
+
+/// Maps a header cartridge type to the controller that runs it. Fails for known controllers
+/// that are not emulated (MBC2/3/5/6/7, MMM01, HuC1/HuC3, TAMA5, GB Camera), which would
+/// otherwise run with the wrong banking.
+pub fn mbc_from_type(cartridge_type: CartridgeType) -> Result<Mbc, CartridgeError> {
+    type CT = CartridgeType;
+
+    match cartridge_type {
+        CT::Rom | CT::RomRam11 | CT::RomRamBattery11 => Ok(Mbc::None),
+        CT::Mbc1 | CT::Mbc1Ram | CT::Mbc1RamBattery => Ok(Mbc::Mbc1),
+        CT::Mbc2
+        | CT::Mbc2Battery
+        | CT::Mmm01
+        | CT::Mmm01Ram
+        | CT::Mmm01RamBattery
+        | CT::Mbc3TimerBattery
+        | CT::Mbc3TimerRamBattery12
+        | CT::Mbc3
+        | CT::Mbc3Ram12
+        | CT::Mbc3RamBattery12
+        | CT::Mbc5
+        | CT::Mbc5Ram
+        | CT::Mbc5RamBattery
+        | CT::Mbc5Rumble
+        | CT::Mbc5RumbleRam
+        | CT::Mbc5RumbleRamBattery
+        | CT::Mbc6
+        | CT::Mbc7SensorRumbleRamBattery
+        | CT::PocketCamera
+        | CT::BandaiTama5
+        | CT::HuC3
+        | CT::HuC1RamBattery => Err(CartridgeError::UnsupportedMbc(cartridge_type as u8)),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Cartridge {
     rom: Vec<u8>,
@@ -530,11 +614,17 @@ impl Cartridge {
             }
         };
 
-        let mapper = match header.as_ref().map(|h| h.cartridge_type) {
-            Some(CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery) => {
-                MapperState::Mbc1(Mbc1State::default())
+        let mbc = match header.as_ref().map(|h| mbc_from_type(h.cartridge_type)) {
+            Some(Ok(mbc)) => mbc,
+            Some(Err(err)) => {
+                warn!("{err}; running without banking");
+                Mbc::None
             }
-            _ => MapperState::RomOnly,
+            None => Mbc::None,
+        };
+        let mapper = match mbc {
+            Mbc::None => MapperState::RomOnly,
+            Mbc::Mbc1 => MapperState::Mbc1(Mbc1State::default()),
         };
 
         Self {
@@ -549,9 +639,42 @@ impl Cartridge {
         self.header.as_ref()
     }
 
-    pub fn copy_visible_windows_into(&self, fixed: &mut [u8], switchable: &mut [u8]) {
-        self.copy_bank_into(self.fixed_bank(), fixed);
-        self.copy_bank_into(self.switchable_bank(), switchable);
+    /// The game title from the header, or "" if the header could not be parsed.
+    pub fn title(&self) -> &str {
+        self.header
+            .as_ref()
+            .map_or("", |header| header.title.as_str())
+    }
+
+    pub fn cgb_flag(&self) -> Option<CgbMode> {
+        self.header.as_ref().map(|header| header.cgb_mode)
+    }
+
+    /// The 16-bit sum of every ROM byte except the stored global checksum at 0x014E-0x014F.
+    pub fn global_checksum(&self) -> u16 {
+        self.rom
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != 0x014E && index != 0x014F)
+            .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(u16::from(byte)))
+    }
+
+    /// True when `global_checksum` matches the header's stored value. Real hardware never
+    /// checks it, so a mismatch only hints at a corrupt or patched image.
+    pub fn verify_global_checksum(&self) -> bool {
+        self.header
+            .as_ref()
+            .is_some_and(|header| header.global_checksum == self.global_checksum())
+    }
+
+    /// Number of 16 KiB ROM banks, taken from the header or else from the image size.
+    pub fn rom_banks(&self) -> usize {
+        self.rom_bank_count()
+    }
+
+    /// Number of 8 KiB external RAM banks.
+    pub fn ram_banks(&self) -> usize {
+        self.external_ram.len() / EXTERNAL_RAM_BANK_SIZE
     }
 
     pub fn read_byte(&self, address: u16) -> u8 {
@@ -630,17 +753,6 @@ impl Cartridge {
         self.rom.get(index).copied().unwrap_or(0xFF)
     }
 
-    fn copy_bank_into(&self, bank: usize, dst: &mut [u8]) {
-        dst.fill(0xFF);
-        let src_start = bank.saturating_mul(ROM_BANK_SIZE);
-        if src_start >= self.rom.len() {
-            return;
-        }
-        let src_end = (src_start + dst.len()).min(self.rom.len());
-        let src = &self.rom[src_start..src_end];
-        dst[..src.len()].copy_from_slice(src);
-    }
-
     fn fixed_bank(&self) -> usize {
         let bank_count = self.rom_bank_count();
         match self.mapper {
@@ -0,0 +1,158 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A Game Genie code: replaces the ROM byte at `address` with `value`, only while the original
+/// byte equals `compare` when one is given (so the patch does not hit other banks).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenieCode {
+    pub address: u16,
+    pub value: u8,
+    pub compare: Option<u8>,
+}
+
+impl GenieCode {
+    /// Parses `ABC-DEF` or `ABC-DEF-GHI` (dashes optional). AB is the new value and FCDE the
+    /// address with F inverted; GI is the compare byte XORed with 0xBA and rotated left by two.
+    /// H is not part of the patch.
+    pub fn parse(code: &str) -> Result<GenieCode, String> {
+        let digits = hex_digits(code)?;
+        if digits.len() != 6 && digits.len() != 9 {
+            return Err(format!(
+                "Game Genie code must have 6 or 9 hex digits: {code}"
+            ));
+        }
+        let value = digits[0] << 4 | digits[1];
+        let address = u16::from(digits[5] ^ 0x0F) << 12
+            | u16::from(digits[2]) << 8
+            | u16::from(digits[3]) << 4
+            | u16::from(digits[4]);
+        if address > 0x7FFF {
+            return Err(format!("Game Genie code patches outside ROM: {code}"));
+        }
+        let compare =
+            (digits.len() == 9).then(|| (digits[6] << 4 | digits[8]).rotate_right(2) ^ 0xBA);
+        Ok(GenieCode {
+            address,
+            value,
+            compare,
+        })
+    }
+
+    /// The byte read at `address` with this patch applied over `original`.
+    pub fn apply(&self, address: u16, original: u8) -> u8 {
+        if address == self.address && self.compare.is_none_or(|compare| compare == original) {
+            self.value
+        } else {
+            original
+        }
+    }
+}
+
+/// A GameShark code: writes `value` to the RAM at `address` once per frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharkCode {
+    pub address: u16,
+    pub value: u8,
+}
+
+impl SharkCode {
+    /// Parses `TTVVLLHH`: type TT (0x01, or 0x80-0x87 for a WRAM bank on CGB, treated alike),
+    /// value VV and the little-endian address HHLL.
+    pub fn parse(code: &str) -> Result<SharkCode, String> {
+        let digits = hex_digits(code)?;
+        if digits.len() != 8 {
+            return Err(format!("GameShark code must have 8 hex digits: {code}"));
+        }
+        let bytes: Vec<u8> = digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect();
+        if bytes[0] != 0x01 && !(0x80..=0x87).contains(&bytes[0]) {
+            return Err(format!(
+                "unsupported GameShark code type 0x{:02X}: {code}",
+                bytes[0]
+            ));
+        }
+        Ok(SharkCode {
+            address: u16::from_le_bytes([bytes[2], bytes[3]]),
+            value: bytes[1],
+        })
+    }
+}
+
+/// The hex digits of `code` as nibbles, skipping dashes.
+fn hex_digits(code: &str) -> Result<Vec<u8>, String> {
+    code.chars()
+        .filter(|&ch| ch != '-')
+        .map(|ch| {
+            ch.to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or_else(|| format!("invalid hex digit '{ch}' in code: {code}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn genie_code_splits_into_address_value_and_compare() {
+        let code = GenieCode::parse("00A-17B-C49").unwrap();
+        assert_eq!(
+            code,
+            GenieCode {
+                address: 0x4A17,
+                value: 0x00,
+                compare: Some(0xC8),
+            }
+        );
+        assert_eq!(
+            GenieCode::parse("00A17BC49"),
+            Ok(code),
+            "dashes are optional"
+        );
+    }
+
+    #[test]
+    fn genie_code_without_compare_always_patches() {
+        let code = GenieCode::parse("3EF-0BE").unwrap();
+        assert_eq!(code.address, 0x1F0B);
+        assert_eq!(code.value, 0x3E);
+        assert_eq!(code.compare, None);
+        assert_eq!(code.apply(0x1F0B, 0x12), 0x3E);
+        assert_eq!(code.apply(0x1F0C, 0x12), 0x12);
+    }
+
+    #[test]
+    fn genie_code_compare_byte_guards_the_patch() {
+        let code = GenieCode::parse("00A-17B-C49").unwrap();
+        assert_eq!(code.apply(0x4A17, 0xC8), 0x00);
+        assert_eq!(code.apply(0x4A17, 0xC9), 0xC9);
+    }
+
+    #[test]
+    fn genie_code_rejects_bad_input() {
+        assert!(GenieCode::parse("00A-17B-C4").is_err());
+        assert!(GenieCode::parse("00A-17G").is_err());
+        // F=7 inverts to 8: 0x8A17 is VRAM.
+        assert!(GenieCode::parse("00A-177").is_err());
+    }
+
+    #[test]
+    fn shark_code_splits_into_address_and_value() {
+        assert_eq!(
+            SharkCode::parse("010238CD"),
+            Ok(SharkCode {
+                address: 0xCD38,
+                value: 0x02,
+            })
+        );
+        assert!(SharkCode::parse("020238CD").is_err());
+        assert!(SharkCode::parse("010238C").is_err());
+    }
+}
@@ -43,6 +43,7 @@ struct AppConfig {
 #[derive(Debug, Deserialize, Default)]
 struct WindowConfig {
     scale: Option<f64>,
+    frame_blend: Option<f32>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -70,7 +71,17 @@ struct HotkeyConfig {
     reload_graphics_config: Option<String>,
     previous_shader: Option<String>,
     next_shader: Option<String>,
+    next_palette: Option<String>,
     debug_frame_dump: Option<String>,
+    toggle_background: Option<String>,
+    toggle_window: Option<String>,
+    toggle_sprites: Option<String>,
+    screenshot: Option<String>,
+    pause: Option<String>,
+    frame_step: Option<String>,
+    fast_forward: Option<String>,
+    turbo: Option<String>,
+    cycle_speed: Option<String>,
     exit: Option<String>,
 }
 
@@ -106,7 +117,17 @@ pub struct HotkeyBindings {
     pub reload_graphics_config: KeyCode,
     pub previous_shader: KeyCode,
     pub next_shader: KeyCode,
+    pub next_palette: KeyCode,
     pub debug_frame_dump: KeyCode,
+    pub toggle_background: KeyCode,
+    pub toggle_window: KeyCode,
+    pub toggle_sprites: KeyCode,
+    pub screenshot: KeyCode,
+    pub pause: KeyCode,
+    pub frame_step: KeyCode,
+    pub fast_forward: KeyCode,
+    pub turbo: KeyCode,
+    pub cycle_speed: KeyCode,
     pub exit: KeyCode,
 }
 
@@ -116,7 +137,17 @@ impl Default for HotkeyBindings {
             reload_graphics_config: KeyCode::KeyR,
             previous_shader: KeyCode::KeyQ,
             next_shader: KeyCode::KeyE,
+            next_palette: KeyCode::KeyP,
             debug_frame_dump: KeyCode::F9,
+            toggle_background: KeyCode::F1,
+            toggle_window: KeyCode::F2,
+            toggle_sprites: KeyCode::F3,
+            screenshot: KeyCode::F12,
+            pause: KeyCode::F6,
+            frame_step: KeyCode::Space,
+            fast_forward: KeyCode::KeyF,
+            turbo: KeyCode::Tab,
+            cycle_speed: KeyCode::F7,
             exit: KeyCode::Escape,
         }
     }
@@ -159,13 +190,25 @@ impl Default for DebugDumpSettings {
     }
 }
 
+/// Window options from the `window` section of the config.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSettings {
+    pub scale: f64,
+    /// Previous-frame weight for LCD ghosting; 0.0 disables blending.
+    pub frame_blend: f32,
+}
+
 pub fn load_graphics_settings(
 ) -> Result<(GraphicsBackendKind, GraphicsOptions), Box<dyn std::error::Error>> {
     load_graphics_settings_from_path(Path::new(CONFIG_FILE))
 }
 
-pub fn load_window_scale() -> Result<f64, Box<dyn std::error::Error>> {
-    load_window_scale_from_path(Path::new(CONFIG_FILE))
+pub fn load_window_settings() -> Result<WindowSettings, Box<dyn std::error::Error>> {
+    let path = Path::new(CONFIG_FILE);
+    Ok(WindowSettings {
+        scale: load_window_scale_from_path(path)?,
+        frame_blend: load_frame_blend_from_path(path)?,
+    })
 }
 
 pub fn load_controls() -> Result<Controls, Box<dyn std::error::Error>> {
@@ -254,6 +297,22 @@ fn load_window_scale_from_path(path: &Path) -> Result<f64, Box<dyn std::error::E
     }
 }
 
+fn load_frame_blend_from_path(path: &Path) -> Result<f32, Box<dyn std::error::Error>> {
+    let cfg = load_config(path)?;
+    let config_name = path.display().to_string();
+
+    match cfg.window.frame_blend {
+        Some(weight) if (0.0..=1.0).contains(&weight) => Ok(weight),
+        Some(weight) => Err(Box::new(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid window.frame_blend in {config_name}: expected a number from 0 to 1, got {weight}"
+            ),
+        ))),
+        None => Ok(0.0),
+    }
+}
+
 fn load_controls_from_path(path: &Path) -> Result<Controls, Box<dyn std::error::Error>> {
     let cfg = load_config(path)?;
     let config_name = path.display().to_string();
@@ -330,12 +389,72 @@ fn load_controls_from_path(path: &Path) -> Result<Controls, Box<dyn std::error::
                 "controls.hotkeys.next_shader",
                 &config_name,
             )?,
+            next_palette: parse_key_binding(
+                cfg.controls.hotkeys.next_palette.as_deref(),
+                hotkey_defaults.next_palette,
+                "controls.hotkeys.next_palette",
+                &config_name,
+            )?,
             debug_frame_dump: parse_key_binding(
                 cfg.controls.hotkeys.debug_frame_dump.as_deref(),
                 hotkey_defaults.debug_frame_dump,
                 "controls.hotkeys.debug_frame_dump",
                 &config_name,
             )?,
+            toggle_background: parse_key_binding(
+                cfg.controls.hotkeys.toggle_background.as_deref(),
+                hotkey_defaults.toggle_background,
+                "controls.hotkeys.toggle_background",
+                &config_name,
+            )?,
+            toggle_window: parse_key_binding(
+                cfg.controls.hotkeys.toggle_window.as_deref(),
+                hotkey_defaults.toggle_window,
+                "controls.hotkeys.toggle_window",
+                &config_name,
+            )?,
+            toggle_sprites: parse_key_binding(
+                cfg.controls.hotkeys.toggle_sprites.as_deref(),
+                hotkey_defaults.toggle_sprites,
+                "controls.hotkeys.toggle_sprites",
+                &config_name,
+            )?,
+            screenshot: parse_key_binding(
+                cfg.controls.hotkeys.screenshot.as_deref(),
+                hotkey_defaults.screenshot,
+                "controls.hotkeys.screenshot",
+                &config_name,
+            )?,
+            pause: parse_key_binding(
+                cfg.controls.hotkeys.pause.as_deref(),
+                hotkey_defaults.pause,
+                "controls.hotkeys.pause",
+                &config_name,
+            )?,
+            frame_step: parse_key_binding(
+                cfg.controls.hotkeys.frame_step.as_deref(),
+                hotkey_defaults.frame_step,
+                "controls.hotkeys.frame_step",
+                &config_name,
+            )?,
+            fast_forward: parse_key_binding(
+                cfg.controls.hotkeys.fast_forward.as_deref(),
+                hotkey_defaults.fast_forward,
+                "controls.hotkeys.fast_forward",
+                &config_name,
+            )?,
+            turbo: parse_key_binding(
+                cfg.controls.hotkeys.turbo.as_deref(),
+                hotkey_defaults.turbo,
+                "controls.hotkeys.turbo",
+                &config_name,
+            )?,
+            cycle_speed: parse_key_binding(
+                cfg.controls.hotkeys.cycle_speed.as_deref(),
+                hotkey_defaults.cycle_speed,
+                "controls.hotkeys.cycle_speed",
+                &config_name,
+            )?,
             exit: parse_key_binding(
                 cfg.controls.hotkeys.exit.as_deref(),
                 hotkey_defaults.exit,
@@ -558,12 +677,23 @@ mod tests {
         let window_scale = load_window_scale_from_path(&path)
             .expect("missing config should provide default scale");
         assert!((window_scale - DEFAULT_WINDOW_SCALE).abs() < f64::EPSILON);
+        assert_eq!(
+            load_frame_blend_from_path(&path).expect("missing config should disable blending"),
+            0.0
+        );
         let controls =
             load_controls_from_path(&path).expect("missing config should provide default controls");
         assert_eq!(controls.joypad.up, KeyCode::ArrowUp);
         assert_eq!(controls.joypad.a, KeyCode::KeyZ);
         assert_eq!(controls.hotkeys.exit, KeyCode::Escape);
         assert_eq!(controls.hotkeys.debug_frame_dump, KeyCode::F9);
+        assert_eq!(controls.hotkeys.toggle_background, KeyCode::F1);
+        assert_eq!(controls.hotkeys.toggle_window, KeyCode::F2);
+        assert_eq!(controls.hotkeys.toggle_sprites, KeyCode::F3);
+        assert_eq!(controls.hotkeys.screenshot, KeyCode::F12);
+        assert_eq!(controls.hotkeys.pause, KeyCode::F6);
+        assert_eq!(controls.hotkeys.frame_step, KeyCode::Space);
+        assert_eq!(controls.hotkeys.cycle_speed, KeyCode::F7);
         let defaults = ShaderOptions::default();
         assert_eq!(options.shader.scanline_strength, defaults.scanline_strength);
         assert_eq!(options.shader.curvature, defaults.curvature);
@@ -718,6 +848,20 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn parses_and_validates_frame_blend() {
+        let path = write_temp_config(r#"{ "window": { "frame_blend": 0.5 } }"#);
+        let weight = load_frame_blend_from_path(&path).expect("valid frame blend should parse");
+        assert_eq!(weight, 0.5);
+        let _ = fs::remove_file(path);
+
+        let path = write_temp_config(r#"{ "window": { "frame_blend": 1.5 } }"#);
+        let err = load_frame_blend_from_path(&path)
+            .expect_err("frame blend above 1 should return an error");
+        assert!(err.to_string().contains("Invalid window.frame_blend"));
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn rejects_invalid_window_scale() {
         let path = write_temp_config(
@@ -756,7 +900,10 @@ mod tests {
                         "reload_graphics_config": "f5",
                         "previous_shader": "1",
                         "next_shader": "2",
+                        "next_palette": "3",
                         "debug_frame_dump": "f8",
+                        "fast_forward": "f",
+                        "turbo": "space",
                         "exit": "esc"
                     }
                 }
@@ -775,7 +922,10 @@ mod tests {
         assert_eq!(controls.hotkeys.reload_graphics_config, KeyCode::F5);
         assert_eq!(controls.hotkeys.previous_shader, KeyCode::Digit1);
         assert_eq!(controls.hotkeys.next_shader, KeyCode::Digit2);
+        assert_eq!(controls.hotkeys.next_palette, KeyCode::Digit3);
         assert_eq!(controls.hotkeys.debug_frame_dump, KeyCode::F8);
+        assert_eq!(controls.hotkeys.fast_forward, KeyCode::KeyF);
+        assert_eq!(controls.hotkeys.turbo, KeyCode::Space);
         assert_eq!(controls.hotkeys.exit, KeyCode::Escape);
 
         let _ = fs::remove_file(path);
@@ -114,6 +114,16 @@ pub fn adc8(value1: u8, value2: u8, flags: &mut u8) -> u8 {
     result
 }
 
+/// Flags for ADD SP,e8 and LD HL,SP+e8: Z and N clear, H and C from the unsigned addition of
+/// SP's low byte and the raw immediate byte, whatever the sign of the offset.
+pub fn add_sp_flags(sp: u16, e: u8, flags: &mut u8) {
+    let low = sp as u8;
+    flags.set_zero(false);
+    flags.set_subtraction(false);
+    flags.set_half_carry((low & 0x0F) + (e & 0x0F) > 0x0F);
+    flags.set_carry((low as u16) + (e as u16) > 0xFF);
+}
+
 pub fn sub8(value1: u8, value2: u8, flags: &mut u8) -> u8 {
     let result = value1.wrapping_sub(value2);
     flags.set_zero(result == 0);
@@ -173,6 +183,51 @@ pub fn rr(value: u8, flags: &mut u8) -> u8 {
     result
 }
 
+pub fn sla(value: u8, flags: &mut u8) -> u8 {
+    let carry = value & 0x80 != 0;
+    let result = value << 1;
+    flags.set_zero(result == 0);
+    flags.set_subtraction(false);
+    flags.set_half_carry(false);
+    flags.set_carry(carry);
+    result
+}
+
+pub fn sra(value: u8, flags: &mut u8) -> u8 {
+    let carry = value & 0x01 != 0;
+    let result = (value >> 1) | (value & 0x80);
+    flags.set_zero(result == 0);
+    flags.set_subtraction(false);
+    flags.set_half_carry(false);
+    flags.set_carry(carry);
+    result
+}
+
+pub fn swap(value: u8, flags: &mut u8) -> u8 {
+    let result = value.rotate_left(4);
+    flags.set_zero(result == 0);
+    flags.set_subtraction(false);
+    flags.set_half_carry(false);
+    flags.set_carry(false);
+    result
+}
+
+pub fn srl(value: u8, flags: &mut u8) -> u8 {
+    let carry = value & 0x01 != 0;
+    let result = value >> 1;
+    flags.set_zero(result == 0);
+    flags.set_subtraction(false);
+    flags.set_half_carry(false);
+    flags.set_carry(carry);
+    result
+}
+
+pub fn bit(bit: u8, value: u8, flags: &mut u8) {
+    flags.set_zero(value & (1 << bit) == 0);
+    flags.set_subtraction(false);
+    flags.set_half_carry(true);
+}
+
 pub fn daa(a: &mut u8, f: &mut u8) {
     if f.subtraction() {
         let mut adjustment: u8 = 0;
@@ -232,3 +287,137 @@ pub fn cp(value1: u8, value2: u8, flags: &mut u8) {
     flags.set_half_carry((value1 & 0x0F) < (value2 & 0x0F));
     flags.set_carry(value1 < value2);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Reference 8-bit add/subtract using wider integers: returns (result, flags).
+    fn reference(a: u8, b: u8, carry_in: bool, subtract: bool) -> (u8, u8) {
+        let (a, b, c) = (a as i32, b as i32, carry_in as i32);
+        let (full, half) = if subtract {
+            (a - b - c, (a & 0x0F) - (b & 0x0F) - c)
+        } else {
+            (a + b + c, (a & 0x0F) + (b & 0x0F) + c)
+        };
+        let result = full.rem_euclid(256) as u8;
+        let mut flags = 0;
+        flags.set_zero(result == 0);
+        flags.set_subtraction(subtract);
+        flags.set_half_carry(!(0..=0x0F).contains(&half));
+        flags.set_carry(!(0..=0xFF).contains(&full));
+        (result, flags)
+    }
+
+    /// Only the upper nibble of F is meaningful; the lower bits are left untouched.
+    fn arbitrary_flags() -> impl Strategy<Value = u8> {
+        any::<u8>().prop_map(|f| f & 0xF0)
+    }
+
+    #[test]
+    fn inc8_sets_half_carry_and_zero_but_keeps_carry() {
+        let mut flags = CARRY_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK;
+        assert_eq!(inc8(0x0F, &mut flags), 0x10);
+        assert_eq!(flags, CARRY_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK);
+
+        assert_eq!(inc8(0xFF, &mut flags), 0x00);
+        assert_eq!(
+            flags,
+            CARRY_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK | ZERO_FLAG_BITMASK
+        );
+    }
+
+    #[test]
+    fn dec8_sets_half_borrow_and_subtraction_but_keeps_carry() {
+        let mut flags = CARRY_FLAG_BITMASK;
+        assert_eq!(dec8(0x10, &mut flags), 0x0F);
+        assert_eq!(
+            flags,
+            CARRY_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK
+        );
+
+        assert_eq!(dec8(0x01, &mut flags), 0x00);
+        assert_eq!(
+            flags,
+            CARRY_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK | ZERO_FLAG_BITMASK
+        );
+    }
+
+    #[test]
+    fn inc16_and_dec16_wrap_without_flags() {
+        assert_eq!(inc16(0xFFFF), 0x0000);
+        assert_eq!(dec16(0x0000), 0xFFFF);
+    }
+
+    #[test]
+    fn add16_carries_from_bits_11_and_15_and_keeps_zero() {
+        let mut flags = ZERO_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK;
+        assert_eq!(add16(0x0FFF, 0x0001, &mut flags), 0x1000);
+        assert_eq!(flags, ZERO_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK);
+
+        assert_eq!(add16(0xFFFF, 0x0001, &mut flags), 0x0000);
+        assert_eq!(
+            flags,
+            ZERO_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK
+        );
+
+        let mut flags = 0;
+        add16(0x8000, 0x8000, &mut flags);
+        assert_eq!(flags, CARRY_FLAG_BITMASK, "zero flag is not set by ADD HL");
+    }
+
+    #[test]
+    fn add_sp_flags_for_positive_offset_from_ffff() {
+        let mut flags = ZERO_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK;
+        add_sp_flags(0xFFFF, 0x01, &mut flags);
+        assert_eq!(flags, HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK);
+    }
+
+    #[test]
+    fn add_sp_flags_for_negative_offset_from_ffff() {
+        let mut flags = 0;
+        add_sp_flags(0xFFFF, 0xFF, &mut flags); // e = -1
+        assert_eq!(flags, HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK);
+    }
+
+    #[test]
+    fn add_sp_flags_ignores_sign_of_offset() {
+        let mut flags = 0;
+        add_sp_flags(0x0000, 0xFF, &mut flags); // e = -1: 0x00 + 0xFF carries nothing
+        assert_eq!(flags, 0);
+
+        add_sp_flags(0x00F8, 0x08, &mut flags);
+        assert_eq!(flags, HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK);
+    }
+
+    proptest! {
+        #[test]
+        fn add8_matches_reference(a: u8, b: u8, f in arbitrary_flags()) {
+            let mut flags = f;
+            let result = add8(a, b, &mut flags);
+            prop_assert_eq!((result, flags), reference(a, b, false, false));
+        }
+
+        #[test]
+        fn adc8_matches_reference(a: u8, b: u8, f in arbitrary_flags()) {
+            let mut flags = f;
+            let result = adc8(a, b, &mut flags);
+            prop_assert_eq!((result, flags), reference(a, b, f.carry(), false));
+        }
+
+        #[test]
+        fn sub8_matches_reference(a: u8, b: u8, f in arbitrary_flags()) {
+            let mut flags = f;
+            let result = sub8(a, b, &mut flags);
+            prop_assert_eq!((result, flags), reference(a, b, false, true));
+        }
+
+        #[test]
+        fn sbc8_matches_reference(a: u8, b: u8, f in arbitrary_flags()) {
+            let mut flags = f;
+            let result = sbc8(a, b, &mut flags);
+            prop_assert_eq!((result, flags), reference(a, b, f.carry(), true));
+        }
+    }
+}
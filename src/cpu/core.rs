@@ -1,24 +1,122 @@
-use std::sync::LazyLock;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use log::{trace, warn};
 
 use super::alu::Flags;
+use super::interrupts::{InterruptFlags, InterruptState};
 use super::ops::{CycleSpec, Instruction};
 use super::{
     alu, map, Mnemonic, CARRY_FLAG_BITMASK, HALF_CARRY_FLAG_BITMASK, SUBTRACTION_FLAG_BITMASK,
 };
-use crate::cartridge::CartridgeHeader;
-use crate::memory::{Addr, Ram, Registers};
+use crate::cartridge::{self, CartridgeHeader};
+use crate::cheats::{GenieCode, SharkCode};
+use crate::memory::{self, io, Addr, Button, Ram, Registers};
+use crate::ppu::{Ppu, CYCLES_PER_FRAME, LINE_CYCLES};
+use crate::renderer::{self, LayerFlags, Palette, ScanlineRegs};
 
 use Mnemonic::*;
 
-static OPCODE_MAP: LazyLock<[Instruction; 256]> = LazyLock::new(map::build_opcode_map);
+const INTERRUPT_SERVICE_CYCLES: usize = 20;
+// Oldest call frames are dropped beyond this depth, e.g. when code discards return addresses
+// with POP.
+const CALL_STACK_LIMIT: usize = 256;
 
 pub struct Cpu {
     memory: Ram,
+    ppu: Ppu,
     pub registers: Registers,
     pub total_cycles: u64,
+    instructions_executed: u64,
     pending_ime: bool,
     halt_bug_armed: bool,
+    frame_ready: bool,
+    /// Return addresses of pending calls, innermost last, when call stack tracking is on.
+    call_stack: Option<VecDeque<u16>>,
+    /// Call frames and mismatched RETs, while the stack balance check is on.
+    stack_check: Option<StackCheck>,
+    /// GameShark codes written to RAM at every VBlank.
+    shark_codes: Vec<SharkCode>,
+    /// Executed unprefixed and CB-prefixed opcodes, when coverage tracking is on.
+    opcode_coverage: Option<([bool; 256], [bool; 256])>,
+    /// Whether a jump from code into VRAM, OAM or I/O logs a warning.
+    region_warnings: bool,
     pub halted: bool,
+    /// Set by STOP; cleared when a button is pressed.
+    pub stopped: bool,
+    /// Wall-clock pacing multiplier for frontends; emulated timing is unaffected.
+    speed: f32,
+}
+
+/// What a single `Cpu::step_debug` did, for interactive debuggers.
+#[derive(Debug, Clone, Copy)]
+pub struct StepResult {
+    /// PC before the step.
+    pub pc: u16,
+    /// The instruction decoded at `pc`; CB-prefixed opcodes decode as the 0xCB table entry.
+    pub instruction: Instruction,
+    /// Cycles consumed, including interrupt dispatch.
+    pub cycles: usize,
+    pub interrupt_serviced: bool,
+    pub frame_completed: bool,
+}
+
+/// A RET that found SP somewhere other than where the matching CALL, RST or interrupt dispatch
+/// left it, e.g. because the subroutine pushed more than it popped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackImbalance {
+    /// Address of the RET, RETI or taken conditional return.
+    pub pc: u16,
+    /// Return address pushed by the matching call.
+    pub return_address: u16,
+    /// SP just after the call pushed its return address.
+    pub expected_sp: u16,
+    /// SP when the return executed.
+    pub actual_sp: u16,
+}
+
+/// State of the stack balance check.
+#[derive(Default)]
+struct StackCheck {
+    /// Return address and SP as each pending call left it, innermost last.
+    frames: VecDeque<(u16, u16)>,
+    imbalances: Vec<StackImbalance>,
+}
+
+/// Returned by `Cpu::run_until` when the cycle cap is reached before the predicate holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout {
+    /// Cycles run before giving up.
+    pub cycles: usize,
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "condition not reached within {} cycles", self.cycles)
+    }
+}
+
+/// The first line of a reference trace that disagrees with the emulated CPU state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceDivergence {
+    /// 1-based line number in the reference trace.
+    pub line: usize,
+    pub expected: String,
+    /// The emulator's state in the same format, from `Cpu::doctor_line`.
+    pub actual: String,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trace diverges at line {}\nexpected: {}\n  actual: {}",
+            self.line, self.expected, self.actual
+        )
+    }
 }
 
 impl Default for Cpu {
@@ -32,20 +130,47 @@ impl Cpu {
     pub fn new() -> Cpu {
         Cpu {
             memory: Ram::new(),
+            ppu: Ppu::new(),
             registers: Registers::new(),
             total_cycles: 0,
+            instructions_executed: 0,
             pending_ime: false,
             halt_bug_armed: false,
+            frame_ready: false,
+            call_stack: None,
+            stack_check: None,
+            shark_codes: Vec::new(),
+            opcode_coverage: None,
+            region_warnings: false,
             halted: false,
+            stopped: false,
+            speed: 1.0,
         }
     }
 
+    /// Creates a CPU with `rom` loaded, e.g. from an embedded `include_bytes!` image.
+    /// Fails if the cartridge header cannot be parsed or names an unsupported controller.
+    pub fn from_rom(rom: Vec<u8>) -> Result<Cpu, String> {
+        let header = CartridgeHeader::from_bytes(&rom)?;
+        cartridge::mbc_from_type(header.cartridge_type).map_err(|err| err.to_string())?;
+        let mut cpu = Cpu::new();
+        cpu.load_rom(rom);
+        Ok(cpu)
+    }
+
     /// Loads a program into memory
     pub fn load_rom(&mut self, rom: Vec<u8>) {
         self.memory.load_rom(rom);
     }
 
-    #[allow(dead_code)]
+    /// Maps a 256-byte boot ROM over 0x0000-0x00FF and points PC at its entry, 0x0000. The boot
+    /// ROM unmaps itself by writing to 0xFF50 as it hands over to the cartridge at 0x0100.
+    pub fn load_boot_rom(&mut self, boot_rom: Vec<u8>) -> Result<(), String> {
+        self.memory.load_boot_rom(boot_rom)?;
+        self.registers.pc = 0x0000;
+        Ok(())
+    }
+
     pub fn cartridge_header(&self) -> Option<&CartridgeHeader> {
         self.memory.cartridge_header()
     }
@@ -66,6 +191,12 @@ impl Cpu {
         self.memory.read_byte(address)
     }
 
+    /// Reads memory for a debugger or memory viewer, bypassing bus behaviour such as PPU-blocked
+    /// VRAM/OAM reads.
+    pub fn peek(&self, address: Addr) -> u8 {
+        self.memory.peek(address)
+    }
+
     pub fn write_byte(&mut self, address: Addr, value: u8) {
         self.memory.write_byte(address, value);
     }
@@ -82,6 +213,7 @@ impl Cpu {
         self.memory.tick(cycles)
     }
 
+    /// The raw bytes at 0x8000-0xFFFF, indexed by `address - RAM_START`; see `Ram::as_slice`.
     pub fn memory_slice(&self) -> &[u8] {
         self.memory.as_slice()
     }
@@ -94,19 +226,23 @@ impl Cpu {
         self.memory.set_stat_raw(stat);
     }
 
-    pub fn set_action_button_pressed(&mut self, bit: u8, pressed: bool) {
-        if pressed {
-            self.memory.action_buttons |= bit;
+    /// Updates the joypad state read through 0xFF00 and requests the joypad interrupt
+    /// when a button goes from released to pressed.
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        let buttons = if button.is_action() {
+            &mut self.memory.action_buttons
         } else {
-            self.memory.action_buttons &= !bit;
-        }
-    }
-
-    pub fn set_direction_button_pressed(&mut self, bit: u8, pressed: bool) {
+            &mut self.memory.direction_buttons
+        };
+        let was_pressed = *buttons & button.mask() != 0;
         if pressed {
-            self.memory.direction_buttons |= bit;
+            *buttons |= button.mask();
         } else {
-            self.memory.direction_buttons &= !bit;
+            *buttons &= !button.mask();
+        }
+        if pressed && !was_pressed {
+            self.memory.raise_if(0x10);
+            self.stopped = false;
         }
     }
 
@@ -114,8 +250,387 @@ impl Cpu {
         &self.memory.serial_output
     }
 
-    /// Executes the next instruction, returning the number of cycles consumed
+    /// Number of instructions fetched and executed by `step()`; idle HALT cycles are not counted.
+    pub fn instructions_executed(&self) -> u64 {
+        self.instructions_executed
+    }
+
+    /// Returns true if the most recent `step()` completed a frame (LY entered VBlank).
+    pub fn frame_ready(&self) -> bool {
+        self.frame_ready
+    }
+
+    /// The full register file, for snapshots and debuggers.
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// Replaces the whole register file at once, e.g. to restore a snapshot taken with
+    /// `registers().clone()`. The low nibble of F is cleared, as it is on hardware.
+    pub fn set_registers(&mut self, mut registers: Registers) {
+        registers.f &= 0xF0;
+        self.registers = registers;
+    }
+
+    /// Sets how fast a frontend should pace emulation relative to real time, e.g. 0.25 for
+    /// quarter speed. Emulated timing is unchanged; non-positive or non-finite values are ignored.
+    pub fn set_speed(&mut self, multiplier: f32) {
+        if multiplier.is_finite() && multiplier > 0.0 {
+            self.speed = multiplier;
+        }
+    }
+
+    /// The wall-clock pacing multiplier set by `set_speed`, 1.0 by default.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Cycles elapsed on the PPU's current scanline.
+    pub fn ppu_line_cycles(&self) -> usize {
+        self.ppu.line_cycles()
+    }
+
+    /// Per-line LCD register latches for rendering the current frame.
+    pub fn scanline_latches(&self) -> [ScanlineRegs; renderer::HEIGHT as usize] {
+        self.ppu.scanline_latches(self.memory.as_slice())
+    }
+
+    /// Turns call stack tracking on or off. Enabling it starts from an empty stack.
+    pub fn set_call_stack_tracking(&mut self, enabled: bool) {
+        self.call_stack = enabled.then(VecDeque::new);
+    }
+
+    /// Return addresses of the CALL/RST instructions and interrupt dispatches that have not
+    /// returned yet, innermost last; empty when call stack tracking is off. Takes `&mut self`
+    /// to lay the ring buffer out contiguously rather than copy it.
+    pub fn call_stack(&mut self) -> &[u16] {
+        self.call_stack
+            .as_mut()
+            .map_or(&[], |stack| stack.make_contiguous())
+    }
+
+    /// Turns the stack balance check on or off. While on, every return is compared with the SP
+    /// its matching call left behind; a mismatch logs a warning and is recorded in
+    /// `stack_imbalances`. Enabling it starts from an empty record.
+    pub fn set_stack_balance_check(&mut self, enabled: bool) {
+        self.stack_check = enabled.then(StackCheck::default);
+    }
+
+    /// Imbalances found since the stack balance check was enabled, oldest first.
+    pub fn stack_imbalances(&self) -> &[StackImbalance] {
+        self.stack_check
+            .as_ref()
+            .map_or(&[], |check| &check.imbalances)
+    }
+
+    /// Parses a Game Genie code (`ABC-DEF` or `ABC-DEF-GHI`) and patches ROM reads with it.
+    pub fn add_genie_code(&mut self, code: &str) -> Result<(), String> {
+        self.memory.add_genie_code(GenieCode::parse(code)?);
+        Ok(())
+    }
+
+    /// Parses a GameShark code (`01VVLLHH`) and writes its value to RAM at every VBlank.
+    pub fn add_shark_code(&mut self, code: &str) -> Result<(), String> {
+        self.shark_codes.push(SharkCode::parse(code)?);
+        Ok(())
+    }
+
+    /// Turns opcode coverage tracking on or off. Enabling it starts from an empty record.
+    pub fn set_opcode_coverage(&mut self, enabled: bool) {
+        self.opcode_coverage = enabled.then_some(([false; 256], [false; 256]));
+    }
+
+    /// Turns warnings for PC leaving ROM or RAM for a region that does not hold code (VRAM,
+    /// OAM, I/O) on or off; usually a sign of a bad jump or a corrupted return address.
+    pub fn set_region_warnings(&mut self, enabled: bool) {
+        self.region_warnings = enabled;
+    }
+
+    /// Which unprefixed opcodes have run since coverage tracking was enabled; all false when
+    /// it is off. A CB-prefixed instruction marks 0xCB here and its opcode in
+    /// `executed_cb_opcodes`.
+    pub fn executed_opcodes(&self) -> [bool; 256] {
+        self.opcode_coverage
+            .map_or([false; 256], |(opcodes, _)| opcodes)
+    }
+
+    /// Which CB-prefixed opcodes have run since coverage tracking was enabled.
+    pub fn executed_cb_opcodes(&self) -> [bool; 256] {
+        self.opcode_coverage
+            .map_or([false; 256], |(_, cb_opcodes)| cb_opcodes)
+    }
+
+    /// Records a call returning to `return_address`. Must run after the address is pushed.
+    fn push_call_frame(&mut self, return_address: u16) {
+        if let Some(stack) = &mut self.call_stack {
+            if stack.len() == CALL_STACK_LIMIT {
+                stack.pop_front();
+            }
+            stack.push_back(return_address);
+        }
+        if let Some(check) = &mut self.stack_check {
+            if check.frames.len() == CALL_STACK_LIMIT {
+                check.frames.pop_front();
+            }
+            check.frames.push_back((return_address, self.registers.sp));
+        }
+    }
+
+    /// Drops the innermost call frame for a return at `pc`, checking SP against it when the
+    /// stack balance check is on. Must run before the return address is popped.
+    fn pop_call_frame(&mut self, pc: u16) {
+        if let Some(stack) = &mut self.call_stack {
+            stack.pop_back();
+        }
+        let Some(check) = &mut self.stack_check else {
+            return;
+        };
+        let Some((return_address, expected_sp)) = check.frames.pop_back() else {
+            return;
+        };
+        let actual_sp = self.registers.sp;
+        if actual_sp != expected_sp {
+            warn!(
+                "stack imbalance: return at {pc:04X} for call returning to \
+                 {return_address:04X} has SP={actual_sp:04X}, expected {expected_sp:04X}"
+            );
+            check.imbalances.push(StackImbalance {
+                pc,
+                return_address,
+                expected_sp,
+                actual_sp,
+            });
+        }
+    }
+
+    /// The CPU state as a Gameboy Doctor log line: registers, SP, PC and the four bytes at PC,
+    /// e.g. `A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02`.
+    pub fn doctor_line(&self) -> String {
+        let r = &self.registers;
+        let pcmem = |offset: u16| self.memory.peek(Addr(r.pc.wrapping_add(offset)));
+        format!(
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} \
+             SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            r.a,
+            r.f,
+            r.b,
+            r.c,
+            r.d,
+            r.e,
+            r.h,
+            r.l,
+            r.sp,
+            r.pc,
+            pcmem(0),
+            pcmem(1),
+            pcmem(2),
+            pcmem(3)
+        )
+    }
+
+    /// Steps through `reference`, a Gameboy Doctor log with the state before each instruction on
+    /// its own line, and returns the first line whose state differs from `doctor_line`. Blank
+    /// lines are skipped; `None` means the whole trace matched.
+    pub fn compare_trace(&mut self, reference: &str) -> Option<TraceDivergence> {
+        let lines = reference
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        for (position, (line, expected)) in lines.enumerate() {
+            if position > 0 {
+                self.step();
+            }
+            let actual = self.doctor_line();
+            if actual != expected {
+                return Some(TraceDivergence {
+                    line,
+                    expected: expected.into(),
+                    actual,
+                });
+            }
+        }
+        None
+    }
+
+    /// Like `compare_trace`, reading the reference log from `trace_path`.
+    #[cfg(feature = "std")]
+    pub fn replay_and_compare(
+        &mut self,
+        trace_path: &std::path::Path,
+    ) -> std::io::Result<Option<TraceDivergence>> {
+        let reference = std::fs::read_to_string(trace_path)?;
+        Ok(self.compare_trace(&reference))
+    }
+
+    /// Runs until the PPU completes a frame (or one frame's worth of cycles with the LCD off)
+    /// and returns the rendered 160x144 RGBA frame.
+    pub fn run_frame(&mut self) -> &[u8] {
+        self.step_frame();
+        self.ppu.frame_buffer()
+    }
+
+    /// Steps until the PPU completes a frame, i.e. up to the next VBlank, and returns the cycles
+    /// consumed. With the LCD off no frame completes, so it stops after one frame's worth of
+    /// cycles instead.
+    pub fn step_frame(&mut self) -> usize {
+        let mut cycles = 0;
+        loop {
+            cycles += self.step();
+            if self.frame_ready {
+                return cycles;
+            }
+            let lcd_on = self.memory.read_byte(Addr(io::LCDC)) & 0x80 != 0;
+            if !lcd_on && cycles >= CYCLES_PER_FRAME {
+                return cycles;
+            }
+        }
+    }
+
+    /// Steps until LY moves on to the next line and returns the cycles consumed, about 456 as
+    /// the last instruction may overrun the line. With the LCD off LY stays at 0, so it stops
+    /// after one line's worth of cycles instead.
+    pub fn step_scanline(&mut self) -> usize {
+        let start_ly = self.memory.read_byte(Addr(io::LY));
+        let mut cycles = 0;
+        loop {
+            cycles += self.step();
+            if self.memory.read_byte(Addr(io::LY)) != start_ly {
+                return cycles;
+            }
+            let lcd_on = self.memory.read_byte(Addr(io::LCDC)) & 0x80 != 0;
+            if !lcd_on && cycles >= LINE_CYCLES {
+                return cycles;
+            }
+        }
+    }
+
+    /// The latest completed 160x144 RGBA frame; it changes when LY enters VBlank.
+    pub fn frame_buffer(&self) -> &[u8] {
+        self.ppu.frame_buffer()
+    }
+
+    /// A stable hash of `frame_buffer`, so tests can pin a rendered frame to a known-good value.
+    pub fn frame_hash(&self) -> u64 {
+        renderer::frame_hash(self.ppu.frame_buffer())
+    }
+
+    /// Sets the colours `run_frame` maps DMG shades to.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.ppu.set_palette(palette);
+    }
+
+    /// Selects which layers the PPU draws, e.g. to debug one layer in isolation.
+    pub fn set_layers(&mut self, layers: LayerFlags) {
+        self.ppu.set_layers(layers);
+    }
+
+    /// Executes the next instruction, advances the PPU and timers by the cycles it took and
+    /// services a pending interrupt. Returns the number of cycles consumed.
     pub fn step(&mut self) -> usize {
+        self.step_and_service().0
+    }
+
+    /// Steps until `predicate` holds (checked before each step) or `max_cycles` have run, e.g. to
+    /// wait in a test harness for a PC, a memory value or serial output without hanging on a
+    /// ROM stuck in a loop.
+    pub fn run_until(
+        &mut self,
+        mut predicate: impl FnMut(&Cpu) -> bool,
+        max_cycles: usize,
+    ) -> Result<(), Timeout> {
+        let mut cycles = 0;
+        while !predicate(self) {
+            if cycles >= max_cycles {
+                return Err(Timeout { cycles });
+            }
+            cycles += self.step();
+        }
+        Ok(())
+    }
+
+    /// Like `step`, but also reports the decoded instruction and whether an interrupt was
+    /// serviced or a frame completed.
+    pub fn step_debug(&mut self) -> StepResult {
+        let pc = self.registers.pc;
+        let instruction = self.peek_instruction();
+        let (cycles, interrupt_serviced) = self.step_and_service();
+        StepResult {
+            pc,
+            instruction,
+            cycles,
+            interrupt_serviced,
+            frame_completed: self.frame_ready,
+        }
+    }
+
+    /// Returns the cycles consumed and whether an interrupt was serviced.
+    fn step_and_service(&mut self) -> (usize, bool) {
+        self.frame_ready = false;
+        // EI takes effect after the instruction that follows it: IME is raised once that
+        // instruction has run (unless it was DI), before the dispatch check below.
+        let ei_delay_elapsed = self.pending_ime;
+        let mut cycles = self.step_instruction();
+        self.tick_peripherals(cycles);
+        if ei_delay_elapsed && self.pending_ime {
+            self.pending_ime = false;
+            self.registers.ime = true;
+        }
+
+        let interrupt_serviced = self.is_interrupt_pending();
+        if interrupt_serviced {
+            let interrupt_cycles = self.service_interrupt();
+            cycles += interrupt_cycles;
+            self.tick_peripherals(interrupt_cycles);
+        }
+        (cycles, interrupt_serviced)
+    }
+
+    fn tick_peripherals(&mut self, cycles: usize) {
+        if self.ppu.step(&mut self.memory, cycles) {
+            self.frame_ready = true;
+            for code in &self.shark_codes {
+                self.memory.write_byte(Addr(code.address), code.value);
+            }
+        }
+        if self.memory.tick(cycles as u32) {
+            self.memory.raise_if(0x04);
+        }
+    }
+
+    fn is_interrupt_pending(&self) -> bool {
+        !self.stopped && self.registers.ime && (self.get_ie() & self.get_if()) != 0
+    }
+
+    fn service_interrupt(&mut self) -> usize {
+        self.halted = false;
+        self.registers.ime = false;
+        let pending = self.get_if() & self.get_ie();
+        for bit in 0..5u8 {
+            if pending & (1 << bit) != 0 {
+                self.clear_if(1 << bit);
+                let vector = 0x0040u16 + (bit as u16) * 8;
+                let sp = self.registers.sp.wrapping_sub(2);
+                self.memory.write_word(Addr(sp), self.registers.pc);
+                self.registers.sp = sp;
+                self.push_call_frame(self.registers.pc);
+                self.registers.pc = vector;
+                self.total_cycles += INTERRUPT_SERVICE_CYCLES as u64;
+                return INTERRUPT_SERVICE_CYCLES;
+            }
+        }
+        0
+    }
+
+    fn step_instruction(&mut self) -> usize {
+        if self.stopped {
+            self.total_cycles += 4;
+            return 4;
+        }
         if self.halted {
             let ie = self.get_ie();
             let ifr = self.get_if();
@@ -127,22 +642,65 @@ impl Cpu {
         }
 
         let opcode = self.memory.read_byte(Addr(self.registers.pc));
+        self.instructions_executed += 1;
         if self.halt_bug_armed {
             // HALT bug: the opcode fetch does not advance PC once, so execute using PC-1.
             self.registers.pc = self.registers.pc.wrapping_sub(1);
             self.halt_bug_armed = false;
         }
 
+        // Trace lines are only formatted when trace logging is enabled for gabalah::cpu, and
+        // compile away entirely with log's `max_level_*` features.
+        let pc = self.registers.pc;
         if opcode == 0xCB {
-            let cb_opcode = self
-                .memory
-                .read_byte(Addr(self.registers.pc.wrapping_add(1)));
-            let cycles = self.execute_cb(cb_opcode);
-            self.total_cycles += cycles as u64;
+            let cb_opcode = self.memory.read_byte(Addr(self.registers.pc).next());
+            if let Some((opcodes, cb_opcodes)) = &mut self.opcode_coverage {
+                opcodes[0xCB] = true;
+                cb_opcodes[cb_opcode as usize] = true;
+            }
+            let cycles = self.execute(&map::cb_opcode_info(cb_opcode));
+            trace!("{pc:04X} CB {cb_opcode:02X} F={:02X}", self.registers.f);
             return cycles;
         }
-        let instruction = OPCODE_MAP[opcode as usize];
-        self.execute(&instruction)
+        if let Some((opcodes, _)) = &mut self.opcode_coverage {
+            opcodes[opcode as usize] = true;
+        }
+        let instruction = map::opcode_info(opcode);
+        let cycles = self.execute(&instruction);
+        trace!(
+            "{pc:04X} {opcode:02X} {:?} F={:02X}",
+            instruction.mnemonic,
+            self.registers.f
+        );
+        if self.region_warnings {
+            let target = memory::region(self.registers.pc);
+            if !target.is_executable() && memory::region(pc).is_executable() {
+                warn!(
+                    "PC entered {target:?} at {:04X} (from {pc:04X})",
+                    self.registers.pc
+                );
+            }
+        }
+        cycles
+    }
+
+    /// Decodes the opcode at PC through the real opcode tables, CB prefix included, and executes
+    /// it. Unlike `step` nothing else advances: no PPU or timer ticks, interrupts or HALT
+    /// handling. Lets tests write opcode bytes instead of constructing `Instruction`s.
+    pub fn execute_opcode_at_pc(&mut self) -> usize {
+        let opcode = self.memory.read_byte(Addr(self.registers.pc));
+        if opcode == 0xCB {
+            let cb_opcode = self.memory.read_byte(Addr(self.registers.pc).next());
+            return self.execute(&map::cb_opcode_info(cb_opcode));
+        }
+        self.execute(&map::opcode_info(opcode))
+    }
+
+    /// Decodes the instruction at PC without executing it.
+    /// CB-prefixed opcodes decode as the 0xCB table entry.
+    pub fn peek_instruction(&self) -> Instruction {
+        let opcode = self.memory.read_byte(Addr(self.registers.pc));
+        map::opcode_info(opcode)
     }
 
     pub fn get_ie(&self) -> u8 {
@@ -153,6 +711,15 @@ impl Cpu {
         self.memory.read_if()
     }
 
+    /// IME, IE and IF decoded per interrupt source, e.g. for a debugger overlay.
+    pub fn interrupt_state(&self) -> InterruptState {
+        InterruptState {
+            ime: self.registers.ime,
+            enabled: InterruptFlags::from_bits(self.get_ie()),
+            requested: InterruptFlags::from_bits(self.get_if()),
+        }
+    }
+
     pub fn raise_if(&mut self, mask: u8) {
         self.memory.raise_if(mask);
     }
@@ -163,6 +730,7 @@ impl Cpu {
 
     /// Executes an instruction, modifying the state of the CPU
     pub fn execute(&mut self, instruction: &Instruction) -> usize {
+        let pc = self.registers.pc;
         let mut new_pc = None;
         let mut conditional_taken = None;
         let if_contents = self.get_if();
@@ -170,11 +738,6 @@ impl Cpu {
         let r = &mut self.registers;
         let m = &mut self.memory;
 
-        if self.pending_ime {
-            self.pending_ime = false;
-            r.ime = true;
-        }
-
         match instruction.mnemonic {
             Nop => (),
             Ld8(dst, src) => {
@@ -192,6 +755,8 @@ impl Cpu {
             }
             Inc16(dst) => {
                 let word = dst.read_word(r, m);
+                #[cfg(feature = "oam-bug")]
+                m.trigger_oam_bug_write(word, self.ppu.line_cycles());
                 let increased = alu::inc16(word);
                 dst.write_word(r, m, increased);
             }
@@ -202,6 +767,8 @@ impl Cpu {
             }
             Dec16(dst) => {
                 let word = dst.read_word(r, m);
+                #[cfg(feature = "oam-bug")]
+                m.trigger_oam_bug_write(word, self.ppu.line_cycles());
                 let decreased = alu::dec16(word);
                 dst.write_word(r, m, decreased);
             }
@@ -241,7 +808,7 @@ impl Cpu {
             Rra => r.a = alu::rr(r.a, &mut r.f),
             Jr(offset) => {
                 let offset = offset.read_byte(r, m) as i8;
-                new_pc = Some((r.pc as i32 + 2 + offset as i32) as u16);
+                new_pc = Some(Addr(r.pc).wrapping_add(2).wrapping_offset(offset as i16).0);
             }
             Jrc(cc, offset) => {
                 conditional_taken = Some(false);
@@ -249,7 +816,7 @@ impl Cpu {
                 if flag == 1 {
                     conditional_taken = Some(true);
                     let offset = offset.read_byte(r, m) as i8;
-                    new_pc = Some((r.pc as i32 + 2 + offset as i32) as u16);
+                    new_pc = Some(Addr(r.pc).wrapping_add(2).wrapping_offset(offset as i16).0);
                 }
             }
             Daa => alu::daa(&mut r.a, &mut r.f),
@@ -291,19 +858,25 @@ impl Cpu {
                 alu::cp(dst_byte, src_byte, &mut r.f);
             }
             Ret => {
-                new_pc = Some(m.read_word(Addr(r.sp)));
-                r.sp = r.sp.wrapping_add(2);
+                self.pop_call_frame(pc);
+                new_pc = Some(self.pop_word());
             }
             Retc(cc) => {
                 conditional_taken = Some(false);
                 let flag = cc.read_byte(r, m);
                 if flag == 1 {
                     conditional_taken = Some(true);
-                    new_pc = Some(m.read_word(Addr(r.sp)));
-                    r.sp = r.sp.wrapping_add(2);
+                    self.pop_call_frame(pc);
+                    new_pc = Some(self.pop_word());
                 }
             }
-            Stop(_op) => (),
+            // STOP is 0x10 followed by a byte that is skipped with it, so PC advances by 2.
+            // Real hardware misbehaves when that byte is not 0x00 ("corrupted STOP"); it is
+            // ignored here. STOP also resets DIV.
+            Stop(_op) => {
+                m.write_byte(Addr(io::DIV), 0);
+                self.stopped = true;
+            }
             Halt => {
                 let pending = (ie_contents & if_contents) != 0;
                 if pending && !r.ime {
@@ -315,12 +888,15 @@ impl Cpu {
                 }
             }
             Reti => {
-                new_pc = Some(m.read_word(Addr(r.sp)));
-                r.sp = r.sp.wrapping_add(2);
-                r.ime = true;
+                self.pop_call_frame(pc);
+                new_pc = Some(self.pop_word());
+                self.registers.ime = true;
             }
             Ei => self.pending_ime = true,
-            Di => r.ime = false,
+            Di => {
+                r.ime = false;
+                self.pending_ime = false;
+            }
             Jp(dst) => {
                 debug_assert!(dst.target_size() == 2);
                 new_pc = Some(dst.read_word(r, m));
@@ -337,9 +913,9 @@ impl Cpu {
             Call(dst) => {
                 debug_assert!(dst.target_size() == 2);
                 let ret = r.pc.wrapping_add(instruction.bytes as u16);
-                m.write_word(Addr(r.sp.wrapping_sub(2)), ret);
-                r.sp = r.sp.wrapping_sub(2);
                 new_pc = Some(dst.read_word(r, m));
+                self.push_word(ret);
+                self.push_call_frame(ret);
             }
             Callc(condition, dst) => {
                 debug_assert!(dst.target_size() == 2);
@@ -348,44 +924,36 @@ impl Cpu {
                 if flag == 1 {
                     conditional_taken = Some(true);
                     let ret = r.pc.wrapping_add(instruction.bytes as u16);
-                    m.write_word(Addr(r.sp.wrapping_sub(2)), ret);
-                    r.sp = r.sp.wrapping_sub(2);
                     new_pc = Some(dst.read_word(r, m));
+                    self.push_word(ret);
+                    self.push_call_frame(ret);
                 }
             }
             Push(src) => {
                 debug_assert!(src.target_size() == 2);
-                m.write_word(Addr(r.sp.wrapping_sub(2)), src.read_word(r, m));
-                r.sp = r.sp.wrapping_sub(2);
+                let value = src.read_word(r, m);
+                self.push_word(value);
             }
             Pop(dst) => {
-                dst.write_word(r, m, m.read_word(Addr(r.sp)));
-                r.sp = r.sp.wrapping_add(2);
+                // POP AF drops the low nibble of F in Registers::set_af.
+                let value = self.pop_word();
+                dst.write_word(&mut self.registers, &mut self.memory, value);
             }
             Rst(dst) => {
                 let ret = r.pc.wrapping_add(instruction.bytes as u16);
-                m.write_byte(Addr(r.sp.wrapping_sub(1)), (ret >> 8) as u8);
-                m.write_byte(Addr(r.sp.wrapping_sub(2)), ret as u8);
-                r.sp = r.sp.wrapping_sub(2);
+                self.push_word(ret);
+                self.push_call_frame(ret);
                 new_pc = Some(dst as u16);
             }
             Ldhl(op) => {
-                let offset = op.read_byte(r, m) as i8;
-                let imm = offset as u8;
-                let result = r.sp.wrapping_add((offset as i16) as u16);
-                r.f = 0;
-                r.f.set_half_carry((r.sp & 0x000F) + ((imm as u16) & 0x000F) > 0x000F);
-                r.f.set_carry((r.sp & 0x00FF) + ((imm as u16) & 0x00FF) > 0x00FF);
-                r.set_hl(result);
+                let e = op.read_byte(r, m);
+                alu::add_sp_flags(r.sp, e, &mut r.f);
+                r.set_hl(Addr(r.sp).wrapping_offset(e as i8 as i16).0);
             }
             AddSp(op) => {
-                let offset = op.read_byte(r, m) as i8;
-                let imm = offset as u8;
-                let result = r.sp.wrapping_add((offset as i16) as u16);
-                r.f = 0;
-                r.f.set_half_carry((r.sp & 0x000F) + ((imm as u16) & 0x000F) > 0x000F);
-                r.f.set_carry((r.sp & 0x00FF) + ((imm as u16) & 0x00FF) > 0x00FF);
-                r.sp = result;
+                let e = op.read_byte(r, m);
+                alu::add_sp_flags(r.sp, e, &mut r.f);
+                r.sp = Addr(r.sp).wrapping_offset(e as i8 as i16).0;
             }
             LdHliA => {
                 let hl = r.hl();
@@ -407,9 +975,67 @@ impl Cpu {
                 r.a = m.read_byte(Addr(hl));
                 r.set_hl(hl.wrapping_sub(1));
             }
+            // CB-prefixed: RLC, RRC, RL and RR set Z from the result, unlike RLCA and friends.
+            Rlc(target) => {
+                let byte = target.read_byte(r, m);
+                let rotated = alu::rlc(byte, &mut r.f);
+                r.f.set_zero(rotated == 0);
+                target.write_byte(r, m, rotated);
+            }
+            Rrc(target) => {
+                let byte = target.read_byte(r, m);
+                let rotated = alu::rrc(byte, &mut r.f);
+                r.f.set_zero(rotated == 0);
+                target.write_byte(r, m, rotated);
+            }
+            Rl(target) => {
+                let byte = target.read_byte(r, m);
+                let rotated = alu::rl(byte, &mut r.f);
+                r.f.set_zero(rotated == 0);
+                target.write_byte(r, m, rotated);
+            }
+            Rr(target) => {
+                let byte = target.read_byte(r, m);
+                let rotated = alu::rr(byte, &mut r.f);
+                r.f.set_zero(rotated == 0);
+                target.write_byte(r, m, rotated);
+            }
+            Sla(target) => {
+                let byte = target.read_byte(r, m);
+                let shifted = alu::sla(byte, &mut r.f);
+                target.write_byte(r, m, shifted);
+            }
+            Sra(target) => {
+                let byte = target.read_byte(r, m);
+                let shifted = alu::sra(byte, &mut r.f);
+                target.write_byte(r, m, shifted);
+            }
+            Swap(target) => {
+                let byte = target.read_byte(r, m);
+                let swapped = alu::swap(byte, &mut r.f);
+                target.write_byte(r, m, swapped);
+            }
+            Srl(target) => {
+                let byte = target.read_byte(r, m);
+                let shifted = alu::srl(byte, &mut r.f);
+                target.write_byte(r, m, shifted);
+            }
+            Bit(bit, target) => {
+                let byte = target.read_byte(r, m);
+                alu::bit(bit, byte, &mut r.f);
+            }
+            Res(bit, target) => {
+                let byte = target.read_byte(r, m);
+                target.write_byte(r, m, byte & !(1 << bit));
+            }
+            Set(bit, target) => {
+                let byte = target.read_byte(r, m);
+                target.write_byte(r, m, byte | (1 << bit));
+            }
             Invalid(msg) => panic!("Invalid instruction or not implemented: {}", msg),
         }
 
+        let r = &mut self.registers;
         if let Some(new_pc) = new_pc {
             r.pc = new_pc;
         } else {
@@ -432,142 +1058,17 @@ impl Cpu {
         cycles
     }
 
-    fn read_cb_target(&self, idx: u8) -> u8 {
-        let r = &self.registers;
-        match idx {
-            0 => r.b,
-            1 => r.c,
-            2 => r.d,
-            3 => r.e,
-            4 => r.h,
-            5 => r.l,
-            6 => self.memory.read_byte(Addr(r.hl())),
-            7 => r.a,
-            _ => unreachable!(),
-        }
+    /// Pushes `value` onto the stack: high byte at SP-1, low byte at SP-2, wrapping.
+    pub fn push_word(&mut self, value: u16) {
+        let sp = self.registers.sp.wrapping_sub(2);
+        self.memory.write_word(Addr(sp), value);
+        self.registers.sp = sp;
     }
 
-    fn write_cb_target(&mut self, idx: u8, value: u8) {
-        let r = &mut self.registers;
-        match idx {
-            0 => r.b = value,
-            1 => r.c = value,
-            2 => r.d = value,
-            3 => r.e = value,
-            4 => r.h = value,
-            5 => r.l = value,
-            6 => self.memory.write_byte(Addr(r.hl()), value),
-            7 => r.a = value,
-            _ => unreachable!(),
-        }
-    }
-
-    fn execute_cb(&mut self, opcode: u8) -> usize {
-        let x = opcode >> 6;
-        let y = (opcode >> 3) & 0x07;
-        let z = opcode & 0x07;
-
-        match x {
-            0 => {
-                let value = self.read_cb_target(z);
-                let out: u8;
-                match y {
-                    0 => {
-                        // RLC r
-                        let carry = (value & 0x80) != 0;
-                        out = value.rotate_left(1);
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry);
-                    }
-                    1 => {
-                        // RRC r
-                        let carry = (value & 0x01) != 0;
-                        out = value.rotate_right(1);
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry);
-                    }
-                    2 => {
-                        // RL r
-                        let carry_in = self.registers.f.carry() as u8;
-                        let carry_out = (value & 0x80) != 0;
-                        out = (value << 1) | carry_in;
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry_out);
-                    }
-                    3 => {
-                        // RR r
-                        let carry_in = (self.registers.f.carry() as u8) << 7;
-                        let carry_out = (value & 0x01) != 0;
-                        out = (value >> 1) | carry_in;
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry_out);
-                    }
-                    4 => {
-                        // SLA r
-                        let carry = (value & 0x80) != 0;
-                        out = value << 1;
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry);
-                    }
-                    5 => {
-                        // SRA r
-                        let carry = (value & 0x01) != 0;
-                        out = (value >> 1) | (value & 0x80);
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry);
-                    }
-                    6 => {
-                        // SWAP r
-                        out = value.rotate_left(4);
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                    }
-                    7 => {
-                        // SRL r
-                        let carry = (value & 0x01) != 0;
-                        out = value >> 1;
-                        self.registers.f = 0;
-                        self.registers.f.set_zero(out == 0);
-                        self.registers.f.set_carry(carry);
-                    }
-                    _ => unreachable!(),
-                }
-                self.write_cb_target(z, out);
-            }
-            1 => {
-                // BIT b,r
-                let value = self.read_cb_target(z);
-                let is_set = (value & (1u8 << y)) != 0;
-                let carry = self.registers.f.carry();
-                self.registers.f = 0;
-                self.registers.f.set_zero(!is_set);
-                self.registers.f.set_half_carry(true);
-                self.registers.f.set_carry(carry);
-            }
-            2 => {
-                // RES b,r
-                let value = self.read_cb_target(z);
-                self.write_cb_target(z, value & !(1u8 << y));
-            }
-            3 => {
-                // SET b,r
-                let value = self.read_cb_target(z);
-                self.write_cb_target(z, value | (1u8 << y));
-            }
-            _ => unreachable!(),
-        }
-
-        self.registers.pc = self.registers.pc.wrapping_add(2);
-        match (x, z) {
-            (1, 6) => 12, // BIT b,(HL)
-            (_, 6) => 16, // rotate/shift/res/set on (HL)
-            _ => 8,
-        }
+    /// Pops the word at SP (low byte first) and moves SP up by two, wrapping.
+    pub fn pop_word(&mut self) -> u16 {
+        let value = self.memory.read_word(Addr(self.registers.sp));
+        self.registers.sp = self.registers.sp.wrapping_add(2);
+        value
     }
 }
@@ -0,0 +1,32 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+/// One bool per interrupt source, in IE/IF bit order (bit 0 = VBlank ... bit 4 = joypad).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterruptFlags {
+    pub vblank: bool,
+    pub lcd_stat: bool,
+    pub timer: bool,
+    pub serial: bool,
+    pub joypad: bool,
+}
+
+impl InterruptFlags {
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            vblank: bits & 0x01 != 0,
+            lcd_stat: bits & 0x02 != 0,
+            timer: bits & 0x04 != 0,
+            serial: bits & 0x08 != 0,
+            joypad: bits & 0x10 != 0,
+        }
+    }
+}
+
+/// Snapshot of the interrupt master enable, IE (0xFFFF) and IF (0xFF0F).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterruptState {
+    pub ime: bool,
+    pub enabled: InterruptFlags,
+    pub requested: InterruptFlags,
+}
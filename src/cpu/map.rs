@@ -5,7 +5,7 @@ use super::Instruction;
 
 type I = Instruction;
 
-fn ld(dst: Operand, src: Operand) -> super::ops::Mnemonic {
+const fn ld(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     if dst.target_size() == 2 || src.target_size() == 2 {
         Ld16(dst, src)
     } else {
@@ -13,7 +13,7 @@ fn ld(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     }
 }
 
-fn inc(dst: Operand) -> super::ops::Mnemonic {
+const fn inc(dst: Operand) -> super::ops::Mnemonic {
     if dst.target_size() == 2 {
         Inc16(dst)
     } else {
@@ -21,7 +21,7 @@ fn inc(dst: Operand) -> super::ops::Mnemonic {
     }
 }
 
-fn dec(dst: Operand) -> super::ops::Mnemonic {
+const fn dec(dst: Operand) -> super::ops::Mnemonic {
     if dst.target_size() == 2 {
         Dec16(dst)
     } else {
@@ -29,7 +29,7 @@ fn dec(dst: Operand) -> super::ops::Mnemonic {
     }
 }
 
-fn add(dst: Operand, src: Operand) -> super::ops::Mnemonic {
+const fn add(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     if dst.target_size() == 2 || src.target_size() == 2 {
         Add16(dst, src)
     } else {
@@ -37,25 +37,57 @@ fn add(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     }
 }
 
-fn adc(dst: Operand, src: Operand) -> super::ops::Mnemonic {
+const fn adc(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     debug_assert!(dst.target_size() == 1 && src.target_size() == 1);
     Adc8(dst, src)
 }
 
-fn sub(dst: Operand, src: Operand) -> super::ops::Mnemonic {
+const fn sub(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     debug_assert!(dst.target_size() == 1 && src.target_size() == 1);
     Sub8(dst, src)
 }
 
-fn sbc(dst: Operand, src: Operand) -> super::ops::Mnemonic {
+const fn sbc(dst: Operand, src: Operand) -> super::ops::Mnemonic {
     debug_assert!(dst.target_size() == 1 && src.target_size() == 1);
     Sbc8(dst, src)
 }
 
+// Both tables are built at compile time and shared by every `Cpu` and decoder.
+static OPCODE_MAP: [Instruction; 256] = build_opcode_map();
+static CB_OPCODE_MAP: [Instruction; 256] = build_cb_opcode_map();
+
+/// The instruction an unprefixed opcode decodes to. 0xCB decodes as an invalid placeholder;
+/// use `cb_opcode_info` for the byte that follows it.
+pub fn opcode_info(opcode: u8) -> Instruction {
+    OPCODE_MAP[opcode as usize]
+}
+
+/// The instruction a CB-prefixed opcode decodes to; `bytes` and `cycles` include the prefix.
+pub fn cb_opcode_info(opcode: u8) -> Instruction {
+    CB_OPCODE_MAP[opcode as usize]
+}
+
+/// Decodes the instruction at the start of `bytes`, following a 0xCB prefix into the CB table.
+/// Returns the instruction with the number of bytes its encoding consumes (prefix, opcode and
+/// immediates, as the operands read them), or `None` when `bytes` is too short to hold it.
+pub fn decode(bytes: &[u8]) -> Option<(Instruction, usize)> {
+    let (instruction, consumed) = match *bytes {
+        [0xCB, opcode, ..] => {
+            let instruction = cb_opcode_info(opcode);
+            (instruction, 1 + instruction.mnemonic.min_bytes())
+        }
+        [0xCB] | [] => return None,
+        [opcode, ..] => {
+            let instruction = opcode_info(opcode);
+            (instruction, instruction.mnemonic.min_bytes())
+        }
+    };
+    (bytes.len() >= consumed).then_some((instruction, consumed))
+}
+
 /// Builds and returns a mapping of the 8-bit opcodes to instruction instances
-pub fn build_opcode_map() -> [Instruction; 256] {
-    let mut map = [I::new(Invalid("Unimplemented opcode"), 1, 4); 256];
-    for (opcode, instruction) in [
+pub const fn build_opcode_map() -> [Instruction; 256] {
+    let entries = [
         // no-op
         (0x00, I::new(Nop, 1, 4)),
         // load nn into BC
@@ -559,7 +591,7 @@ pub fn build_opcode_map() -> [Instruction; 256] {
         (0xE7, I::new(Rst(0x20), 1, 16)),
         // add n to SP
         (0xE8, I::new(AddSp(Const8.imm()), 2, 16)),
-        // jump to HL
+        // jump to HL (written `JP (HL)`, but PC takes the value of HL, not memory at HL)
         (0xE9, I::new(Jp(HL.imm()), 1, 4)),
         // load A into [nn]
         (0xEA, I::new(ld(Const16.ind(), A.imm()), 3, 16)),
@@ -605,8 +637,58 @@ pub fn build_opcode_map() -> [Instruction; 256] {
         (0xFE, I::new(Cp(A.imm(), Const8.imm()), 2, 8)),
         // restart from 0x38
         (0xFF, I::new(Rst(0x38), 1, 16)),
-    ] {
+    ];
+    let mut map = [I::new(Invalid("Unimplemented opcode"), 1, 4); 256];
+    let mut i = 0;
+    while i < entries.len() {
+        let (opcode, instruction) = entries[i];
         map[opcode as usize] = instruction;
+        i += 1;
+    }
+    map
+}
+
+/// Builds and returns a mapping of the CB-prefixed opcodes to instruction instances.
+/// The table is regular: bits 7-6 pick the operation group, bits 5-3 the operation or bit
+/// number and bits 2-0 the operand.
+pub const fn build_cb_opcode_map() -> [Instruction; 256] {
+    let targets = [
+        B.imm(),
+        C.imm(),
+        D.imm(),
+        E.imm(),
+        H.imm(),
+        L.imm(),
+        HL.ind(),
+        A.imm(),
+    ];
+    let mut map = [I::new(Nop, 2, 8); 256];
+    let mut opcode = 0;
+    while opcode < map.len() {
+        let group = opcode >> 6;
+        let y = ((opcode >> 3) & 0x07) as u8;
+        let z = opcode & 0x07;
+        let target = targets[z];
+        let mnemonic = match (group, y) {
+            (0, 0) => Rlc(target),
+            (0, 1) => Rrc(target),
+            (0, 2) => Rl(target),
+            (0, 3) => Rr(target),
+            (0, 4) => Sla(target),
+            (0, 5) => Sra(target),
+            (0, 6) => Swap(target),
+            (0, _) => Srl(target),
+            (1, bit) => Bit(bit, target),
+            (2, bit) => Res(bit, target),
+            (_, bit) => Set(bit, target),
+        };
+        let cycles = match (group, z) {
+            (1, 6) => 12, // BIT b,(HL)
+            (_, 6) => 16, // read-modify-write on (HL)
+            _ => 8,
+        };
+        map[opcode] = I::new(mnemonic, 2, cycles);
+        opcode += 1;
     }
     map
 }
@@ -1,12 +1,15 @@
 mod alu;
 mod core;
+mod interrupts;
 mod map;
 mod ops;
 
-pub use core::Cpu;
+pub use core::{Cpu, StackImbalance, StepResult, Timeout, TraceDivergence};
+pub use interrupts::{InterruptFlags, InterruptState};
+pub use map::{cb_opcode_info, decode, opcode_info};
 #[allow(unused_imports)]
 pub use ops::Location;
-pub use ops::{Instruction, Mnemonic};
+pub use ops::{CycleSpec, Instruction, Mnemonic};
 pub use ops::{
     CARRY_FLAG_BITMASK, HALF_CARRY_FLAG_BITMASK, SUBTRACTION_FLAG_BITMASK, ZERO_FLAG_BITMASK,
 };
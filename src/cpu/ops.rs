@@ -101,10 +101,71 @@ pub enum Mnemonic {
     LdHldA,
     /// LD A, (HL-)
     LdAHld,
+    /// CB: rotate left; old bit 7 to Carry flag
+    Rlc(Operand),
+    /// CB: rotate right; old bit 0 to Carry flag
+    Rrc(Operand),
+    /// CB: rotate left through Carry flag
+    Rl(Operand),
+    /// CB: rotate right through Carry flag
+    Rr(Operand),
+    /// CB: shift left into Carry flag
+    Sla(Operand),
+    /// CB: shift right into Carry flag, keeping bit 7
+    Sra(Operand),
+    /// CB: swap upper and lower nibbles
+    Swap(Operand),
+    /// CB: shift right into Carry flag
+    Srl(Operand),
+    /// CB: test a bit
+    Bit(u8, Operand),
+    /// CB: reset a bit
+    Res(u8, Operand),
+    /// CB: set a bit
+    Set(u8, Operand),
     /// Invalid instruction
     Invalid(&'static str),
 }
 
+impl Mnemonic {
+    /// The smallest encoding length consistent with the operands: the opcode byte plus
+    /// the widest immediate an operand reads from the instruction stream.
+    pub const fn min_bytes(&self) -> usize {
+        use Mnemonic as M;
+        let (first, second) = match *self {
+            M::Ld8(a, b)
+            | M::Ld16(a, b)
+            | M::Add8(a, b)
+            | M::Add16(a, b)
+            | M::Adc8(a, b)
+            | M::Sub8(a, b)
+            | M::Sbc8(a, b)
+            | M::And(a, b)
+            | M::Xor(a, b)
+            | M::Or(a, b)
+            | M::Cp(a, b)
+            | M::Jrc(a, b)
+            | M::Jpc(a, b)
+            | M::Callc(a, b) => (a.immediate_bytes(), b.immediate_bytes()),
+            M::Stop(a)
+            | M::Inc8(a)
+            | M::Inc16(a)
+            | M::Dec8(a)
+            | M::Dec16(a)
+            | M::Jr(a)
+            | M::Retc(a)
+            | M::Pop(a)
+            | M::Jp(a)
+            | M::Call(a)
+            | M::Push(a)
+            | M::Ldhl(a)
+            | M::AddSp(a) => (a.immediate_bytes(), 0),
+            _ => (0, 0),
+        };
+        1 + if first > second { first } else { second }
+    }
+}
+
 /// Represents the location of an instruction's operands
 #[derive(Debug, Clone, Copy)]
 pub enum Location {
@@ -188,7 +249,7 @@ impl Location {
             FlagZ => r.f.zero() as u8,
             FlagNc => !r.f.carry() as u8,
             FlagC => r.f.carry() as u8,
-            Const8 => memory.read_byte(Addr(r.pc + 1)),
+            Const8 => memory.read_byte(Addr(r.pc).next()),
             _ => panic!("Invalid location for read_byte"),
         }
     }
@@ -200,12 +261,12 @@ impl Location {
             DE => r.de(),
             HL => r.hl(),
             SP => r.sp,
-            Const16 => memory.read_word(Addr(r.pc + 1)),
+            Const16 => memory.read_word(Addr(r.pc).next()),
             _ => panic!("Invalid location for read_word"),
         }
     }
 
-    fn target_size(&self) -> usize {
+    const fn target_size(&self) -> usize {
         match self {
             A | B | C | D | E | H | L | FlagNz | FlagZ | FlagNc | FlagC => 1,
             SP | AF | BC | DE | HL | Const16 => 2,
@@ -213,15 +274,15 @@ impl Location {
         }
     }
 
-    pub fn imm(&self) -> Operand {
+    pub const fn imm(&self) -> Operand {
         Operand::Immediate(*self)
     }
 
-    pub fn ind(&self) -> Operand {
+    pub const fn ind(&self) -> Operand {
         Operand::Indirect(*self)
     }
 
-    pub fn high(&self) -> Operand {
+    pub const fn high(&self) -> Operand {
         Operand::HighMemory(*self)
     }
 }
@@ -238,7 +299,17 @@ pub enum Operand {
 }
 
 impl Operand {
-    pub fn target_size(&self) -> usize {
+    /// Number of immediate bytes this operand reads from the instruction stream.
+    const fn immediate_bytes(&self) -> usize {
+        let (Operand::Immediate(loc) | Operand::Indirect(loc) | Operand::HighMemory(loc)) = self;
+        match loc {
+            Const8 => 1,
+            Const16 => 2,
+            _ => 0,
+        }
+    }
+
+    pub const fn target_size(&self) -> usize {
         match self {
             Operand::Immediate(loc) => loc.target_size(),
             Operand::Indirect(_) => 1,
@@ -256,7 +327,7 @@ impl Operand {
             }
             Operand::HighMemory(loc) => {
                 let addr = loc.read_byte(registers, memory) as u16;
-                memory.read_byte(Addr(0xFF00 + addr))
+                memory.read_byte(Addr(0xFF00).wrapping_add(addr))
             }
         }
     }
@@ -264,7 +335,11 @@ impl Operand {
     pub fn read_word(&self, registers: &Registers, memory: &Ram) -> u16 {
         match self {
             Operand::Immediate(loc) => loc.read_word(registers, memory),
-            _ => panic!("Invalid operand size"),
+            Operand::Indirect(loc) => {
+                let addr = loc.read_word(registers, memory);
+                memory.read_word(Addr(addr))
+            }
+            Operand::HighMemory(_) => panic!("Invalid operand size for read_word"),
         }
     }
 
@@ -277,7 +352,7 @@ impl Operand {
             }
             Operand::HighMemory(loc) => {
                 let addr = loc.read_byte(registers, memory) as u16;
-                memory.write_byte(Addr(0xFF00 + addr), value);
+                memory.write_byte(Addr(0xFF00).wrapping_add(addr), value);
             }
         }
     }
@@ -312,8 +387,13 @@ pub struct Instruction {
 }
 
 impl Instruction {
-    fn with_cycles(mnemonic: Mnemonic, bytes: usize, cycles: CycleSpec) -> Instruction {
-        debug_assert!(u8::try_from(bytes).is_ok(), "instruction length overflow");
+    // Const so the opcode tables are built at compile time, where a failed check is a build error.
+    const fn with_cycles(mnemonic: Mnemonic, bytes: usize, cycles: CycleSpec) -> Instruction {
+        debug_assert!(bytes <= u8::MAX as usize, "instruction length overflow");
+        debug_assert!(
+            bytes >= mnemonic.min_bytes(),
+            "instruction declared shorter than its operands need"
+        );
         Instruction {
             mnemonic,
             bytes: bytes as u8,
@@ -322,12 +402,12 @@ impl Instruction {
     }
 
     /// Creates a new instruction
-    pub fn new(mnemonic: Mnemonic, bytes: usize, cycles: usize) -> Instruction {
+    pub const fn new(mnemonic: Mnemonic, bytes: usize, cycles: usize) -> Instruction {
         Instruction::with_cycles(mnemonic, bytes, CycleSpec::Fixed(cycles))
     }
 
     /// Creates a new instruction with branch-dependent timing
-    pub fn new_branch(
+    pub const fn new_branch(
         mnemonic: Mnemonic,
         bytes: usize,
         taken: usize,
@@ -0,0 +1,429 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use std::collections::HashMap;
+
+use crate::cpu::{self, Cpu, Instruction, Mnemonic};
+use crate::memory::Addr;
+
+/// Why a debugger command returned control to the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested step finished.
+    Step,
+    /// PC reached a user breakpoint.
+    Breakpoint(u16),
+    /// The cycle budget ran out before anything else stopped execution.
+    CycleLimit,
+}
+
+/// A CPU register a breakpoint condition can test. 16-bit pairs compare their full value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    F,
+    B,
+    C,
+    D,
+    E,
+    H,
+    L,
+    AF,
+    BC,
+    DE,
+    HL,
+    SP,
+}
+
+impl Register {
+    fn read(self, cpu: &Cpu) -> u16 {
+        let r = &cpu.registers;
+        match self {
+            Register::A => r.a.into(),
+            Register::F => r.f.into(),
+            Register::B => r.b.into(),
+            Register::C => r.c.into(),
+            Register::D => r.d.into(),
+            Register::E => r.e.into(),
+            Register::H => r.h.into(),
+            Register::L => r.l.into(),
+            Register::AF => r.af(),
+            Register::BC => r.bc(),
+            Register::DE => r.de(),
+            Register::HL => r.hl(),
+            Register::SP => r.sp,
+        }
+    }
+}
+
+/// How a condition compares the current value (left) with its expected value (right).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+}
+
+impl Comparison {
+    fn holds(self, actual: u16, expected: u16) -> bool {
+        match self {
+            Comparison::Equal => actual == expected,
+            Comparison::NotEqual => actual != expected,
+            Comparison::Less => actual < expected,
+            Comparison::Greater => actual > expected,
+        }
+    }
+}
+
+/// Extra state a breakpoint requires before it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Compares a register with `value`.
+    Register {
+        register: Register,
+        comparison: Comparison,
+        value: u16,
+    },
+    /// Compares the byte at `address` (read with `Cpu::peek`) with `value`.
+    Memory {
+        address: u16,
+        comparison: Comparison,
+        value: u8,
+    },
+}
+
+impl Condition {
+    pub fn holds(&self, cpu: &Cpu) -> bool {
+        match *self {
+            Condition::Register {
+                register,
+                comparison,
+                value,
+            } => comparison.holds(register.read(cpu), value),
+            Condition::Memory {
+                address,
+                comparison,
+                value,
+            } => comparison.holds(cpu.peek(Addr(address)).into(), value.into()),
+        }
+    }
+}
+
+/// Stops execution when PC reaches `addr`, and `condition` holds if there is one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    pub condition: Option<Condition>,
+}
+
+/// Execution control on top of `Cpu::step`: PC breakpoints, single-step and step-over.
+#[derive(Debug, Default)]
+pub struct Debugger {
+    /// Breakpoint conditions keyed by address; `None` breaks unconditionally.
+    breakpoints: HashMap<u16, Option<Condition>>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_breakpoint(&mut self, address: u16) {
+        self.breakpoints.insert(address, None);
+    }
+
+    /// Adds `breakpoint`, replacing any breakpoint already at its address.
+    pub fn add_conditional_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints
+            .insert(breakpoint.addr, breakpoint.condition);
+    }
+
+    /// Returns true if a breakpoint was set at `address`.
+    pub fn remove_breakpoint(&mut self, address: u16) -> bool {
+        self.breakpoints.remove(&address).is_some()
+    }
+
+    /// True if a breakpoint is set at `address`, whatever its condition.
+    pub fn has_breakpoint(&self, address: u16) -> bool {
+        self.breakpoints.contains_key(&address)
+    }
+
+    /// Every breakpoint address, in ascending order.
+    pub fn breakpoints(&self) -> Vec<u16> {
+        let mut addresses: Vec<u16> = self.breakpoints.keys().copied().collect();
+        addresses.sort_unstable();
+        addresses
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// True if a breakpoint at the current PC fires: it has no condition or its condition holds.
+    fn breaks_at_pc(&self, cpu: &Cpu) -> bool {
+        self.breakpoints
+            .get(&cpu.registers.pc)
+            .is_some_and(|condition| condition.is_none_or(|condition| condition.holds(cpu)))
+    }
+
+    /// Executes a single instruction (plus any interrupt dispatch it triggers).
+    pub fn step(&mut self, cpu: &mut Cpu) -> StopReason {
+        cpu.step();
+        StopReason::Step
+    }
+
+    /// Runs until PC hits a breakpoint whose condition holds, or `max_cycles` elapse.
+    /// Always executes at least one instruction, so it can resume from a breakpoint.
+    pub fn run_until_break(&mut self, cpu: &mut Cpu, max_cycles: usize) -> StopReason {
+        let mut cycles = 0;
+        while cycles < max_cycles {
+            cycles += cpu.step();
+            if self.breaks_at_pc(cpu) {
+                return StopReason::Breakpoint(cpu.registers.pc);
+            }
+        }
+        StopReason::CycleLimit
+    }
+
+    /// Like `step`, except that a CALL or RST runs to completion: execution continues until the
+    /// subroutine returns to the instruction after the call, a breakpoint is hit or `max_cycles`
+    /// elapse.
+    pub fn step_over(&mut self, cpu: &mut Cpu, max_cycles: usize) -> StopReason {
+        let instruction = cpu.peek_instruction();
+        if !matches!(
+            instruction.mnemonic,
+            Mnemonic::Call(_) | Mnemonic::Callc(_, _) | Mnemonic::Rst(_)
+        ) {
+            return self.step(cpu);
+        }
+
+        let return_address = cpu.registers.pc.wrapping_add(instruction.bytes as u16);
+        let caller_sp = cpu.registers.sp;
+        let mut cycles = cpu.step();
+        loop {
+            // Matching SP as well keeps recursive calls to the same site from stopping early.
+            if cpu.registers.pc == return_address && cpu.registers.sp == caller_sp {
+                return StopReason::Step;
+            }
+            if self.breaks_at_pc(cpu) {
+                return StopReason::Breakpoint(cpu.registers.pc);
+            }
+            if cycles >= max_cycles {
+                return StopReason::CycleLimit;
+            }
+            cycles += cpu.step();
+        }
+    }
+}
+
+/// Decodes `count` consecutive instructions starting at `start`, reading memory with `Cpu::peek`.
+/// CB-prefixed opcodes decode through the CB table. Each entry is paired with its address.
+pub fn disassemble(cpu: &Cpu, start: u16, count: usize) -> Vec<(u16, Instruction)> {
+    let mut address = start;
+    let mut listing = Vec::with_capacity(count);
+    for _ in 0..count {
+        let bytes: [u8; 3] =
+            std::array::from_fn(|offset| cpu.peek(Addr(address.wrapping_add(offset as u16))));
+        let (instruction, _) = cpu::decode(&bytes).expect("3 bytes hold any instruction");
+        listing.push((address, instruction));
+        address = address.wrapping_add(instruction.bytes as u16);
+    }
+    listing
+}
+
+/// Formats `bytes`, read from `start` onwards, as a classic hex dump: 16 bytes per row, each
+/// row prefixed with its address and followed by an ASCII gutter (`.` for non-printable bytes).
+pub fn hex_dump(start: u16, bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        let address = start.wrapping_add((row * 16) as u16);
+        out.push_str(&format!("{address:04X} "));
+        for column in 0..16 {
+            if column == 8 {
+                out.push(' ');
+            }
+            match chunk.get(column) {
+                Some(byte) => out.push_str(&format!(" {byte:02X}")),
+                None => out.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        out.push_str(&format!("  |{ascii}|\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu_with_program(program: &[(u16, u8)]) -> Cpu {
+        let mut cpu = Cpu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        for &(address, byte) in program {
+            cpu.write_byte(Addr(address), byte);
+        }
+        cpu
+    }
+
+    #[test]
+    fn step_over_runs_call_to_completion() {
+        let mut cpu = cpu_with_program(&[
+            (0xC000, 0xCD), // CALL 0xC100
+            (0xC001, 0x00),
+            (0xC002, 0xC1),
+            (0xC003, 0x00), // NOP
+            (0xC100, 0x3C), // INC A
+            (0xC101, 0x3C), // INC A
+            (0xC102, 0xC9), // RET
+        ]);
+        cpu.registers.a = 0;
+        let mut debugger = Debugger::new();
+
+        assert_eq!(debugger.step_over(&mut cpu, 1_000), StopReason::Step);
+
+        assert_eq!(cpu.registers.pc, 0xC003);
+        assert_eq!(cpu.registers.sp, 0xDFFE);
+        assert_eq!(cpu.registers.a, 2, "subroutine body should have run");
+    }
+
+    #[test]
+    fn step_over_single_steps_non_call_instructions() {
+        let mut cpu = cpu_with_program(&[(0xC000, 0x3C), (0xC001, 0x3C)]);
+        let mut debugger = Debugger::new();
+
+        assert_eq!(debugger.step_over(&mut cpu, 1_000), StopReason::Step);
+
+        assert_eq!(cpu.registers.pc, 0xC001);
+    }
+
+    #[test]
+    fn step_over_stops_at_breakpoint_inside_subroutine() {
+        let mut cpu = cpu_with_program(&[
+            (0xC000, 0xCD), // CALL 0xC100
+            (0xC001, 0x00),
+            (0xC002, 0xC1),
+            (0xC100, 0x00), // NOP
+            (0xC101, 0xC9), // RET
+        ]);
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(0xC101);
+
+        assert_eq!(
+            debugger.step_over(&mut cpu, 1_000),
+            StopReason::Breakpoint(0xC101)
+        );
+    }
+
+    #[test]
+    fn run_until_break_stops_at_breakpoint() {
+        let mut cpu = cpu_with_program(&[(0xC000, 0x00), (0xC001, 0x00), (0xC002, 0x00)]);
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(0xC002);
+
+        assert_eq!(
+            debugger.run_until_break(&mut cpu, 1_000),
+            StopReason::Breakpoint(0xC002)
+        );
+        assert_eq!(cpu.registers.pc, 0xC002);
+    }
+
+    #[test]
+    fn conditional_breakpoint_only_fires_when_its_condition_holds() {
+        // 0xC000: INC A; JR 0xC000
+        let mut cpu = cpu_with_program(&[(0xC000, 0x3C), (0xC001, 0x18), (0xC002, 0xFD)]);
+        cpu.registers.a = 0;
+        let mut debugger = Debugger::new();
+        debugger.add_conditional_breakpoint(Breakpoint {
+            addr: 0xC001,
+            condition: Some(Condition::Register {
+                register: Register::A,
+                comparison: Comparison::Equal,
+                value: 3,
+            }),
+        });
+
+        assert_eq!(
+            debugger.run_until_break(&mut cpu, 1_000),
+            StopReason::Breakpoint(0xC001)
+        );
+        assert_eq!(cpu.registers.a, 3, "passed 0xC001 twice without stopping");
+
+        debugger.add_conditional_breakpoint(Breakpoint {
+            addr: 0xC001,
+            condition: Some(Condition::Memory {
+                address: 0xD000,
+                comparison: Comparison::NotEqual,
+                value: 0,
+            }),
+        });
+        assert_eq!(
+            debugger.run_until_break(&mut cpu, 1_000),
+            StopReason::CycleLimit,
+            "memory[0xD000] stays zero"
+        );
+
+        cpu.write_byte(Addr(0xD000), 0x01);
+        assert_eq!(
+            debugger.run_until_break(&mut cpu, 1_000),
+            StopReason::Breakpoint(0xC001)
+        );
+    }
+
+    #[test]
+    fn breakpoints_are_listed_sorted_and_cleared_together() {
+        let mut debugger = Debugger::new();
+        for address in [0x0150, 0x0100, 0xC000] {
+            debugger.add_breakpoint(address);
+        }
+        assert_eq!(debugger.breakpoints(), vec![0x0100, 0x0150, 0xC000]);
+
+        debugger.clear_breakpoints();
+        assert!(debugger.breakpoints().is_empty());
+        assert!(!debugger.has_breakpoint(0x0100));
+    }
+
+    #[test]
+    fn disassemble_walks_instruction_lengths_including_cb_prefix() {
+        let cpu = cpu_with_program(&[
+            (0xC000, 0x3E), // LD A,0x12
+            (0xC001, 0x12),
+            (0xC002, 0xCB), // SWAP A
+            (0xC003, 0x37),
+            (0xC004, 0xC3), // JP 0xC000
+            (0xC005, 0x00),
+            (0xC006, 0xC0),
+        ]);
+
+        let listing = disassemble(&cpu, 0xC000, 3);
+
+        let addresses: Vec<u16> = listing.iter().map(|&(address, _)| address).collect();
+        assert_eq!(addresses, [0xC000, 0xC002, 0xC004]);
+        assert!(matches!(listing[0].1.mnemonic, Mnemonic::Ld8(_, _)));
+        assert!(matches!(listing[1].1.mnemonic, Mnemonic::Swap(_)));
+        assert!(matches!(listing[2].1.mnemonic, Mnemonic::Jp(_)));
+    }
+
+    #[test]
+    fn hex_dump_formats_rows_with_address_and_ascii_gutter() {
+        let mut bytes: Vec<u8> = b"NINTENDO TETRIS\0".to_vec();
+        bytes.extend_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+
+        assert_eq!(
+            hex_dump(0x0130, &bytes),
+            "0130  4E 49 4E 54 45 4E 44 4F  20 54 45 54 52 49 53 00  |NINTENDO TETRIS.|\n\
+             0140  00 C3 50 01                                       |..P.|\n"
+        );
+    }
+}
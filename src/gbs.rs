@@ -0,0 +1,261 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use crate::cpu::Cpu;
+use crate::memory::Addr;
+
+const MAGIC: &[u8; 3] = b"GBS";
+const HEADER_LEN: usize = 0x70;
+const ROM_BANK_SIZE: usize = 16 * 1024;
+/// Where init/play return to. The synthetic ROM holds `JR -2` here in case it is ever executed.
+const RETURN_ADDRESS: u16 = 0x0100;
+/// `RST n` jumps to `load_address + n`: the synthetic ROM holds a `JP` there at each vector.
+const RST_VECTORS: [u16; 8] = [0x00, 0x08, 0x10, 0x18, 0x20, 0x28, 0x30, 0x38];
+/// Upper bound for a single init or play call before it is considered stuck (about 10 frames).
+const ROUTINE_CYCLE_LIMIT: u64 = 702_240;
+
+/// The 0x70-byte header of a `.gbs` sound rip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GbsHeader {
+    pub version: u8,
+    pub song_count: u8,
+    /// 1-based index of the song to play by default.
+    pub first_song: u8,
+    pub load_address: u16,
+    pub init_address: u16,
+    pub play_address: u16,
+    pub stack_pointer: u16,
+    pub timer_modulo: u8,
+    pub timer_control: u8,
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+}
+
+impl GbsHeader {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err(format!(
+                "GBS file is {} bytes, shorter than its {HEADER_LEN}-byte header",
+                bytes.len()
+            ));
+        }
+        if !bytes.starts_with(MAGIC) {
+            return Err("missing GBS header".to_string());
+        }
+        let word = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let text = |offset: usize| {
+            let field = &bytes[offset..offset + 32];
+            let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+
+        let header = Self {
+            version: bytes[0x03],
+            song_count: bytes[0x04],
+            first_song: bytes[0x05],
+            load_address: word(0x06),
+            init_address: word(0x08),
+            play_address: word(0x0A),
+            stack_pointer: word(0x0C),
+            timer_modulo: bytes[0x0E],
+            timer_control: bytes[0x0F],
+            title: text(0x10),
+            author: text(0x30),
+            copyright: text(0x50),
+        };
+        if header.song_count == 0 {
+            return Err("GBS file declares no songs".to_string());
+        }
+        if header.load_address < 0x0400 || header.load_address > 0x7FFF {
+            return Err(format!(
+                "GBS load address 0x{:04X} is outside 0x0400-0x7FFF",
+                header.load_address
+            ));
+        }
+        Ok(header)
+    }
+
+    /// CPU cycles between play calls: the timer overflow period when TAC enables the timer,
+    /// otherwise one frame (VBlank rate).
+    pub fn play_interval_cycles(&self) -> usize {
+        if self.timer_control & 0x04 == 0 {
+            return crate::ppu::CYCLES_PER_FRAME;
+        }
+        let tick = match self.timer_control & 0x03 {
+            0 => 1024,
+            1 => 16,
+            2 => 64,
+            _ => 256,
+        };
+        tick * (256 - self.timer_modulo as usize)
+    }
+}
+
+/// Runs GBS music code on the CPU: `start_song` calls the init routine and `play` the play
+/// routine, which a frontend calls every `play_interval_cycles`. The APU is driven through
+/// ordinary writes to its registers.
+pub struct GbsPlayer {
+    header: GbsHeader,
+    cpu: Cpu,
+}
+
+impl GbsPlayer {
+    /// Parses `file` and maps its code at the load address of a synthetic cartridge,
+    /// MBC1-banked when it does not fit in 32 KiB.
+    pub fn new(file: &[u8]) -> Result<Self, String> {
+        let header = GbsHeader::parse(file)?;
+        let code = &file[HEADER_LEN..];
+
+        let image_len = header.load_address as usize + code.len();
+        let banks = image_len.div_ceil(ROM_BANK_SIZE).max(2).next_power_of_two();
+        if banks > 512 {
+            return Err(format!("GBS code of {} bytes is too large", code.len()));
+        }
+        let mut rom = vec![0xFF; banks * ROM_BANK_SIZE];
+        for vector in RST_VECTORS {
+            let [lo, hi] = (header.load_address + vector).to_le_bytes();
+            rom[vector as usize..vector as usize + 3].copy_from_slice(&[0xC3, lo, hi]);
+        }
+        rom[RETURN_ADDRESS as usize..RETURN_ADDRESS as usize + 2].copy_from_slice(&[0x18, 0xFE]);
+        rom[0x0134..0x0150].fill(0);
+        rom[0x0147] = if banks > 2 { 0x01 } else { 0x00 }; // MBC1 or ROM only
+        rom[0x0148] = banks.trailing_zeros() as u8 - 1;
+        rom[header.load_address as usize..image_len].copy_from_slice(code);
+
+        let mut cpu = Cpu::from_rom(rom)?;
+        cpu.write_byte(Addr(0xFF06), header.timer_modulo);
+        cpu.write_byte(Addr(0xFF07), header.timer_control);
+        Ok(Self { header, cpu })
+    }
+
+    pub fn header(&self) -> &GbsHeader {
+        &self.header
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    /// Calls the init routine for `song` (1-based), which the routine receives 0-based in A.
+    pub fn start_song(&mut self, song: u8) -> Result<(), String> {
+        if song == 0 || song > self.header.song_count {
+            return Err(format!(
+                "song {song} is out of range 1-{}",
+                self.header.song_count
+            ));
+        }
+        self.cpu.registers.sp = self.header.stack_pointer;
+        self.cpu.registers.a = song - 1;
+        self.call(self.header.init_address)
+    }
+
+    /// Calls the play routine once.
+    pub fn play(&mut self) -> Result<(), String> {
+        self.call(self.header.play_address)
+    }
+
+    fn call(&mut self, address: u16) -> Result<(), String> {
+        let sp = self.cpu.registers.sp.wrapping_sub(2);
+        self.cpu.write_word(Addr(sp), RETURN_ADDRESS);
+        self.cpu.registers.sp = sp;
+        self.cpu.registers.pc = address;
+
+        let start = self.cpu.total_cycles;
+        while self.cpu.registers.pc != RETURN_ADDRESS {
+            if self.cpu.total_cycles - start > ROUTINE_CYCLE_LIMIT {
+                return Err(format!("routine at 0x{address:04X} did not return"));
+            }
+            self.cpu.step();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gbs_file(code: &[u8]) -> Vec<u8> {
+        let mut file = vec![0u8; HEADER_LEN];
+        file[0..3].copy_from_slice(MAGIC);
+        file[0x03] = 1; // version
+        file[0x04] = 3; // songs
+        file[0x05] = 1; // first song
+        file[0x06..0x08].copy_from_slice(&0x0400u16.to_le_bytes()); // load
+        file[0x08..0x0A].copy_from_slice(&0x0400u16.to_le_bytes()); // init
+        file[0x0A..0x0C].copy_from_slice(&0x0404u16.to_le_bytes()); // play
+        file[0x0C..0x0E].copy_from_slice(&0xDFFFu16.to_le_bytes()); // stack
+        file[0x10..0x15].copy_from_slice(b"Theme");
+        file[0x30..0x33].copy_from_slice(b"Ann");
+        file.extend_from_slice(code);
+        file
+    }
+
+    /// init: LD (0xC000),A; RET. play: LD HL,0xC001; INC (HL); RET.
+    const CODE: [u8; 9] = [0xEA, 0x00, 0xC0, 0xC9, 0x21, 0x01, 0xC0, 0x34, 0xC9];
+
+    #[test]
+    fn parses_header_addresses_and_song_count() {
+        let header = GbsHeader::parse(&gbs_file(&CODE)).unwrap();
+
+        assert_eq!(header.song_count, 3);
+        assert_eq!(header.first_song, 1);
+        assert_eq!(header.load_address, 0x0400);
+        assert_eq!(header.init_address, 0x0400);
+        assert_eq!(header.play_address, 0x0404);
+        assert_eq!(header.stack_pointer, 0xDFFF);
+        assert_eq!(header.title, "Theme");
+        assert_eq!(header.author, "Ann");
+        assert_eq!(header.play_interval_cycles(), 70224);
+    }
+
+    #[test]
+    fn rejects_missing_magic_and_short_files() {
+        assert!(GbsHeader::parse(&[0u8; HEADER_LEN]).is_err());
+        assert!(GbsHeader::parse(b"GBS").is_err());
+    }
+
+    #[test]
+    fn timer_driven_play_rate_follows_tma_and_tac() {
+        let mut file = gbs_file(&CODE);
+        file[0x0E] = 0xC0; // TMA: 64 ticks per overflow
+        file[0x0F] = 0x04; // TAC: enabled, 4096 Hz
+        let header = GbsHeader::parse(&file).unwrap();
+
+        assert_eq!(header.play_interval_cycles(), 1024 * 64);
+    }
+
+    #[test]
+    fn player_runs_init_with_song_index_and_play_routine() {
+        let mut player = GbsPlayer::new(&gbs_file(&CODE)).unwrap();
+
+        player.start_song(2).unwrap();
+        assert_eq!(
+            player.cpu().read_byte(Addr(0xC000)),
+            1,
+            "A holds the 0-based song"
+        );
+
+        player.play().unwrap();
+        player.play().unwrap();
+        assert_eq!(player.cpu().read_byte(Addr(0xC001)), 2);
+        assert_eq!(player.cpu().registers.sp, 0xDFFF);
+
+        assert!(player.start_song(4).is_err());
+    }
+
+    #[test]
+    fn rst_calls_reach_the_relocated_vectors_at_the_load_address() {
+        // init: RST 08; RET. play: RET. 0x0408: LD A,0x42; LD (0xC000),A; RET.
+        let code = [
+            0xCF, 0xC9, 0x00, 0x00, 0xC9, 0x00, 0x00, 0x00, 0x3E, 0x42, 0xEA, 0x00, 0xC0, 0xC9,
+        ];
+        let mut player = GbsPlayer::new(&gbs_file(&code)).unwrap();
+
+        player.start_song(1).unwrap();
+
+        assert_eq!(player.cpu().read_byte(Addr(0xC000)), 0x42);
+        assert_eq!(player.cpu().registers.sp, 0xDFFF);
+    }
+}
@@ -0,0 +1,203 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use crate::cpu::Cpu;
+use crate::memory::Button;
+
+const MAGIC: &[u8; 4] = b"GBIL";
+const EVENT_LEN: usize = 6;
+
+/// A button transition applied at the start of frame `frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub frame: u32,
+    pub button: Button,
+    pub pressed: bool,
+}
+
+/// A timeline of joypad events for deterministic replays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputLog {
+    events: Vec<InputEvent>,
+}
+
+impl InputLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Appends an event. Events must be recorded in frame order.
+    pub fn record(&mut self, frame: u32, button: Button, pressed: bool) {
+        debug_assert!(
+            self.events.last().is_none_or(|last| last.frame <= frame),
+            "input events must be recorded in frame order"
+        );
+        self.events.push(InputEvent {
+            frame,
+            button,
+            pressed,
+        });
+    }
+
+    /// Serializes the log as `GBIL` followed by one 6-byte record per event:
+    /// frame (u32 LE), button index, pressed flag.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + self.events.len() * EVENT_LEN);
+        bytes.extend_from_slice(MAGIC);
+        for event in &self.events {
+            bytes.extend_from_slice(&event.frame.to_le_bytes());
+            bytes.push(event.button as u8);
+            bytes.push(event.pressed as u8);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let body = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or_else(|| "missing input log header".to_string())?;
+        if body.len() % EVENT_LEN != 0 {
+            return Err(format!(
+                "truncated input log: {} trailing byte(s)",
+                body.len() % EVENT_LEN
+            ));
+        }
+
+        let mut log = InputLog::new();
+        for record in body.chunks_exact(EVENT_LEN) {
+            let frame = u32::from_le_bytes([record[0], record[1], record[2], record[3]]);
+            let button = *Button::ALL
+                .get(record[4] as usize)
+                .ok_or_else(|| format!("invalid button index {}", record[4]))?;
+            let pressed = match record[5] {
+                0 => false,
+                1 => true,
+                other => return Err(format!("invalid pressed flag {other}")),
+            };
+            if log.events.last().is_some_and(|last| last.frame > frame) {
+                return Err(format!("event for frame {frame} is out of order"));
+            }
+            log.record(frame, button, pressed);
+        }
+        Ok(log)
+    }
+
+    /// Returns a player that feeds this log into a CPU frame by frame.
+    pub fn playback(&self) -> Playback<'_> {
+        Playback {
+            events: &self.events,
+            frame: 0,
+        }
+    }
+}
+
+/// Applies an `InputLog` to a `Cpu` at the recorded frames.
+#[derive(Debug)]
+pub struct Playback<'a> {
+    events: &'a [InputEvent],
+    frame: u32,
+}
+
+impl Playback<'_> {
+    /// The frame the next `step_frame` will run.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// True once every event has been applied.
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Applies the events due this frame, then runs the CPU to the end of the frame.
+    pub fn step_frame(&mut self, cpu: &mut Cpu) {
+        while let Some((event, rest)) = self.events.split_first() {
+            if event.frame > self.frame {
+                break;
+            }
+            cpu.set_button(event.button, event.pressed);
+            self.events = rest;
+        }
+        cpu.run_frame();
+        self.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Addr;
+
+    fn nop_cpu() -> Cpu {
+        let mut cpu = Cpu::new();
+        cpu.load_rom(vec![0x00; 0x8000]);
+        cpu
+    }
+
+    fn joypad_state(cpu: &mut Cpu) -> (u8, u8) {
+        cpu.write_byte(Addr(0xFF00), 0x10);
+        let action = cpu.read_byte(Addr(0xFF00)) & 0x0F;
+        cpu.write_byte(Addr(0xFF00), 0x20);
+        let direction = cpu.read_byte(Addr(0xFF00)) & 0x0F;
+        (action, direction)
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut log = InputLog::new();
+        log.record(0, Button::Start, true);
+        log.record(3, Button::Start, false);
+        log.record(3, Button::Left, true);
+
+        let decoded = InputLog::from_bytes(&log.to_bytes()).expect("valid log should decode");
+
+        assert_eq!(decoded, log);
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_input() {
+        assert!(InputLog::from_bytes(b"nope").is_err());
+        assert!(InputLog::from_bytes(b"GBIL\x00\x00").is_err());
+        assert!(InputLog::from_bytes(b"GBIL\x00\x00\x00\x00\x08\x01").is_err());
+    }
+
+    #[test]
+    fn playback_reproduces_recorded_joypad_states() {
+        let mut log = InputLog::new();
+        let mut recorded_cpu = nop_cpu();
+        let mut recorded_states = Vec::new();
+        let script = [
+            (1, Button::A, true),
+            (2, Button::Up, true),
+            (4, Button::A, false),
+            (5, Button::Up, false),
+        ];
+        for frame in 0..6u32 {
+            for &(at, button, pressed) in &script {
+                if at == frame {
+                    log.record(frame, button, pressed);
+                    recorded_cpu.set_button(button, pressed);
+                }
+            }
+            recorded_cpu.run_frame();
+            recorded_states.push(joypad_state(&mut recorded_cpu));
+        }
+
+        let replay_log = InputLog::from_bytes(&log.to_bytes()).expect("valid log should decode");
+        let mut replay_cpu = nop_cpu();
+        let mut playback = replay_log.playback();
+        let mut replayed_states = Vec::new();
+        for _ in 0..6 {
+            playback.step_frame(&mut replay_cpu);
+            replayed_states.push(joypad_state(&mut replay_cpu));
+        }
+
+        assert!(playback.is_finished());
+        assert_eq!(replayed_states, recorded_states);
+        assert_eq!(replayed_states[2], (0x0E, 0x0B), "A and Up held on frame 2");
+    }
+}
@@ -1,11 +1,28 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 #[cfg(feature = "frontend")]
 pub mod app;
+pub mod apu;
 pub mod cartridge;
+pub mod cheats;
 #[cfg(feature = "frontend")]
 pub mod config;
 pub mod cpu;
+#[cfg(feature = "std")]
+pub mod debugger;
+#[cfg(feature = "std")]
+pub mod gbs;
+#[cfg(feature = "std")]
+pub mod input_log;
 pub mod memory;
+pub mod png;
+pub mod ppu;
 pub mod renderer;
+#[cfg(feature = "std")]
 pub mod rom_loader;
+#[cfg(feature = "std")]
+pub mod snapshot;
 #[cfg(feature = "frontend")]
 pub mod ui;
@@ -1,4 +1,4 @@
-use gabalah::{app, config, cpu::Cpu, rom_loader};
+use gabalah::{app, config, cpu::Cpu, debugger, memory::Addr, rom_loader};
 use std::env;
 use std::fs;
 use std::io::ErrorKind;
@@ -16,10 +16,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let rom = rom_loader::load_rom_from_path(rom_input_path, cli.entry.as_deref())?;
     let save_path = derive_save_path(rom_input_path, cli.entry.as_deref());
 
+    let mut cpu = Cpu::from_rom(rom)?;
+    load_battery_ram_from_disk(&mut cpu, save_path.as_deref());
+    if let Some(pc) = cli.pc {
+        cpu.registers.pc = pc;
+    }
+
+    if let Some((start, len)) = cli.dump {
+        for _ in 0..cli.frames.unwrap_or(0) {
+            cpu.run_frame();
+        }
+        let bytes: Vec<u8> = (0..len)
+            .map(|offset| cpu.peek(Addr(start.wrapping_add(offset as u16))))
+            .collect();
+        print!("{}", debugger::hex_dump(start, &bytes));
+        return Ok(());
+    }
+
     if let Some(frames) = cli.test_frames {
-        let mut cpu = Cpu::new();
-        cpu.load_rom(rom);
-        load_battery_ram_from_disk(&mut cpu, save_path.as_deref());
         let serial = app::run_headless(cpu, frames);
         if serial == MOONEYE_PASS {
             println!("PASS");
@@ -30,18 +44,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    let mut cpu = Cpu::new();
-    cpu.load_rom(rom);
-    load_battery_ram_from_disk(&mut cpu, save_path.as_deref());
     let (backend_kind, backend_options) = config::load_graphics_settings()?;
-    let window_scale = config::load_window_scale()?;
+    let window_settings = config::load_window_settings()?;
     let controls = config::load_controls()?;
     let debug_dump_settings = config::load_debug_dump_settings()?;
     app::run_loop(
         cpu,
         backend_kind,
         backend_options,
-        window_scale,
+        window_settings,
         controls,
         debug_dump_settings,
         save_path,
@@ -52,6 +63,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 struct CliArgs {
     test_frames: Option<usize>,
     entry: Option<String>,
+    pc: Option<u16>,
+    dump: Option<(u16, usize)>,
+    frames: Option<usize>,
     rom_path: String,
 }
 
@@ -60,10 +74,16 @@ fn parse_cli_args(args: &[String]) -> Result<CliArgs, rom_loader::RomLoadError>
         .first()
         .cloned()
         .unwrap_or_else(|| "gabalah".to_string());
-    let usage = format!("Usage: {program} [--test <frames>] [--entry <archive-path>] <rom file>");
+    let usage = format!(
+        "Usage: {program} [--test <frames>] [--entry <archive-path>] [--pc <hex>] \
+         [--dump <start>:<len> [--frames <n>]] <rom file>"
+    );
 
     let mut test_frames = None;
     let mut entry = None;
+    let mut pc = None;
+    let mut dump = None;
+    let mut frames = None;
     let mut rom_path = None;
     let mut i = 1;
 
@@ -103,6 +123,64 @@ fn parse_cli_args(args: &[String]) -> Result<CliArgs, rom_loader::RomLoadError>
                 };
                 entry = Some(raw_entry.clone());
             }
+            "--pc" => {
+                if pc.is_some() {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(
+                        "`--pc` may only be provided once".to_string(),
+                    ));
+                }
+                i += 1;
+                let Some(raw_pc) = args.get(i) else {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "missing address after `--pc`\n{usage}"
+                    )));
+                };
+                let address = parse_hex_u16(raw_pc).ok_or_else(|| {
+                    rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "invalid address `{raw_pc}` for `--pc` (expected hex 0000-FFFF)\n{usage}"
+                    ))
+                })?;
+                pc = Some(address);
+            }
+            "--dump" => {
+                if dump.is_some() {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(
+                        "`--dump` may only be provided once".to_string(),
+                    ));
+                }
+                i += 1;
+                let Some(raw_range) = args.get(i) else {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "missing range after `--dump`\n{usage}"
+                    )));
+                };
+                let range = parse_dump_range(raw_range).ok_or_else(|| {
+                    rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "invalid range `{raw_range}` for `--dump` (expected hex <start>:<len> \
+                         within 0000-FFFF)\n{usage}"
+                    ))
+                })?;
+                dump = Some(range);
+            }
+            "--frames" => {
+                if frames.is_some() {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(
+                        "`--frames` may only be provided once".to_string(),
+                    ));
+                }
+                i += 1;
+                let Some(raw_frames) = args.get(i) else {
+                    return Err(rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "missing frame count after `--frames`\n{usage}"
+                    )));
+                };
+                let count = raw_frames.parse::<usize>().map_err(|_| {
+                    rom_loader::RomLoadError::InvalidCliArgument(format!(
+                        "invalid frame count `{raw_frames}` for `--frames`\n{usage}"
+                    ))
+                })?;
+                frames = Some(count);
+            }
             value if value.starts_with("--") => {
                 return Err(rom_loader::RomLoadError::InvalidCliArgument(format!(
                     "unknown argument `{value}`\n{usage}"
@@ -123,14 +201,50 @@ fn parse_cli_args(args: &[String]) -> Result<CliArgs, rom_loader::RomLoadError>
     let Some(rom_path) = rom_path else {
         return Err(rom_loader::RomLoadError::InvalidCliArgument(usage));
     };
+    if frames.is_some() && dump.is_none() {
+        return Err(rom_loader::RomLoadError::InvalidCliArgument(format!(
+            "`--frames` only applies together with `--dump`\n{usage}"
+        )));
+    }
 
     Ok(CliArgs {
         test_frames,
         entry,
+        pc,
+        dump,
+        frames,
         rom_path,
     })
 }
 
+/// Parses a 16-bit hex value, with or without a `0x`/`0X` prefix.
+fn parse_hex_u16(raw: &str) -> Option<u16> {
+    u16::try_from(parse_hex(raw)?).ok()
+}
+
+fn parse_hex(raw: &str) -> Option<usize> {
+    let digits = raw
+        .strip_prefix("0x")
+        .or_else(|| raw.strip_prefix("0X"))
+        .unwrap_or(raw);
+    if digits.is_empty() || digits.starts_with('+') {
+        return None;
+    }
+    usize::from_str_radix(digits, 16).ok()
+}
+
+/// Parses a `<start>:<len>` dump range, both in hex. The range must be non-empty and end
+/// within the 64 KiB address space.
+fn parse_dump_range(raw: &str) -> Option<(u16, usize)> {
+    let (raw_start, raw_len) = raw.split_once(':')?;
+    let start = parse_hex_u16(raw_start)?;
+    let len = parse_hex(raw_len)?;
+    if len == 0 || start as usize + len > 0x10000 {
+        return None;
+    }
+    Some((start, len))
+}
+
 fn derive_save_path(rom_input_path: &Path, entry: Option<&str>) -> Option<PathBuf> {
     if entry.is_some() {
         return None;
@@ -180,7 +294,7 @@ fn load_battery_ram_from_disk(cpu: &mut Cpu, save_path: Option<&Path>) {
 
 #[cfg(test)]
 mod tests {
-    use super::{derive_save_path, parse_cli_args, CliArgs};
+    use super::{derive_save_path, parse_cli_args, parse_dump_range, parse_hex_u16, CliArgs};
     use std::path::Path;
 
     fn args(items: &[&str]) -> Vec<String> {
@@ -200,6 +314,9 @@ mod tests {
             cli,
             CliArgs {
                 test_frames: None,
+                pc: None,
+                dump: None,
+                frames: None,
                 entry: Some("roms/game.gb".to_string()),
                 rom_path: "archive.zip".to_string(),
             }
@@ -221,6 +338,9 @@ mod tests {
             cli,
             CliArgs {
                 test_frames: Some(1200),
+                pc: None,
+                dump: None,
+                frames: None,
                 entry: Some("suite/pass.gb".to_string()),
                 rom_path: "tests.7z".to_string(),
             }
@@ -275,6 +395,60 @@ mod tests {
             .contains("unexpected extra positional argument"));
     }
 
+    #[test]
+    fn parses_pc_override() {
+        let cli = parse_cli_args(&args(&["gabalah", "--pc", "0x0150", "rom.gb"]))
+            .expect("--pc should parse");
+        assert_eq!(cli.pc, Some(0x0150));
+    }
+
+    #[test]
+    fn parse_hex_u16_accepts_optional_prefix() {
+        assert_eq!(parse_hex_u16("150"), Some(0x0150));
+        assert_eq!(parse_hex_u16("0x150"), Some(0x0150));
+        assert_eq!(parse_hex_u16("0XFFFF"), Some(0xFFFF));
+        assert_eq!(parse_hex_u16("c000"), Some(0xC000));
+    }
+
+    #[test]
+    fn parse_hex_u16_rejects_invalid_or_out_of_range() {
+        assert_eq!(parse_hex_u16(""), None);
+        assert_eq!(parse_hex_u16("0x"), None);
+        assert_eq!(parse_hex_u16("10000"), None);
+        assert_eq!(parse_hex_u16("0x1G"), None);
+        assert_eq!(parse_hex_u16("+150"), None);
+        assert_eq!(parse_hex_u16("-1"), None);
+    }
+
+    #[test]
+    fn parses_dump_with_frames() {
+        let cli = parse_cli_args(&args(&[
+            "gabalah", "--dump", "8000:200", "--frames", "30", "rom.gb",
+        ]))
+        .expect("--dump and --frames should parse");
+        assert_eq!(cli.dump, Some((0x8000, 0x200)));
+        assert_eq!(cli.frames, Some(30));
+    }
+
+    #[test]
+    fn rejects_frames_without_dump() {
+        let err = parse_cli_args(&args(&["gabalah", "--frames", "30", "rom.gb"]))
+            .expect_err("--frames alone should fail");
+        assert!(err
+            .to_string()
+            .contains("only applies together with `--dump`"));
+    }
+
+    #[test]
+    fn parse_dump_range_checks_bounds() {
+        assert_eq!(parse_dump_range("0x0134:0x10"), Some((0x0134, 0x10)));
+        assert_eq!(parse_dump_range("FFF0:10"), Some((0xFFF0, 0x10)));
+        assert_eq!(parse_dump_range("FFF0:11"), None);
+        assert_eq!(parse_dump_range("0134:0"), None);
+        assert_eq!(parse_dump_range("0134"), None);
+        assert_eq!(parse_dump_range(":10"), None);
+    }
+
     #[test]
     fn derive_save_path_for_raw_rom() {
         let path = derive_save_path(Path::new("roms/zelda.gb"), None)
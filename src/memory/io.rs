@@ -0,0 +1,128 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+/// Joypad button group select and state
+pub const JOYP: u16 = 0xFF00;
+/// Serial transfer data
+pub const SB: u16 = 0xFF01;
+/// Serial transfer control
+pub const SC: u16 = 0xFF02;
+/// Divider: high byte of the internal timer counter
+pub const DIV: u16 = 0xFF04;
+/// Timer counter
+pub const TIMA: u16 = 0xFF05;
+/// Timer modulo, reloaded into TIMA on overflow
+pub const TMA: u16 = 0xFF06;
+/// Timer control
+pub const TAC: u16 = 0xFF07;
+/// Interrupt flags (requested interrupts)
+pub const IF: u16 = 0xFF0F;
+/// Sound channel 1 period, low 8 bits
+pub const NR13: u16 = 0xFF13;
+/// Sound channel 1 trigger, length enable and period high 3 bits
+pub const NR14: u16 = 0xFF14;
+/// Sound channel 2 period, low 8 bits
+pub const NR23: u16 = 0xFF18;
+/// Sound channel 2 trigger, length enable and period high 3 bits
+pub const NR24: u16 = 0xFF19;
+/// Sound channel 3 (wave) period, low 8 bits
+pub const NR33: u16 = 0xFF1D;
+/// Sound channel 3 trigger, length enable and period high 3 bits
+pub const NR34: u16 = 0xFF1E;
+/// Sound channel 4 (noise) clock shift, LFSR width and clock divider
+pub const NR43: u16 = 0xFF22;
+/// LCD control
+pub const LCDC: u16 = 0xFF40;
+/// LCD status
+pub const STAT: u16 = 0xFF41;
+/// Background scroll Y
+pub const SCY: u16 = 0xFF42;
+/// Background scroll X
+pub const SCX: u16 = 0xFF43;
+/// Current scanline
+pub const LY: u16 = 0xFF44;
+/// Scanline compare
+pub const LYC: u16 = 0xFF45;
+/// OAM DMA source page
+pub const DMA: u16 = 0xFF46;
+/// Background palette
+pub const BGP: u16 = 0xFF47;
+/// Object palette 0
+pub const OBP0: u16 = 0xFF48;
+/// Object palette 1
+pub const OBP1: u16 = 0xFF49;
+/// Window Y position
+pub const WY: u16 = 0xFF4A;
+/// Window X position plus 7
+pub const WX: u16 = 0xFF4B;
+/// CGB VRAM bank select (bit 0)
+pub const VBK: u16 = 0xFF4F;
+/// Boot ROM disable latch: any nonzero write unmaps the boot ROM for good
+pub const BOOT: u16 = 0xFF50;
+/// CGB background palette index (bit 7: auto-increment)
+pub const BCPS: u16 = 0xFF68;
+/// CGB background palette data at the BCPS index
+pub const BCPD: u16 = 0xFF69;
+/// CGB object palette index (bit 7: auto-increment)
+pub const OCPS: u16 = 0xFF6A;
+/// CGB object palette data at the OCPS index
+pub const OCPD: u16 = 0xFF6B;
+/// CGB WRAM bank select for 0xD000-0xDFFF (bits 0-2; 0 selects bank 1)
+pub const SVBK: u16 = 0xFF70;
+/// Interrupt enable
+pub const IE: u16 = 0xFFFF;
+
+/// True for addresses in the I/O range 0xFF00-0xFF7F that no DMG register backs. They read as
+/// 0xFF regardless of what was written. With the `cgb` feature the CGB registers are mapped.
+pub fn is_unmapped_io(address: u16) -> bool {
+    #[cfg(feature = "cgb")]
+    if matches!(address, VBK | BCPS..=OCPD | SVBK) {
+        return false;
+    }
+    matches!(
+        address,
+        0xFF03 | 0xFF08..=0xFF0E | 0xFF15 | 0xFF1F | 0xFF27..=0xFF2F | 0xFF4C..=0xFF7F
+    )
+}
+
+/// The conventional name of the I/O register at `address`, for debug output.
+pub fn io_register_name(address: u16) -> Option<&'static str> {
+    Some(match address {
+        JOYP => "JOYP",
+        SB => "SB",
+        SC => "SC",
+        DIV => "DIV",
+        TIMA => "TIMA",
+        TMA => "TMA",
+        TAC => "TAC",
+        IF => "IF",
+        NR13 => "NR13",
+        NR14 => "NR14",
+        NR23 => "NR23",
+        NR24 => "NR24",
+        NR33 => "NR33",
+        NR34 => "NR34",
+        NR43 => "NR43",
+        LCDC => "LCDC",
+        STAT => "STAT",
+        SCY => "SCY",
+        SCX => "SCX",
+        LY => "LY",
+        LYC => "LYC",
+        DMA => "DMA",
+        BGP => "BGP",
+        OBP0 => "OBP0",
+        OBP1 => "OBP1",
+        WY => "WY",
+        WX => "WX",
+        VBK => "VBK",
+        BOOT => "BOOT",
+        BCPS => "BCPS",
+        BCPD => "BCPD",
+        OCPS => "OCPS",
+        OCPD => "OCPD",
+        SVBK => "SVBK",
+        IE => "IE",
+        _ => return None,
+    })
+}
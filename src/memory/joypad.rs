@@ -0,0 +1,43 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+/// A button on the DMG joypad. Discriminants index `Button::ALL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+impl Button {
+    pub const ALL: [Button; 8] = [
+        Button::Right,
+        Button::Left,
+        Button::Up,
+        Button::Down,
+        Button::A,
+        Button::B,
+        Button::Select,
+        Button::Start,
+    ];
+
+    /// True for A/B/Select/Start, which are read with P15 (bit 5 of 0xFF00) low.
+    pub fn is_action(self) -> bool {
+        matches!(self, Button::A | Button::B | Button::Select | Button::Start)
+    }
+
+    /// The button's bit within its group's low nibble of 0xFF00.
+    pub fn mask(self) -> u8 {
+        match self {
+            Button::Right | Button::A => 0x01,
+            Button::Left | Button::B => 0x02,
+            Button::Up | Button::Select => 0x04,
+            Button::Down | Button::Start => 0x08,
+        }
+    }
+}
@@ -1,3 +1,16 @@
+pub mod io;
+mod joypad;
+#[cfg(feature = "cgb")]
+mod palette;
 mod ram;
+mod region;
+mod tiles;
+mod timer;
 
-pub use ram::{Addr, Ram, Registers};
+pub use joypad::Button;
+#[cfg(feature = "cgb")]
+pub use palette::{bgr555_to_rgba, PaletteRam};
+pub use ram::{Addr, Flags, InitPattern, Ram, Registers, RAM_START};
+pub use region::{region, MemRegion};
+pub use tiles::DirtyTiles;
+pub use timer::Timer;
@@ -0,0 +1,104 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+/// One set of CGB palette memory: 8 palettes of 4 BGR555 colours, 64 bytes in all. Reached
+/// through an index register (BCPS/OCPS) and a data register (BCPD/OCPD), mapped by `Ram`.
+#[derive(Debug, Clone)]
+pub struct PaletteRam {
+    data: [u8; PALETTE_RAM_SIZE],
+    /// Bits 0-5: byte index into `data`; bit 7: advance the index after each data write
+    index: u8,
+}
+
+const PALETTE_RAM_SIZE: usize = 64;
+
+impl Default for PaletteRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaletteRam {
+    /// Palette memory as the CGB boot ROM leaves it for a DMG-compatible game: all white.
+    pub fn new() -> Self {
+        Self {
+            data: [0xFF; PALETTE_RAM_SIZE],
+            index: 0,
+        }
+    }
+
+    /// BCPS/OCPS; bit 6 is unused and reads as 1.
+    pub fn read_index(&self) -> u8 {
+        self.index | 0x40
+    }
+
+    pub fn write_index(&mut self, value: u8) {
+        self.index = value & 0xBF;
+    }
+
+    /// BCPD/OCPD: the byte under the index.
+    pub fn read_data(&self) -> u8 {
+        self.data[(self.index & 0x3F) as usize]
+    }
+
+    pub fn write_data(&mut self, value: u8) {
+        self.data[(self.index & 0x3F) as usize] = value;
+        if self.index & 0x80 != 0 {
+            self.index = 0x80 | (self.index.wrapping_add(1) & 0x3F);
+        }
+    }
+
+    /// The BGR555 colour `color` (0-3) of palette `palette` (0-7), stored little-endian.
+    pub fn color(&self, palette: u8, color: u8) -> u16 {
+        let offset = ((palette & 0x07) as usize * 4 + (color & 0x03) as usize) * 2;
+        u16::from_le_bytes([self.data[offset], self.data[offset + 1]])
+    }
+
+    /// Like `color`, converted to RGBA.
+    pub fn rgba(&self, palette: u8, color: u8) -> [u8; 4] {
+        bgr555_to_rgba(self.color(palette, color))
+    }
+}
+
+/// Expands a CGB BGR555 colour (red in bits 0-4, green 5-9, blue 10-14) to opaque RGBA,
+/// repeating each channel's top bits so 0x1F maps to 0xFF.
+pub fn bgr555_to_rgba(color: u16) -> [u8; 4] {
+    let channel = |shift: u16| {
+        let value = ((color >> shift) & 0x1F) as u8;
+        (value << 3) | (value >> 2)
+    };
+    [channel(0), channel(5), channel(10), 0xFF]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgr555_expands_each_channel_to_eight_bits() {
+        assert_eq!(bgr555_to_rgba(0x0000), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(bgr555_to_rgba(0x7FFF), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(bgr555_to_rgba(0x001F), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(bgr555_to_rgba(0x03E0), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(bgr555_to_rgba(0x7C00), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(bgr555_to_rgba(0x0010), [0x84, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn data_writes_auto_increment_only_when_bit_7_is_set() {
+        let mut palettes = PaletteRam::new();
+        palettes.write_index(0x80 | 0x3F);
+        palettes.write_data(0x12);
+        assert_eq!(palettes.read_index(), 0xC0, "index wraps within 64 bytes");
+        palettes.write_data(0x34);
+        palettes.write_data(0x56);
+        assert_eq!(palettes.color(0, 0), 0x5634);
+        assert_eq!(palettes.color(7, 3), 0x12FF);
+
+        palettes.write_index(0x02);
+        palettes.write_data(0xAA);
+        palettes.write_data(0xBB);
+        assert_eq!(palettes.read_index(), 0x42);
+        assert_eq!(palettes.read_data(), 0xBB);
+    }
+}
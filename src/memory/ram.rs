@@ -1,6 +1,30 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::io;
+#[cfg(feature = "cgb")]
+use super::palette::PaletteRam;
+use super::tiles::DirtyTiles;
+use super::timer::Timer;
+#[cfg(feature = "cgb")]
+use crate::cartridge::CgbMode;
 use crate::cartridge::{Cartridge, CartridgeHeader};
+use crate::cheats::GenieCode;
 
 const VISIBLE_ROM_END: usize = 0x7FFF;
+const BOOT_ROM_SIZE: usize = 0x100;
+const VRAM_START: usize = 0x8000;
+const VRAM_END: usize = 0x9FFF;
+#[cfg(feature = "cgb")]
+const VRAM_SIZE: usize = VRAM_END - VRAM_START + 1;
+#[cfg(feature = "cgb")]
+const WRAM_BANKED_START: usize = 0xD000;
+#[cfg(feature = "cgb")]
+const WRAM_BANKED_END: usize = 0xDFFF;
+#[cfg(feature = "cgb")]
+const WRAM_BANK_SIZE: usize = WRAM_BANKED_END - WRAM_BANKED_START + 1;
 const EXTERNAL_RAM_START: usize = 0xA000;
 const EXTERNAL_RAM_END: usize = 0xBFFF;
 
@@ -9,7 +33,7 @@ pub fn word(hi: u8, lo: u8) -> u16 {
 }
 
 /// The Game Boy's CPU registers
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct Registers {
     /// accumulator A
     pub a: u8,
@@ -35,6 +59,40 @@ pub struct Registers {
     pub ime: bool,
 }
 
+/// The flags register F as separate bits, for tests and debuggers
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flags {
+    /// Z, bit 7
+    pub zero: bool,
+    /// N, bit 6
+    pub subtraction: bool,
+    /// H, bit 5
+    pub half_carry: bool,
+    /// C, bit 4
+    pub carry: bool,
+}
+
+impl From<u8> for Flags {
+    /// The low nibble of F is always zero on hardware and is ignored.
+    fn from(f: u8) -> Flags {
+        Flags {
+            zero: f & 0x80 != 0,
+            subtraction: f & 0x40 != 0,
+            half_carry: f & 0x20 != 0,
+            carry: f & 0x10 != 0,
+        }
+    }
+}
+
+impl From<Flags> for u8 {
+    fn from(flags: Flags) -> u8 {
+        (flags.zero as u8) << 7
+            | (flags.subtraction as u8) << 6
+            | (flags.half_carry as u8) << 5
+            | (flags.carry as u8) << 4
+    }
+}
+
 impl Registers {
     /// returns an instance of Registers with every register set to 0
     pub fn new() -> Registers {
@@ -96,10 +154,23 @@ impl Registers {
         self.h = hi(value);
         self.l = lo(value);
     }
+
+    /// returns the flags register F as individual flags
+    pub fn flags(&self) -> Flags {
+        Flags::from(self.f)
+    }
+
+    /// sets the flags register F from individual flags
+    pub fn set_flags(&mut self, flags: Flags) {
+        self.f = flags.into();
+    }
 }
 
-/// The size of the Game Boy's RAM in bytes
-pub const RAM_SIZE: usize = 64 * 1024;
+/// The first address `Ram` backs with its own array; the cartridge serves everything below.
+/// `Ram::as_slice()[0]` is this address.
+pub const RAM_START: usize = 0x8000;
+/// The size of that array: 0x8000-0xFFFF
+const RAM_SIZE: usize = 0x10000 - RAM_START;
 
 /// Return the high byte of the provided word
 pub fn hi(word: u16) -> u8 {
@@ -112,113 +183,290 @@ pub fn lo(word: u16) -> u8 {
 }
 
 /// A 16-bit address into the Game Boy's RAM
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Addr(pub u16);
 
+impl Addr {
+    /// The following address, wrapping from 0xFFFF to 0x0000.
+    pub fn next(self) -> Addr {
+        self.wrapping_add(1)
+    }
+
+    pub fn wrapping_add(self, offset: u16) -> Addr {
+        Addr(self.0.wrapping_add(offset))
+    }
+
+    /// Adds a signed offset, e.g. a relative jump displacement, wrapping at either end.
+    pub fn wrapping_offset(self, offset: i16) -> Addr {
+        Addr(self.0.wrapping_add_signed(offset))
+    }
+}
+
 impl Default for Ram {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Power-on contents of VRAM, WRAM, OAM and HRAM. I/O registers always start in their
+/// post-boot state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitPattern {
+    #[default]
+    Zero,
+    Ones,
+    /// Zeroed RAM with VRAM as the DMG boot ROM leaves it: the Nintendo logo and (R) tiles at
+    /// 0x8010-0x819F and their tile map entries at 0x9904-0x992F.
+    DmgBootVram,
+}
+
+/// The logo bitmap the boot ROM checks against cartridge header bytes 0x0104-0x0133.
+const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+const REGISTERED_MARK: [u8; 8] = [0x3C, 0x42, 0xB9, 0xA5, 0xB9, 0xA5, 0x42, 0x3C];
+
 /// The Game Boy's random-access memory
 #[derive(Debug)]
 pub struct Ram {
+    /// 0x8000-0xFFFF, indexed by `address - RAM_START`
     cells: [u8; RAM_SIZE],
     cartridge: Option<Cartridge>,
+    /// Plain RAM standing in for 0x0000-0x7FFF until a cartridge is loaded, so programs can be
+    /// written there directly; `load_rom` frees it
+    rom_area: Vec<u8>,
     /// Bits 4-5 of the last write to 0xFF00: selects which button group to read
     joypad_select: u8,
     /// Active-high bitmask of pressed action buttons (bit 0=A, 1=B, 2=Select, 3=Start)
     pub action_buttons: u8,
     /// Active-high bitmask of pressed direction buttons (bit 0=Right, 1=Left, 2=Up, 3=Down)
     pub direction_buttons: u8,
-    /// Internal 16-bit counter backing DIV (0xFF04); DIV register = high byte
-    div_counter: u32,
-    /// Accumulated cycles since last TIMA increment
-    tima_counter: u32,
+    timer: Timer,
     /// Bytes captured from serial transfers (0xFF01 at each 0xFF02 write with bit 7 set)
     pub serial_output: Vec<u8>,
+    /// The OAM DMA transfer in progress, if any
+    dma: Option<DmaTransfer>,
+    /// VRAM tiles whose data changed since the renderer last took them
+    dirty_tiles: DirtyTiles,
+    /// Game Genie patches applied to ROM reads
+    genie_codes: Vec<GenieCode>,
+    /// The boot ROM overlaying 0x0000-0x00FF until a nonzero write to 0xFF50 drops it
+    boot_rom: Option<Vec<u8>>,
+    /// CGB background palette memory behind BCPS/BCPD
+    #[cfg(feature = "cgb")]
+    bg_palettes: PaletteRam,
+    /// CGB object palette memory behind OCPS/OCPD
+    #[cfg(feature = "cgb")]
+    obj_palettes: PaletteRam,
+    /// CGB VRAM bank 1 (tile attributes and extra tile data); bank 0 lives in `cells`
+    #[cfg(feature = "cgb")]
+    vram_bank1: Vec<u8>,
+    /// VBK bit 0: the VRAM bank the CPU sees at 0x8000-0x9FFF
+    #[cfg(feature = "cgb")]
+    vram_bank: u8,
+    /// CGB WRAM banks 2-7; bank 1, the only one a DMG has at 0xD000, lives in `cells`
+    #[cfg(feature = "cgb")]
+    wram_banks: Vec<u8>,
+    /// SVBK: the WRAM bank (1-7) mapped at 0xD000-0xDFFF
+    #[cfg(feature = "cgb")]
+    wram_bank: u8,
+}
+
+/// Bytes in one OAM DMA transfer; the hardware copies one per machine cycle.
+const DMA_LENGTH: u16 = 160;
+
+/// An OAM DMA transfer started by a write to 0xFF46.
+#[derive(Debug, Clone, Copy)]
+struct DmaTransfer {
+    source: u16,
+    /// CPU cycles since the transfer started
+    elapsed: u32,
 }
 
 impl Ram {
     /// Returns an instance of Ram with post-boot DMG0 hardware register state
     pub fn new() -> Ram {
+        Self::with_pattern(InitPattern::Zero)
+    }
+
+    /// Like `new`, with RAM filled according to `pattern`.
+    pub fn with_pattern(pattern: InitPattern) -> Ram {
         let mut ram = Ram {
             cells: [0; RAM_SIZE],
             cartridge: None,
+            rom_area: vec![0; RAM_START],
             joypad_select: 0x30,
             action_buttons: 0,
             direction_buttons: 0,
-            div_counter: 0x183A,
-            tima_counter: 0,
+            timer: Timer::new(),
             serial_output: Vec::new(),
+            dma: None,
+            dirty_tiles: DirtyTiles::ALL,
+            genie_codes: Vec::new(),
+            boot_rom: None,
+            #[cfg(feature = "cgb")]
+            bg_palettes: PaletteRam::new(),
+            #[cfg(feature = "cgb")]
+            obj_palettes: PaletteRam::new(),
+            #[cfg(feature = "cgb")]
+            vram_bank1: vec![0; VRAM_SIZE],
+            #[cfg(feature = "cgb")]
+            vram_bank: 0,
+            #[cfg(feature = "cgb")]
+            wram_banks: vec![0; WRAM_BANK_SIZE * 6],
+            #[cfg(feature = "cgb")]
+            wram_bank: 1,
         };
-        ram.cells[0xFF07] = 0xF8; // TAC: upper bits set, timer disabled
-        ram.cells[0xFF0F] = 0xE1; // IF: VBlank + upper unused bits set
-        ram.cells[0xFF40] = 0x91; // LCDC: display on, BG enabled, unsigned tile data
-        ram.cells[0xFF41] = 0x80; // STAT: upper bit set, mode/coincidence initialized to 0
-        ram.cells[0xFF47] = 0xFC; // BGP: shades 3,3,2,0
-        ram.cells[0xFF48] = 0xFF; // OBP0
-        ram.cells[0xFF49] = 0xFF; // OBP1
+        ram.cells[io::IF as usize - RAM_START] = 0xE1; // IF: VBlank + upper unused bits set
+        ram.cells[io::LCDC as usize - RAM_START] = 0x91; // LCDC: display on, BG enabled, unsigned tile data
+        ram.cells[io::STAT as usize - RAM_START] = 0x80; // STAT: upper bit set, mode/coincidence initialized to 0
+        ram.cells[io::BGP as usize - RAM_START] = 0xFC; // BGP: shades 3,3,2,0
+        ram.cells[io::OBP0 as usize - RAM_START] = 0xFF; // OBP0
+        ram.cells[io::OBP1 as usize - RAM_START] = 0xFF; // OBP1
+
+        match pattern {
+            InitPattern::Zero => {}
+            InitPattern::Ones => {
+                ram.cells[VRAM_START - RAM_START..=VRAM_END - RAM_START].fill(0xFF);
+                ram.cells[0xC000 - RAM_START..=0xDFFF - RAM_START].fill(0xFF);
+                #[cfg(feature = "cgb")]
+                {
+                    ram.vram_bank1.fill(0xFF);
+                    ram.wram_banks.fill(0xFF);
+                }
+                ram.cells[0xFE00 - RAM_START..=0xFE9F - RAM_START].fill(0xFF);
+                ram.cells[0xFF80 - RAM_START..=0xFFFE - RAM_START].fill(0xFF);
+            }
+            InitPattern::DmgBootVram => ram.draw_boot_logo(),
+        }
         ram
     }
 
-    /// Loads a ROM into memory
+    /// Mirrors the boot ROM: each logo nibble becomes one 2x-wide tile row, written twice
+    /// (2x tall), low bit plane only, starting at tile 1; the (R) mark follows as tile 0x19.
+    fn draw_boot_logo(&mut self) {
+        let mut address = 0x8010;
+        for byte in NINTENDO_LOGO {
+            for nibble in [byte >> 4, byte & 0x0F] {
+                let row = (0..4).fold(0u8, |row, bit| {
+                    if nibble & (0x08 >> bit) != 0 {
+                        row | (0xC0 >> (bit * 2))
+                    } else {
+                        row
+                    }
+                });
+                self.cells[address - RAM_START] = row;
+                self.cells[address + 2 - RAM_START] = row;
+                address += 4;
+            }
+        }
+        for (row, byte) in REGISTERED_MARK.into_iter().enumerate() {
+            self.cells[0x8190 + row * 2 - RAM_START] = byte;
+        }
+
+        for tile in 1..=12u8 {
+            self.cells[0x9903 + tile as usize - RAM_START] = tile;
+            self.cells[0x9923 + tile as usize - RAM_START] = tile + 12;
+        }
+        self.cells[0x9910 - RAM_START] = 0x19;
+    }
+
+    /// Loads a ROM into memory. The cartridge keeps the whole image and serves 0x0000-0x7FFF
+    /// through its mapper; without one that range is the plain RAM in `rom_area`.
     pub fn load_rom(&mut self, rom: Vec<u8>) {
         self.cartridge = Some(Cartridge::new(rom));
-        self.sync_cartridge_visible_rom();
+        self.rom_area = Vec::new();
     }
 
-    fn sync_cartridge_visible_rom(&mut self) {
-        if let Some(cartridge) = self.cartridge.as_ref() {
-            let (fixed, rest) = self.cells.split_at_mut(0x4000);
-            let switchable = &mut rest[..0x4000];
-            cartridge.copy_visible_windows_into(fixed, switchable);
-        } else {
-            self.cells[0x0000..=VISIBLE_ROM_END].fill(0xFF);
+    /// Maps a 256-byte boot ROM over 0x0000-0x00FF. It stays mapped until a nonzero write to
+    /// 0xFF50, after which the cartridge shows through again for the rest of the session.
+    pub fn load_boot_rom(&mut self, boot_rom: Vec<u8>) -> Result<(), String> {
+        if boot_rom.len() != BOOT_ROM_SIZE {
+            return Err(format!(
+                "boot ROM must be {BOOT_ROM_SIZE} bytes, got {}",
+                boot_rom.len()
+            ));
         }
+        self.boot_rom = Some(boot_rom);
+        Ok(())
+    }
+
+    /// True until the boot ROM is unmapped through 0xFF50 (or when none was loaded).
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
     }
 
     /// Sets the byte at the specified address to the specified value
     pub fn write_byte(&mut self, address: Addr, value: u8) {
         let addr = address.0 as usize;
-        if address.0 == 0xFF00 {
+        if address.0 == io::JOYP {
             self.joypad_select = value & 0x30;
             return;
         }
-        if address.0 == 0xFF04 {
-            self.div_counter = 0;
-            self.cells[0xFF04] = 0;
+        if Timer::contains(address.0) {
+            self.timer.write(address.0, value);
+            return;
+        }
+        if address.0 == io::SC && value & 0x81 == 0x81 {
+            self.serial_output
+                .push(self.cells[io::SB as usize - RAM_START]);
+            self.cells[io::SC as usize - RAM_START] = value & 0x7F;
+            self.cells[io::IF as usize - RAM_START] |= 0x08;
+            return;
+        }
+        if address.0 == io::BOOT {
+            // One-way latch: once unmapped the boot ROM is gone, so later writes change nothing.
+            if value != 0 {
+                self.boot_rom = None;
+            }
             return;
         }
-        if address.0 == 0xFF02 && value & 0x81 == 0x81 {
-            self.serial_output.push(self.cells[0xFF01]);
-            self.cells[0xFF02] = value & 0x7F;
-            self.cells[0xFF0F] |= 0x08;
+        #[cfg(feature = "cgb")]
+        match address.0 {
+            io::BCPS => return self.bg_palettes.write_index(value),
+            io::BCPD => return self.bg_palettes.write_data(value),
+            io::OCPS => return self.obj_palettes.write_index(value),
+            io::OCPD => return self.obj_palettes.write_data(value),
+            io::VBK => {
+                self.vram_bank = value & 0x01;
+                return;
+            }
+            io::SVBK => {
+                self.wram_bank = (value & 0x07).max(1);
+                return;
+            }
+            _ => {}
+        }
+        if address.0 == io::DMA {
+            // Starting a transfer also cancels one in progress.
+            self.cells[addr - RAM_START] = value;
+            self.dma = Some(DmaTransfer {
+                source: (value as u16) << 8,
+                elapsed: 0,
+            });
             return;
         }
-        if address.0 == 0xFF46 {
-            let src_base = (value as usize) << 8;
-            self.cells.copy_within(src_base..src_base + 160, 0xFE00);
+        if self.dma_blocks_access(address.0) {
             return;
         }
-        if address.0 == 0xFF41 {
+        if address.0 == io::STAT {
             // STAT: bits 0-2 are read-only (mode + coincidence), bits 3-6 writable, bit 7 always set.
-            let ro = self.cells[0xFF41] & 0x07;
-            self.cells[0xFF41] = 0x80 | (value & 0x78) | ro;
+            let ro = self.cells[io::STAT as usize - RAM_START] & 0x07;
+            self.cells[io::STAT as usize - RAM_START] = 0x80 | (value & 0x78) | ro;
             return;
         }
-        if address.0 == 0xFF44 {
-            // LY resets to zero on write.
-            self.cells[0xFF44] = 0;
+        if address.0 == io::LY {
+            // LY is read-only; only the PPU advances it (through `set_ly_raw`).
             return;
         }
         // Cartridge ROM area. After a cartridge is loaded, writes are delegated to mapper control.
-        if addr <= VISIBLE_ROM_END && self.cartridge.is_some() {
-            if let Some(cartridge) = self.cartridge.as_mut() {
-                cartridge.write_rom_control(address.0, value);
+        if addr <= VISIBLE_ROM_END {
+            match self.cartridge.as_mut() {
+                Some(cartridge) => cartridge.write_rom_control(address.0, value),
+                None => self.rom_area[addr] = value,
             }
-            self.sync_cartridge_visible_rom();
             return;
         }
         if (EXTERNAL_RAM_START..=EXTERNAL_RAM_END).contains(&addr) && self.cartridge.is_some() {
@@ -229,34 +477,104 @@ impl Ram {
         }
         // Echo RAM mirrors C000-DDFF.
         if (0xE000..=0xFDFF).contains(&addr) {
-            self.cells[addr - 0x2000] = value;
+            self.write_wram(addr - 0x2000, value);
             return;
         }
         // Unusable memory area.
         if (0xFEA0..=0xFEFF).contains(&addr) {
             return;
         }
-        self.cells[addr] = value;
+        #[cfg(feature = "cgb")]
+        if self.vram_bank == 1 && (VRAM_START..=VRAM_END).contains(&addr) {
+            self.vram_bank1[addr - VRAM_START] = value;
+            return;
+        }
+        if (VRAM_START..=VRAM_END).contains(&addr) && self.cells[addr - RAM_START] != value {
+            self.dirty_tiles.mark(address.0);
+        }
+        self.write_wram(addr, value);
+    }
+
+    /// Stores `value` at `addr`, routed to the selected CGB WRAM bank for 0xD000-0xDFFF.
+    fn write_wram(&mut self, addr: usize, value: u8) {
+        #[cfg(feature = "cgb")]
+        if let Some(offset) = self.banked_wram_offset(addr) {
+            self.wram_banks[offset] = value;
+            return;
+        }
+        self.cells[addr - RAM_START] = value;
+    }
+
+    /// Reads `addr` from the cells, or from the selected CGB WRAM bank for 0xD000-0xDFFF.
+    fn read_wram(&self, addr: usize) -> u8 {
+        #[cfg(feature = "cgb")]
+        if let Some(offset) = self.banked_wram_offset(addr) {
+            return self.wram_banks[offset];
+        }
+        self.cells[addr - RAM_START]
+    }
+
+    /// The offset into `wram_banks` of `addr` while SVBK selects bank 2-7 and `addr` is in
+    /// 0xD000-0xDFFF.
+    #[cfg(feature = "cgb")]
+    fn banked_wram_offset(&self, addr: usize) -> Option<usize> {
+        (self.wram_bank >= 2 && (WRAM_BANKED_START..=WRAM_BANKED_END).contains(&addr))
+            .then(|| (self.wram_bank as usize - 2) * WRAM_BANK_SIZE + addr - WRAM_BANKED_START)
     }
 
     /// Sets the word at the specified address to the specified value
     pub fn write_word(&mut self, address: Addr, value: u16) {
-        self.cells[address.0 as usize] = lo(value);
-        self.cells[address.0.wrapping_add(1) as usize] = hi(value);
+        self.write_byte(address, lo(value));
+        self.write_byte(address.next(), hi(value));
     }
 
-    /// Retrieves the byte at the specified address
+    /// Retrieves the byte at the specified address as the CPU sees it, including the 0xFF
+    /// returned for PPU-blocked VRAM/OAM and unmapped I/O.
     pub fn read_byte(&self, address: Addr) -> u8 {
         let addr = address.0 as usize;
+        if self.dma_blocks_access(address.0) {
+            return 0xFF;
+        }
+        if (0xFEA0..=0xFEFF).contains(&addr) {
+            // Prohibited area: on DMG it reads 0x00, or 0xFF while the PPU has OAM blocked.
+            return if self.ppu_blocks_read(0xFE00) {
+                0xFF
+            } else {
+                0x00
+            };
+        }
+        if self.ppu_blocks_read(addr) || io::is_unmapped_io(address.0) {
+            return 0xFF;
+        }
+        self.peek(address)
+    }
+
+    /// Reads the byte stored at `address` without any bus behaviour: VRAM and OAM are visible
+    /// whatever the PPU mode, and unmapped I/O returns the raw backing byte. Meant for
+    /// debuggers and memory viewers.
+    pub fn peek(&self, address: Addr) -> u8 {
+        let addr = address.0 as usize;
+        if let Some(boot_rom) = &self.boot_rom {
+            if addr < BOOT_ROM_SIZE {
+                return boot_rom[addr];
+            }
+        }
         if addr <= VISIBLE_ROM_END {
-            return self.cells[addr];
+            let original = match self.cartridge.as_ref() {
+                Some(cartridge) => cartridge.read_byte(address.0),
+                None => self.rom_area[addr],
+            };
+            return self
+                .genie_codes
+                .iter()
+                .fold(original, |byte, code| code.apply(address.0, byte));
         }
         if (EXTERNAL_RAM_START..=EXTERNAL_RAM_END).contains(&addr) && self.cartridge.is_some() {
             if let Some(cartridge) = self.cartridge.as_ref() {
                 return cartridge.read_external_ram(address.0);
             }
         }
-        if address.0 == 0xFF00 {
+        if address.0 == io::JOYP {
             let mut lo = 0x0Fu8; // all buttons not pressed (active low)
             if self.joypad_select & 0x20 == 0 {
                 lo &= !self.action_buttons;
@@ -266,77 +584,179 @@ impl Ram {
             }
             return 0xC0 | (self.joypad_select & 0x30) | (lo & 0x0F);
         }
-        if address.0 == 0xFF04 {
-            return (self.div_counter >> 8) as u8;
+        if Timer::contains(address.0) {
+            return self.timer.read(address.0);
         }
-        if (0xE000..=0xFDFF).contains(&addr) {
-            return self.cells[addr - 0x2000];
+        #[cfg(feature = "cgb")]
+        match address.0 {
+            io::BCPS => return self.bg_palettes.read_index(),
+            io::BCPD => return self.bg_palettes.read_data(),
+            io::OCPS => return self.obj_palettes.read_index(),
+            io::OCPD => return self.obj_palettes.read_data(),
+            io::VBK => return 0xFE | self.vram_bank,
+            io::SVBK => return 0xF8 | self.wram_bank,
+            _ => {}
         }
-        if (0xFEA0..=0xFEFF).contains(&addr) {
-            return 0xFF;
+        #[cfg(feature = "cgb")]
+        if self.vram_bank == 1 && (VRAM_START..=VRAM_END).contains(&addr) {
+            return self.vram_bank1[addr - VRAM_START];
+        }
+        if (0xE000..=0xFDFF).contains(&addr) {
+            return self.read_wram(addr - 0x2000);
         }
-        self.cells[addr]
+        self.read_wram(addr)
     }
 
-    /// Advances timer state by `cycles` CPU cycles. Returns true if TIMA overflowed.
-    pub fn tick(&mut self, cycles: u32) -> bool {
-        self.div_counter = self.div_counter.wrapping_add(cycles);
-        self.cells[0xFF04] = (self.div_counter >> 8) as u8;
+    /// Applies the DMG OAM corruption bug for a 16-bit increment/decrement of a register holding
+    /// `address`, `line_cycles` into the current scanline. Only takes effect during OAM scan
+    /// (mode 2) with the pointer in 0xFE00-0xFEFF. OAM is 20 rows of four 16-bit words; the row the
+    /// PPU is scanning gets its first word replaced by `((a ^ c) & (b ^ c)) ^ c` (a: its own first
+    /// word, b/c: first/third word of the previous row) and its other three words copied from the
+    /// previous row. Row 0 is never corrupted.
+    pub fn trigger_oam_bug_write(&mut self, address: u16, line_cycles: usize) {
+        if !(0xFE00..=0xFEFF).contains(&address)
+            || self.cells[io::LCDC as usize - RAM_START] & 0x80 == 0
+            || self.cells[io::STAT as usize - RAM_START] & 0x03 != 2
+        {
+            return;
+        }
+        // The PPU reads one 8-byte row per M-cycle during the 80-cycle OAM scan.
+        let row = line_cycles / 4;
+        if row == 0 || row >= 20 {
+            return;
+        }
+
+        let oam = &mut self.cells[0xFE00 - RAM_START..0xFEA0 - RAM_START];
+        let word = |oam: &[u8], offset: usize| u16::from_le_bytes([oam[offset], oam[offset + 1]]);
+        let current = row * 8;
+        let previous = current - 8;
+        let a = word(oam, current);
+        let b = word(oam, previous);
+        let c = word(oam, previous + 4);
+        let corrupted = ((a ^ c) & (b ^ c)) ^ c;
+        oam[current..current + 2].copy_from_slice(&corrupted.to_le_bytes());
+        oam.copy_within(previous + 2..previous + 8, current + 2);
+    }
 
-        let tac = self.cells[0xFF07];
-        if tac & 0x04 == 0 {
+    /// The PPU owns OAM during modes 2 and 3 and VRAM during mode 3; CPU reads then return 0xFF.
+    /// The mode comes from STAT, which the PPU keeps current, and nothing is blocked with the LCD off.
+    fn ppu_blocks_read(&self, addr: usize) -> bool {
+        if self.cells[io::LCDC as usize - RAM_START] & 0x80 == 0 {
             return false;
         }
+        let mode = self.cells[io::STAT as usize - RAM_START] & 0x03;
+        match addr {
+            0xFE00..=0xFE9F => mode == 2 || mode == 3,
+            0x8000..=0x9FFF => mode == 3,
+            _ => false,
+        }
+    }
 
-        let threshold = match tac & 0x03 {
-            0 => 1024u32,
-            1 => 16,
-            2 => 64,
-            _ => 256,
-        };
+    /// Advances timer and OAM DMA state by `cycles` CPU cycles. Returns true if TIMA
+    /// overflowed.
+    pub fn tick(&mut self, cycles: u32) -> bool {
+        self.advance_dma(cycles);
+        self.timer.tick(cycles)
+    }
 
-        self.tima_counter += cycles;
-        let mut overflow = false;
-        while self.tima_counter >= threshold {
-            self.tima_counter -= threshold;
-            let tima = self.cells[0xFF05];
-            if tima == 0xFF {
-                self.cells[0xFF05] = self.cells[0xFF06];
-                overflow = true;
-            } else {
-                self.cells[0xFF05] = tima + 1;
-            }
+    /// True while an OAM DMA transfer is copying.
+    pub fn dma_active(&self) -> bool {
+        self.dma.is_some()
+    }
+
+    /// While OAM DMA runs the CPU only reaches I/O and HRAM (0xFF00-0xFFFF): the buses behind
+    /// everything else are busy, so reads return 0xFF and writes are dropped.
+    fn dma_blocks_access(&self, address: u16) -> bool {
+        self.dma.is_some() && address < 0xFF00
+    }
+
+    /// Copies the bytes due after `cycles` more cycles, one every 4 cycles, and ends the
+    /// transfer once all 160 have been copied.
+    fn advance_dma(&mut self, cycles: u32) {
+        let Some(dma) = self.dma.as_mut() else {
+            return;
+        };
+        let copied = (dma.elapsed / 4).min(DMA_LENGTH as u32) as u16;
+        dma.elapsed += cycles;
+        let due = (dma.elapsed / 4).min(DMA_LENGTH as u32) as u16;
+        let source = dma.source;
+        if due == DMA_LENGTH {
+            self.dma = None;
+        }
+        for offset in copied..due {
+            self.cells[0xFE00 + offset as usize - RAM_START] =
+                self.peek(Addr(source.wrapping_add(offset)));
         }
-        overflow
     }
 
+    /// Takes the VRAM tiles written with new data since the last call, for
+    /// `TileCache::mark_dirty`. A fresh `Ram` reports every tile.
+    pub fn take_dirty_tiles(&mut self) -> DirtyTiles {
+        core::mem::take(&mut self.dirty_tiles)
+    }
+
+    /// Patches ROM reads (0x0000-0x7FFF) with `code` from now on.
+    pub fn add_genie_code(&mut self, code: GenieCode) {
+        self.genie_codes.push(code);
+    }
+
+    pub fn timer(&self) -> &Timer {
+        &self.timer
+    }
+
+    /// Retrieves the little-endian word at the specified address: the low byte is read from
+    /// `address` and the high byte from `address + 1` (wrapping at 0xFFFF).
     pub fn read_word(&self, address: Addr) -> u16 {
-        word(
-            self.cells[address.0.wrapping_add(1) as usize],
-            self.cells[address.0 as usize],
-        )
+        word(self.read_byte(address.next()), self.read_byte(address))
     }
 
     pub fn read_ie(&self) -> u8 {
-        self.cells[0xFFFF]
+        self.cells[io::IE as usize - RAM_START]
     }
 
     pub fn read_if(&self) -> u8 {
-        self.cells[0xFF0F]
+        self.cells[io::IF as usize - RAM_START]
     }
 
     pub fn raise_if(&mut self, mask: u8) {
-        self.cells[0xFF0F] |= mask;
+        self.cells[io::IF as usize - RAM_START] |= mask;
     }
 
     pub fn clear_if(&mut self, mask: u8) {
-        self.cells[0xFF0F] &= !mask;
+        self.cells[io::IF as usize - RAM_START] &= !mask;
     }
 
+    /// The raw bytes at 0x8000-0xFFFF (VRAM, cartridge RAM when no cartridge is loaded, WRAM,
+    /// OAM, I/O and HRAM), indexed by `address - RAM_START`.
     pub fn as_slice(&self) -> &[u8] {
         &self.cells
     }
 
+    /// CGB background palette memory.
+    #[cfg(feature = "cgb")]
+    pub fn bg_palettes(&self) -> &PaletteRam {
+        &self.bg_palettes
+    }
+
+    /// CGB object palette memory.
+    #[cfg(feature = "cgb")]
+    pub fn obj_palettes(&self) -> &PaletteRam {
+        &self.obj_palettes
+    }
+
+    /// CGB VRAM bank 1 (0x8000-0x9FFF) whatever VBK selects; bank 0 is in `as_slice`.
+    #[cfg(feature = "cgb")]
+    pub fn vram_bank1(&self) -> &[u8] {
+        &self.vram_bank1
+    }
+
+    /// Whether the loaded cartridge's header declares CGB support, so it is drawn in CGB colour.
+    #[cfg(feature = "cgb")]
+    pub fn cgb_mode(&self) -> bool {
+        self.cartridge_header()
+            .is_some_and(|header| header.cgb_mode != CgbMode::None)
+    }
+
     #[allow(dead_code)]
     pub fn cartridge_header(&self) -> Option<&CartridgeHeader> {
         self.cartridge
@@ -364,11 +784,11 @@ impl Ram {
 
     /// Sets LY directly (used by PPU timing logic).
     pub fn set_ly_raw(&mut self, ly: u8) {
-        self.cells[0xFF44] = ly;
+        self.cells[io::LY as usize - RAM_START] = ly;
     }
 
     /// Sets STAT directly (used by PPU timing logic).
     pub fn set_stat_raw(&mut self, stat: u8) {
-        self.cells[0xFF41] = 0x80 | (stat & 0x7F);
+        self.cells[io::STAT as usize - RAM_START] = 0x80 | (stat & 0x7F);
     }
 }
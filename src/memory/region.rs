@@ -0,0 +1,56 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+/// The areas of the DMG address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemRegion {
+    /// 0x0000-0x3FFF, fixed cartridge bank
+    Rom0,
+    /// 0x4000-0x7FFF, switchable cartridge bank
+    RomX,
+    /// 0x8000-0x9FFF
+    Vram,
+    /// 0xA000-0xBFFF, cartridge RAM
+    ExtRam,
+    /// 0xC000-0xDFFF
+    Wram,
+    /// 0xE000-0xFDFF, mirror of 0xC000-0xDDFF
+    EchoRam,
+    /// 0xFE00-0xFE9F
+    Oam,
+    /// 0xFEA0-0xFEFF
+    Unusable,
+    /// 0xFF00-0xFF7F
+    Io,
+    /// 0xFF80-0xFFFE
+    Hram,
+    /// 0xFFFF
+    Ie,
+}
+
+impl MemRegion {
+    /// False for regions code is never meant to run from; a PC there usually means a bad jump.
+    pub fn is_executable(self) -> bool {
+        !matches!(
+            self,
+            MemRegion::Vram | MemRegion::Oam | MemRegion::Unusable | MemRegion::Io | MemRegion::Ie
+        )
+    }
+}
+
+/// Classifies `addr` into the region of the address space it belongs to.
+pub fn region(addr: u16) -> MemRegion {
+    match addr {
+        0x0000..=0x3FFF => MemRegion::Rom0,
+        0x4000..=0x7FFF => MemRegion::RomX,
+        0x8000..=0x9FFF => MemRegion::Vram,
+        0xA000..=0xBFFF => MemRegion::ExtRam,
+        0xC000..=0xDFFF => MemRegion::Wram,
+        0xE000..=0xFDFF => MemRegion::EchoRam,
+        0xFE00..=0xFE9F => MemRegion::Oam,
+        0xFEA0..=0xFEFF => MemRegion::Unusable,
+        0xFF00..=0xFF7F => MemRegion::Io,
+        0xFF80..=0xFFFE => MemRegion::Hram,
+        0xFFFF => MemRegion::Ie,
+    }
+}
@@ -0,0 +1,48 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use core::ops::BitOrAssign;
+
+/// Tiles in the VRAM tile data area, 0x8000-0x97FF, at 16 bytes each.
+const TILE_COUNT: usize = 384;
+const TILE_DATA_START: u16 = 0x8000;
+const TILE_DATA_END: u16 = 0x97FF;
+
+/// One flag per VRAM tile, set when a write changes that tile's data. `Ram` collects them for
+/// the renderer's tile cache, which decodes a tile again only once it has been marked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DirtyTiles([u64; TILE_COUNT / 64]);
+
+impl DirtyTiles {
+    /// Every tile marked, e.g. for a cache that has not decoded anything yet.
+    pub const ALL: Self = Self([u64::MAX; TILE_COUNT / 64]);
+
+    /// Marks the tile holding `address`; addresses outside 0x8000-0x97FF are ignored.
+    pub fn mark(&mut self, address: u16) {
+        if (TILE_DATA_START..=TILE_DATA_END).contains(&address) {
+            let tile = (address - TILE_DATA_START) as usize / 16;
+            self.0[tile / 64] |= 1 << (tile % 64);
+        }
+    }
+
+    /// Whether tile `tile` (0-383, counted from 0x8000) is marked.
+    pub fn contains(&self, tile: usize) -> bool {
+        self.0[tile / 64] & (1 << (tile % 64)) != 0
+    }
+
+    pub fn clear(&mut self, tile: usize) {
+        self.0[tile / 64] &= !(1 << (tile % 64));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&bits| bits == 0)
+    }
+}
+
+impl BitOrAssign for DirtyTiles {
+    fn bitor_assign(&mut self, other: Self) {
+        for (bits, other) in self.0.iter_mut().zip(other.0) {
+            *bits |= other;
+        }
+    }
+}
@@ -0,0 +1,108 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use super::io::{DIV, TAC, TIMA, TMA};
+
+/// DIV/TIMA/TMA/TAC (0xFF04-0xFF07), mapped into the address space by `Ram`.
+#[derive(Debug)]
+pub struct Timer {
+    /// Internal 16-bit counter backing DIV (0xFF04); DIV register = high byte
+    div_counter: u16,
+    /// TIMA overflowed during a register write; reported by the next `tick`
+    overflow_pending: bool,
+    tima: u8,
+    tma: u8,
+    /// Only bits 0-2 (clock select and enable); the rest read back as 1
+    tac: u8,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timer {
+    /// Post-boot DMG0 timer state: DIV mid-count, timer disabled.
+    pub fn new() -> Self {
+        Self {
+            div_counter: 0x183A,
+            overflow_pending: false,
+            tima: 0,
+            tma: 0,
+            tac: 0x00,
+        }
+    }
+
+    /// True for the addresses this timer answers to.
+    pub fn contains(address: u16) -> bool {
+        (DIV..=TAC).contains(&address)
+    }
+
+    pub fn read(&self, address: u16) -> u8 {
+        match address {
+            DIV => (self.div_counter >> 8) as u8,
+            TIMA => self.tima,
+            TMA => self.tma,
+            TAC => self.tac | 0xF8,
+            _ => 0xFF,
+        }
+    }
+
+    /// Register writes that drop the timer input from 1 to 0 tick TIMA, like the hardware's
+    /// falling-edge detector: resetting DIV while the selected bit is set, or a TAC change.
+    pub fn write(&mut self, address: u16, value: u8) {
+        let input = self.input();
+        match address {
+            // Any write to DIV resets the whole internal counter.
+            DIV => self.div_counter = 0,
+            TIMA => self.tima = value,
+            TMA => self.tma = value,
+            TAC => self.tac = value & 0x07,
+            _ => {}
+        }
+        if input && !self.input() {
+            self.overflow_pending |= self.increment_tima();
+        }
+    }
+
+    /// Advances timer state by `cycles` CPU cycles. Returns true if TIMA overflowed.
+    pub fn tick(&mut self, cycles: u32) -> bool {
+        let mut overflow = core::mem::take(&mut self.overflow_pending);
+        // Step one M-cycle at a time so no falling edge of the selected bit is skipped.
+        let mut remaining = cycles;
+        while remaining > 0 {
+            let step = remaining.min(4);
+            remaining -= step;
+            let input = self.input();
+            self.div_counter = self.div_counter.wrapping_add(step as u16);
+            if input && !self.input() {
+                overflow |= self.increment_tima();
+            }
+        }
+        overflow
+    }
+
+    /// The timer input: the internal counter bit selected by TAC, gated by the enable bit.
+    /// TIMA increments on each falling edge of this signal.
+    fn input(&self) -> bool {
+        let bit = match self.tac & 0x03 {
+            0 => 9, // 1024 cycles
+            1 => 3, // 16 cycles
+            2 => 5, // 64 cycles
+            _ => 7, // 256 cycles
+        };
+        self.tac & 0x04 != 0 && self.div_counter & (1 << bit) != 0
+    }
+
+    /// Returns true if TIMA overflowed and was reloaded from TMA.
+    fn increment_tima(&mut self) -> bool {
+        if self.tima == 0xFF {
+            self.tima = self.tma;
+            true
+        } else {
+            self.tima += 1;
+            false
+        }
+    }
+}
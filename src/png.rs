@@ -0,0 +1,139 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use alloc::vec::Vec;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+/// Largest payload of a stored (uncompressed) deflate block.
+const STORED_BLOCK_MAX: usize = 0xFFFF;
+
+/// Encodes an 8-bit RGBA image as a PNG file. Pixel data is stored uncompressed, which keeps the
+/// encoder dependency-free; a 160x144 screenshot is about 92 KiB.
+pub fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        rgba.len(),
+        width as usize * height as usize * 4,
+        "RGBA buffer does not match {width}x{height}"
+    );
+
+    // Each scanline is prefixed with filter type 0 (None).
+    let row_len = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in rgba.chunks_exact(row_len.max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]); // depth 8, RGBA, deflate, no filter, no interlace
+
+    let mut png = Vec::new();
+    png.extend_from_slice(&SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream made of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(STORED_BLOCK_MAX).max(1);
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    out.extend_from_slice(&[0x78, 0x01]);
+    for block in 0..blocks {
+        let chunk = &data[block * STORED_BLOCK_MAX..data.len().min((block + 1) * STORED_BLOCK_MAX)];
+        let len = chunk.len() as u16;
+        out.push((block + 1 == blocks) as u8); // BFINAL, BTYPE 00 (stored)
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % MOD;
+        (a, (b + a) % MOD)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn encodes_signature_header_and_trailer() {
+        let png = encode_rgba(2, 1, &[0xFF, 0, 0, 0xFF, 0, 0xFF, 0, 0xFF]);
+
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 2);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 1);
+        assert_eq!(&png[24..29], &[8, 6, 0, 0, 0]);
+        assert_eq!(
+            &png[png.len() - 12..],
+            &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn large_images_split_into_multiple_stored_blocks() {
+        let data = vec![0xAB; STORED_BLOCK_MAX + 10];
+        let stream = zlib_stored(&data);
+
+        assert_eq!(stream[2], 0x00, "first block is not final");
+        let second = 2 + 5 + STORED_BLOCK_MAX;
+        assert_eq!(stream[second], 0x01, "second block is final");
+        assert_eq!(&stream[second + 1..second + 3], &10u16.to_le_bytes());
+        assert_eq!(stream.len(), 2 + 2 * 5 + data.len() + 4);
+    }
+}
@@ -0,0 +1,502 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::memory::{io, Addr, Ram};
+use crate::renderer::{self, LayerFlags, Palette, ScanlineRegs, TileCache};
+
+const HEIGHT: usize = renderer::HEIGHT as usize;
+const PIXELS: usize = renderer::WIDTH as usize * HEIGHT;
+/// CPU cycles per frame: 154 lines of 456 cycles.
+pub const CYCLES_PER_FRAME: usize = 70224;
+/// CPU cycles per scanline, visible or VBlank.
+pub const LINE_CYCLES: usize = 456;
+/// Length of mode 2 (OAM scan) at the start of each visible line.
+pub const OAM_SCAN_CYCLES: usize = 80;
+/// Length of mode 3 (pixel transfer). Fixed at its minimum; on hardware sprites, SCX and the
+/// window stretch it to as much as 289 cycles at HBlank's expense. HBlank (mode 0) fills the
+/// remaining 204 cycles of the line.
+pub const DRAWING_CYCLES: usize = 172;
+/// First VBlank line (mode 1 lasts through line 153).
+const VBLANK_START_LINE: u8 = 144;
+
+/// LCD timing state: LY/STAT progression, VBlank/STAT interrupt requests and
+/// per-scanline register latches, plus the RGBA frame buffer, rendered one line at a time
+/// as each line enters pixel transfer (mode 3) and published at VBlank.
+#[derive(Debug)]
+pub struct Ppu {
+    line_cycles: usize,
+    scanline_latches: [ScanlineRegs; HEIGHT],
+    scanline_latched: [bool; HEIGHT],
+    frame: Vec<u8>,
+    completed_frame: Vec<u8>,
+    bg_opaque: Vec<bool>,
+    tile_cache: TileCache,
+    palette: Palette,
+    layers: LayerFlags,
+    lcd_on: bool,
+    /// Set when the LCD is switched on: the first frame after that is not shown.
+    blank_next_frame: bool,
+    /// Set when the LCD is switched on: line 0 reports mode 0 instead of its OAM scan.
+    first_line_after_enable: bool,
+}
+
+impl Default for Ppu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ppu {
+    pub fn new() -> Self {
+        Self {
+            line_cycles: 0,
+            scanline_latches: [ScanlineRegs::default(); HEIGHT],
+            scanline_latched: [false; HEIGHT],
+            frame: Palette::default().color(0).repeat(PIXELS),
+            completed_frame: Palette::default().color(0).repeat(PIXELS),
+            bg_opaque: vec![false; PIXELS],
+            tile_cache: TileCache::new(),
+            palette: Palette::default(),
+            layers: LayerFlags::default(),
+            // Power-on state is post-boot with the LCD already running, not a fresh enable.
+            lcd_on: true,
+            blank_next_frame: false,
+            first_line_after_enable: false,
+        }
+    }
+
+    /// The latest completed RGBA frame (160x144x4), updated when LY reaches 144.
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.completed_frame
+    }
+
+    /// Sets the colours used for lines rendered from now on.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Sets the layers drawn for lines rendered from now on.
+    pub fn set_layers(&mut self, layers: LayerFlags) {
+        self.layers = layers;
+    }
+
+    /// Cycles elapsed on the current scanline.
+    pub fn line_cycles(&self) -> usize {
+        self.line_cycles
+    }
+
+    /// Advances LCD timing by `cycles` CPU cycles.
+    /// Returns true if a frame completed, i.e. LY entered VBlank (line 144) during this step.
+    pub fn step(&mut self, memory: &mut Ram, cycles: usize) -> bool {
+        let lcdc = memory.read_byte(Addr(io::LCDC));
+        if (lcdc & 0x80) == 0 {
+            if self.lcd_on {
+                // A disabled LCD shows blank (shade 0) until it is turned back on.
+                self.lcd_on = false;
+                let blank = self.palette.color(0);
+                for pixel in self.frame.chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&blank);
+                }
+                self.completed_frame.copy_from_slice(&self.frame);
+            }
+            self.line_cycles = 0;
+            memory.set_ly_raw(0);
+            self.scanline_latched.fill(false);
+            Self::update_stat(memory, 0, false, false);
+            return false;
+        }
+
+        if !self.lcd_on {
+            // Switching the LCD on restarts the frame at the top of line 0.
+            self.lcd_on = true;
+            self.line_cycles = 0;
+            memory.set_ly_raw(0);
+            self.scanline_latched.fill(false);
+            self.blank_next_frame = true;
+            self.first_line_after_enable = true;
+        }
+
+        let mut frame_complete = false;
+        self.line_cycles += cycles;
+        while self.line_cycles >= LINE_CYCLES {
+            self.line_cycles -= LINE_CYCLES;
+            self.first_line_after_enable = false;
+            let ly = memory.read_byte(Addr(io::LY));
+            let new_ly = if ly >= 153 { 0 } else { ly + 1 };
+            memory.set_ly_raw(new_ly);
+            if new_ly == 0 {
+                self.scanline_latched.fill(false);
+            }
+            if new_ly == VBLANK_START_LINE {
+                memory.raise_if(0x01);
+                if self.blank_next_frame {
+                    self.blank_next_frame = false;
+                } else {
+                    self.completed_frame.copy_from_slice(&self.frame);
+                }
+                frame_complete = true;
+            }
+        }
+
+        let ly = memory.read_byte(Addr(io::LY));
+        let mode = match Self::mode_at(ly, self.line_cycles) {
+            2 if self.first_line_after_enable => 0,
+            mode => mode,
+        };
+        let lyc = memory.read_byte(Addr(io::LYC));
+        Self::update_stat(memory, mode, ly == lyc, true);
+        self.maybe_latch_scanline(memory, ly, mode);
+        frame_complete
+    }
+
+    /// The STAT mode `line_cycles` into line `ly`: 2 (OAM scan), 3 (drawing) and 0 (HBlank) on
+    /// visible lines, 1 (VBlank) on lines 144-153.
+    fn mode_at(ly: u8, line_cycles: usize) -> u8 {
+        if ly >= VBLANK_START_LINE {
+            1
+        } else if line_cycles < OAM_SCAN_CYCLES {
+            2
+        } else if line_cycles < OAM_SCAN_CYCLES + DRAWING_CYCLES {
+            3
+        } else {
+            0
+        }
+    }
+
+    fn update_stat(memory: &mut Ram, mode: u8, coincidence: bool, allow_interrupt: bool) {
+        let old_stat = memory.read_byte(Addr(io::STAT));
+        let old_mode = old_stat & 0x03;
+        let old_coincidence = (old_stat & 0x04) != 0;
+        let mut new_stat = (old_stat & 0x78) | (mode & 0x03);
+        if coincidence {
+            new_stat |= 0x04;
+        }
+        memory.set_stat_raw(new_stat);
+
+        if !allow_interrupt {
+            return;
+        }
+
+        let mode_changed = mode != old_mode;
+        let mode_irq = match mode {
+            0 => (new_stat & 0x08) != 0,
+            1 => (new_stat & 0x10) != 0,
+            2 => (new_stat & 0x20) != 0,
+            _ => false,
+        };
+        let lyc_irq = coincidence && !old_coincidence && (new_stat & 0x40) != 0;
+        if (mode_changed && mode_irq) || lyc_irq {
+            memory.raise_if(0x02);
+        }
+    }
+
+    fn maybe_latch_scanline(&mut self, memory: &mut Ram, ly: u8, mode: u8) {
+        if mode != 3 || ly as usize >= HEIGHT {
+            return;
+        }
+        let line = ly as usize;
+        if self.scanline_latched[line] {
+            return;
+        }
+
+        self.scanline_latches[line] = renderer::scanline_regs_from_ram(memory.as_slice());
+        self.scanline_latched[line] = true;
+        self.tile_cache.mark_dirty(memory.take_dirty_tiles());
+        #[cfg(feature = "cgb")]
+        if memory.cgb_mode() {
+            renderer::render_scanline_cgb(
+                memory.as_slice(),
+                memory.vram_bank1(),
+                memory.bg_palettes(),
+                memory.obj_palettes(),
+                &mut self.frame,
+                line,
+            );
+            return;
+        }
+        renderer::render_scanline(
+            memory.as_slice(),
+            &mut self.frame,
+            &mut self.bg_opaque,
+            line,
+            &self.palette,
+            &mut self.tile_cache,
+            self.layers,
+        );
+    }
+
+    /// Returns the per-line register latches for the current frame.
+    /// Lines not yet latched fall back to the live register values in `ram`.
+    pub fn scanline_latches(&self, ram: &[u8]) -> [ScanlineRegs; HEIGHT] {
+        let mut latches = self.scanline_latches;
+        if self.scanline_latched.iter().any(|latched| !latched) {
+            let fallback = renderer::scanline_regs_from_ram(ram);
+            for (line, latched) in self.scanline_latched.iter().enumerate() {
+                if !latched {
+                    latches[line] = fallback;
+                }
+            }
+        }
+        latches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maybe_latch_scanline_captures_registers_once_per_line() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF40), 0xB1);
+        ram.write_byte(Addr(0xFF42), 0x22);
+        ram.write_byte(Addr(0xFF43), 0x11);
+        ram.write_byte(Addr(0xFF47), 0xE4);
+        ram.write_byte(Addr(0xFF4A), 0x05);
+        ram.write_byte(Addr(0xFF4B), 0x10);
+
+        let mut ppu = Ppu::new();
+        ppu.maybe_latch_scanline(&mut ram, 12, 3);
+
+        assert!(ppu.scanline_latched[12]);
+        let first = ppu.scanline_latches[12];
+        assert_eq!(first.lcdc, 0xB1);
+        assert_eq!(first.scy, 0x22);
+        assert_eq!(first.scx, 0x11);
+        assert_eq!(first.bgp, 0xE4);
+        assert_eq!(first.wy, 0x05);
+        assert_eq!(first.wx, 0x10);
+
+        ram.write_byte(Addr(0xFF42), 0x99);
+        ram.write_byte(Addr(0xFF43), 0x88);
+        ppu.maybe_latch_scanline(&mut ram, 12, 3);
+        let second = ppu.scanline_latches[12];
+
+        assert_eq!(
+            first.scy, second.scy,
+            "line latch should be stable after first capture"
+        );
+        assert_eq!(
+            first.scx, second.scx,
+            "line latch should be stable after first capture"
+        );
+    }
+
+    #[test]
+    fn vram_writes_reach_the_tile_cache_before_the_next_line_renders() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(io::BGP), 0xE4);
+        ram.write_byte(Addr(0x8000), 0xFF); // tile 0, row 0: shade 1
+        let mut ppu = Ppu::new();
+        ppu.maybe_latch_scanline(&mut ram, 0, 3);
+        assert_eq!(ppu.frame[0..4], ppu.palette.color(1));
+
+        ram.write_byte(Addr(0x8001), 0xFF); // row 0 becomes shade 3
+        ppu.scanline_latched[0] = false;
+        ppu.maybe_latch_scanline(&mut ram, 0, 3);
+        assert_eq!(ppu.frame[0..4], ppu.palette.color(3));
+    }
+
+    #[cfg(feature = "cgb")]
+    #[test]
+    fn cgb_cartridges_render_lines_from_cgb_palette_memory() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0143] = 0x80; // CGB flag: works on DMG too
+        let mut ram = Ram::new();
+        ram.load_rom(rom);
+        // Background palette 0, colour 1 = pure red (BGR555 0x001F).
+        ram.write_byte(Addr(io::BCPS), 0x82);
+        ram.write_byte(Addr(io::BCPD), 0x1F);
+        ram.write_byte(Addr(io::BCPD), 0x00);
+        ram.write_byte(Addr(0x8000), 0x80); // tile 0, row 0: leftmost pixel colour 1
+        let mut ppu = Ppu::new();
+
+        ppu.maybe_latch_scanline(&mut ram, 0, 3);
+
+        assert_eq!(ppu.frame[0..4], [0xFF, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn step_clears_scanline_latches_on_frame_wrap() {
+        let mut ram = Ram::new();
+        let mut ppu = Ppu::new();
+
+        ppu.scanline_latched.fill(true);
+        ram.set_ly_raw(153);
+        ppu.step(&mut ram, LINE_CYCLES);
+
+        assert!(
+            ppu.scanline_latched.iter().all(|latched| !latched),
+            "all scanline latches should reset when LY wraps to 0"
+        );
+    }
+
+    #[test]
+    fn step_signals_frame_complete_once_per_70224_cycles() {
+        let mut ram = Ram::new();
+        let mut ppu = Ppu::new();
+
+        let mut frames = 0;
+        for _ in 0..70224 / 4 {
+            if ppu.step(&mut ram, 4) {
+                frames += 1;
+            }
+        }
+
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn stat_mode_follows_80_172_204_line_timing() {
+        let mut ram = Ram::new();
+        let mut ppu = Ppu::new();
+        let stat_mode = |ram: &Ram| ram.read_byte(Addr(io::STAT)) & 0x03;
+
+        // Step to each offset on line 0 and check the mode just before and at each boundary.
+        let mut elapsed = 0;
+        for (offset, mode) in [
+            (0, 2),
+            (76, 2),
+            (80, 3),
+            (248, 3),
+            (252, 0),
+            (452, 0),
+            (456, 2),
+        ] {
+            ppu.step(&mut ram, offset - elapsed);
+            elapsed = offset;
+            assert_eq!(stat_mode(&ram), mode, "{offset} cycles into the line");
+        }
+        assert_eq!(ram.read_byte(Addr(io::LY)), 1);
+    }
+
+    #[test]
+    fn stat_mode_is_vblank_for_lines_144_to_153() {
+        let mut ram = Ram::new();
+        let mut ppu = Ppu::new();
+
+        ppu.step(&mut ram, 144 * LINE_CYCLES);
+        assert_eq!(ram.read_byte(Addr(io::LY)), 144);
+        assert_eq!(ram.read_byte(Addr(io::STAT)) & 0x03, 1);
+
+        ppu.step(&mut ram, 9 * LINE_CYCLES + OAM_SCAN_CYCLES + DRAWING_CYCLES);
+        assert_eq!(ram.read_byte(Addr(io::LY)), 153);
+        assert_eq!(
+            ram.read_byte(Addr(io::STAT)) & 0x03,
+            1,
+            "no HBlank during VBlank"
+        );
+
+        ppu.step(&mut ram, LINE_CYCLES - OAM_SCAN_CYCLES - DRAWING_CYCLES);
+        assert_eq!(ram.read_byte(Addr(io::LY)), 0);
+        assert_eq!(ram.read_byte(Addr(io::STAT)) & 0x03, 2);
+    }
+
+    #[test]
+    fn entering_hblank_requests_stat_interrupt_when_enabled() {
+        let mut ram = Ram::new();
+        let mut ppu = Ppu::new();
+        ram.write_byte(Addr(io::STAT), 0x08); // mode 0 interrupt source
+        ram.write_byte(Addr(io::IF), 0x00);
+
+        ppu.step(&mut ram, OAM_SCAN_CYCLES + DRAWING_CYCLES - 4);
+        assert_eq!(ram.read_byte(Addr(io::IF)) & 0x02, 0);
+        ppu.step(&mut ram, 4);
+        assert_eq!(ram.read_byte(Addr(io::IF)) & 0x02, 0x02);
+    }
+
+    fn pixel(ppu: &Ppu, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * renderer::WIDTH as usize + x) * 4;
+        ppu.frame_buffer()[offset..offset + 4].try_into().unwrap()
+    }
+
+    /// LCD on, BG from tile data 0x8000 and map 0x9800, where map column 0 holds a solid
+    /// shade-3 tile on every row and everything else is shade 0.
+    fn split_test_ram() -> Ram {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF40), 0x91);
+        ram.write_byte(Addr(0xFF47), 0xE4);
+        for offset in 0..16 {
+            ram.write_byte(Addr(0x8010 + offset), 0xFF); // tile 1: shade 3
+        }
+        for row in 0..32 {
+            ram.write_byte(Addr(0x9800 + row * 32), 0x01);
+        }
+        ram
+    }
+
+    #[test]
+    fn scx_change_mid_frame_only_shifts_later_lines() {
+        let mut ram = split_test_ram();
+        let mut ppu = Ppu::new();
+        let dark = Palette::DMG_GREEN.color(3);
+        let light = Palette::DMG_GREEN.color(0);
+
+        // Run to the OAM scan of line 72, before that line is drawn.
+        while ram.read_byte(Addr(0xFF44)) != 72 {
+            ppu.step(&mut ram, 4);
+        }
+        ram.write_byte(Addr(0xFF43), 8); // SCX: tile column 0 scrolls off screen
+        while !ppu.step(&mut ram, 4) {}
+
+        assert_eq!(pixel(&ppu, 0, 0), dark);
+        assert_eq!(pixel(&ppu, 0, 71), dark);
+        assert_eq!(pixel(&ppu, 0, 72), light);
+        assert_eq!(pixel(&ppu, 0, 143), light);
+    }
+
+    #[test]
+    fn lcd_off_blanks_frame_buffer() {
+        let mut ram = split_test_ram();
+        let mut ppu = Ppu::new();
+        while !ppu.step(&mut ram, 4) {}
+        assert_eq!(pixel(&ppu, 0, 0), Palette::DMG_GREEN.color(3));
+
+        ram.write_byte(Addr(0xFF40), 0x11); // LCD off
+        ppu.step(&mut ram, 4);
+
+        assert_eq!(ppu.frame_buffer().len(), PIXELS * 4);
+        assert!(ppu
+            .frame_buffer()
+            .chunks_exact(4)
+            .all(|pixel| pixel == Palette::DMG_GREEN.color(0)));
+    }
+
+    #[test]
+    fn lcd_enable_restarts_at_line_0_and_skips_the_first_frame() {
+        let mut ram = split_test_ram();
+        let mut ppu = Ppu::new();
+        while !ppu.step(&mut ram, 4) {}
+        ppu.step(&mut ram, 3 * LINE_CYCLES + 100);
+        assert_eq!(ram.read_byte(Addr(io::LY)), 147);
+
+        ram.write_byte(Addr(0xFF40), 0x11); // LCD off
+        ppu.step(&mut ram, 4);
+        ram.write_byte(Addr(0xFF40), 0x91); // and on again
+        ppu.step(&mut ram, 4);
+
+        assert_eq!(ram.read_byte(Addr(io::LY)), 0);
+        assert_eq!(ppu.line_cycles(), 4, "cycle counter restarted");
+        assert_eq!(
+            ram.read_byte(Addr(io::STAT)) & 0x03,
+            0,
+            "no OAM scan on the first line"
+        );
+
+        let blank = Palette::DMG_GREEN.color(0);
+        while !ppu.step(&mut ram, 4) {}
+        assert!(
+            ppu.frame_buffer()
+                .chunks_exact(4)
+                .all(|pixel| pixel == blank),
+            "the first frame after enabling is blank"
+        );
+        assert_eq!(ram.read_byte(Addr(io::STAT)) & 0x03, 1);
+
+        while !ppu.step(&mut ram, 4) {}
+        assert_eq!(pixel(&ppu, 0, 0), Palette::DMG_GREEN.color(3));
+    }
+}
@@ -1,16 +1,299 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "cgb")]
+use crate::memory::PaletteRam;
+use crate::memory::{io, DirtyTiles, RAM_START};
+
+// Functions here that take `ram` expect `Ram::as_slice`: the bytes at 0x8000-0xFFFF, indexed
+// by `address - RAM_START`.
+
 pub const WIDTH: u32 = 160;
 pub const HEIGHT: u32 = 144;
 
-// Game Boy default palette: lightest to darkest
-const GB_COLORS: [[u8; 4]; 4] = [
-    [0x9B, 0xBC, 0x0F, 0xFF],
-    [0x8B, 0xAC, 0x0F, 0xFF],
-    [0x30, 0x62, 0x30, 0xFF],
-    [0x0F, 0x38, 0x0F, 0xFF],
-];
+/// Maps the four DMG shade indices (0 = lightest, 3 = darkest) to RGBA colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub name: &'static str,
+    pub colors: [[u8; 4]; 4],
+}
+
+impl Palette {
+    /// The original DMG's green LCD.
+    pub const DMG_GREEN: Palette = Palette {
+        name: "dmg-green",
+        colors: [
+            [0x9B, 0xBC, 0x0F, 0xFF],
+            [0x8B, 0xAC, 0x0F, 0xFF],
+            [0x30, 0x62, 0x30, 0xFF],
+            [0x0F, 0x38, 0x0F, 0xFF],
+        ],
+    };
+
+    pub const GRAYSCALE: Palette = Palette {
+        name: "grayscale",
+        colors: [
+            [0xFF, 0xFF, 0xFF, 0xFF],
+            [0xAA, 0xAA, 0xAA, 0xFF],
+            [0x55, 0x55, 0x55, 0xFF],
+            [0x00, 0x00, 0x00, 0xFF],
+        ],
+    };
+
+    /// The Game Boy Pocket's olive-gray LCD.
+    pub const POCKET: Palette = Palette {
+        name: "pocket",
+        colors: [
+            [0xC4, 0xCF, 0xA1, 0xFF],
+            [0x8B, 0x95, 0x6D, 0xFF],
+            [0x4D, 0x53, 0x3C, 0xFF],
+            [0x1F, 0x1F, 0x1F, 0xFF],
+        ],
+    };
+
+    /// Built-in presets in the order the frontend cycles through them.
+    pub const PRESETS: [Palette; 3] = [Palette::DMG_GREEN, Palette::GRAYSCALE, Palette::POCKET];
+
+    /// Returns the RGBA colour for a shade index (only the low two bits are used).
+    pub fn color(&self, shade: u8) -> [u8; 4] {
+        self.colors[(shade & 0x03) as usize]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::DMG_GREEN
+    }
+}
+
+/// Which layers `render_scanline` draws, for isolating rendering bugs. LCDC still applies on
+/// top: a layer is only drawn when both its flag and its LCDC enable bit are set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerFlags {
+    pub background: bool,
+    pub window: bool,
+    pub sprites: bool,
+}
+
+impl Default for LayerFlags {
+    fn default() -> Self {
+        Self {
+            background: true,
+            window: true,
+            sprites: true,
+        }
+    }
+}
+
+/// Mixes each new frame with the one before it to mimic the DMG LCD's slow pixel response, so
+/// sprites flickered on alternate frames show as translucent rather than blinking.
+#[derive(Clone, Debug)]
+pub struct FrameBlender {
+    /// Share of the previous frame in the output, in 1/256ths; 0 disables blending.
+    previous_weight: u16,
+    current: Vec<u8>,
+    previous: Vec<u8>,
+}
+
+impl FrameBlender {
+    /// `weight` is the previous frame's share of each output pixel, clamped to 0.0..=1.0.
+    pub fn new(weight: f32) -> Self {
+        Self {
+            previous_weight: (weight.clamp(0.0, 1.0) * 256.0) as u16,
+            current: Vec::new(),
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.previous_weight as f32 / 256.0
+    }
+
+    /// Writes `frame` blended with the previously seen frame into `out`. Presenting the same
+    /// frame again (a redraw without emulation) reuses the same pair, so the result is stable.
+    pub fn blend(&mut self, frame: &[u8], out: &mut [u8]) {
+        if self.current != frame {
+            core::mem::swap(&mut self.previous, &mut self.current);
+            self.current.clear();
+            self.current.extend_from_slice(frame);
+        }
+        if self.previous_weight == 0 || self.previous.len() != frame.len() {
+            out.copy_from_slice(frame);
+            return;
+        }
+        let weight = self.previous_weight as u32;
+        for ((out, &current), &previous) in out.iter_mut().zip(frame).zip(&self.previous) {
+            let mixed = current as u32 * (256 - weight) + previous as u32 * weight;
+            *out = ((mixed + 128) >> 8) as u8;
+        }
+    }
+}
+
+impl Default for FrameBlender {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+/// Nearest-neighbour upscales a `WIDTH`x`HEIGHT` RGBA frame by an integer `factor`, keeping
+/// pixel edges sharp without relying on GPU filtering.
+pub fn scale_framebuffer(src: &[u8], factor: u32) -> Vec<u8> {
+    scale_rgba(src, WIDTH, HEIGHT, factor)
+}
+
+/// Nearest-neighbour upscales a `width`x`height` RGBA image by an integer `factor`.
+pub fn scale_rgba(src: &[u8], width: u32, height: u32, factor: u32) -> Vec<u8> {
+    let (width, height, factor) = (width as usize, height as usize, factor as usize);
+    assert_eq!(
+        src.len(),
+        width * height * 4,
+        "RGBA buffer does not match {width}x{height}"
+    );
+    let mut out = Vec::with_capacity(src.len() * factor * factor);
+    for row in src.chunks_exact((width * 4).max(1)) {
+        let start = out.len();
+        for pixel in row.chunks_exact(4) {
+            for _ in 0..factor {
+                out.extend_from_slice(pixel);
+            }
+        }
+        let scaled_row = start..out.len();
+        for _ in 1..factor {
+            out.extend_from_within(scaled_row.clone());
+        }
+    }
+    out
+}
+
+/// A stable 64-bit FNV-1a hash of an RGBA frame, for comparing rendered output against a
+/// known-good value in regression tests.
+pub fn frame_hash(frame: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+    frame.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Characters for `render_ascii`, lightest to darkest, one per DMG shade.
+pub const ASCII_RAMP: [char; 4] = [' ', '.', '+', '#'];
+/// Screen pixels averaged into one `render_ascii` character. Twice as tall as wide, since
+/// terminal cells are, giving a 40x18 picture.
+const ASCII_BLOCK_WIDTH: usize = 4;
+const ASCII_BLOCK_HEIGHT: usize = 8;
+
+/// Renders the frame described by `ram` (0x8000-0xFFFF) as ASCII art: each
+/// block of pixels becomes the `ASCII_RAMP` character of its average shade, one line per block
+/// row. Meant for terminals and test output where there is no display.
+pub fn render_ascii(ram: &[u8]) -> String {
+    let mut screen = vec![0u8; WIDTH as usize * HEIGHT as usize * 4];
+    let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
+    render_frame_with_bg_opaque(
+        ram,
+        &mut screen,
+        &mut bg_opaque,
+        &Palette::GRAYSCALE,
+        &mut TileCache::new(),
+    );
+
+    let (columns, rows) = (
+        WIDTH as usize / ASCII_BLOCK_WIDTH,
+        HEIGHT as usize / ASCII_BLOCK_HEIGHT,
+    );
+    let mut out = String::with_capacity((columns + 1) * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut total = 0usize;
+            for y in row * ASCII_BLOCK_HEIGHT..(row + 1) * ASCII_BLOCK_HEIGHT {
+                for x in column * ASCII_BLOCK_WIDTH..(column + 1) * ASCII_BLOCK_WIDTH {
+                    // Grayscale: the red channel is the brightness.
+                    total += screen[(y * WIDTH as usize + x) * 4] as usize;
+                }
+            }
+            let brightness = total / (ASCII_BLOCK_WIDTH * ASCII_BLOCK_HEIGHT);
+            let shade = (255 - brightness) * ASCII_RAMP.len() / 256;
+            out.push(ASCII_RAMP[shade]);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+const TILE_COUNT: usize = 384; // 0x8000-0x97FF, 16 bytes per tile
+
+/// Decoded 2bpp tile data (palette indices per pixel) for the 384 VRAM tiles. Each tile is
+/// decoded on its first lookup and again only after `mark_dirty` reports a write to it.
+#[derive(Clone, Debug)]
+pub struct TileCache {
+    tiles: Vec<[[u8; 8]; 8]>,
+    dirty: DirtyTiles,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for TileCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TileCache {
+    pub fn new() -> Self {
+        Self {
+            tiles: vec![[[0; 8]; 8]; TILE_COUNT],
+            dirty: DirtyTiles::ALL,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Marks `tiles` for decoding again on their next lookup, e.g. with the tiles
+    /// `Ram::take_dirty_tiles` reports written.
+    pub fn mark_dirty(&mut self, tiles: DirtyTiles) {
+        self.dirty |= tiles;
+    }
+
+    /// Returns the 8x8 palette indices of the tile at `tile_address` (0x8000-0x97F0),
+    /// decoding it only if it was marked dirty since the last lookup. Other addresses are
+    /// masked into VRAM and wrapped onto a tile boundary in the tile data area, so bad input
+    /// reads a garbage tile instead of panicking.
+    pub fn tile(&mut self, ram: &[u8], tile_address: usize) -> &[[u8; 8]; 8] {
+        let offset = ((tile_address & 0x1FFF) % (TILE_COUNT * 16)) & !0x0F;
+        let index = offset / 16;
+        let entry = &mut self.tiles[index];
+        if self.dirty.contains(index) {
+            self.misses += 1;
+            self.dirty.clear(index);
+            let raw = &ram[0x8000 - RAM_START + offset..0x8000 - RAM_START + offset + 16];
+            for (row, indices) in entry.iter_mut().enumerate() {
+                let lo = raw[row * 2];
+                let hi = raw[row * 2 + 1];
+                for (col, index) in indices.iter_mut().enumerate() {
+                    let bit = 7 - col;
+                    *index = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                }
+            }
+        } else {
+            self.hits += 1;
+        }
+        entry
+    }
+
+    /// Lookups served without decoding.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that had to decode the tile.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct ScanlineRegs {
@@ -22,33 +305,47 @@ pub struct ScanlineRegs {
     pub wx: u8,
 }
 
-fn scanline_regs_from_ram(ram: &[u8]) -> ScanlineRegs {
+/// Captures the live BG/window registers from `ram` (0x8000-0xFFFF).
+pub fn scanline_regs_from_ram(ram: &[u8]) -> ScanlineRegs {
     ScanlineRegs {
-        lcdc: ram[0xFF40],
-        scy: ram[0xFF42],
-        scx: ram[0xFF43],
-        bgp: ram[0xFF47],
-        wy: ram[0xFF4A],
-        wx: ram[0xFF4B],
+        lcdc: ram[io::LCDC as usize - RAM_START],
+        scy: ram[io::SCY as usize - RAM_START],
+        scx: ram[io::SCX as usize - RAM_START],
+        bgp: ram[io::BGP as usize - RAM_START],
+        wy: ram[io::WY as usize - RAM_START],
+        wx: ram[io::WX as usize - RAM_START],
     }
 }
 
 /// Renders the background layer into `screen` (RGBA, 160×144).
-/// `ram` must be a 65536-byte slice (the full Game Boy address space).
+/// `ram` must be a 32768-byte slice holding 0x8000-0xFFFF.
 /// Reads SCX/SCY scroll registers and respects LCDC tile map / data area bits.
 #[allow(dead_code)]
 pub fn render_frame(ram: &[u8], screen: &mut [u8]) {
     let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
-    render_frame_with_bg_opaque(ram, screen, &mut bg_opaque);
+    let mut tile_cache = TileCache::new();
+    render_frame_with_bg_opaque(
+        ram,
+        screen,
+        &mut bg_opaque,
+        &Palette::default(),
+        &mut tile_cache,
+    );
 }
 
-/// Renders a frame while reusing a caller-provided opacity buffer.
+/// Renders a frame while reusing a caller-provided opacity buffer and tile cache.
 /// `bg_opaque` must have one entry per screen pixel.
-pub fn render_frame_with_bg_opaque(ram: &[u8], screen: &mut [u8], bg_opaque: &mut [bool]) {
+pub fn render_frame_with_bg_opaque(
+    ram: &[u8],
+    screen: &mut [u8],
+    bg_opaque: &mut [bool],
+    palette: &Palette,
+    tile_cache: &mut TileCache,
+) {
     let mut latches = [ScanlineRegs::default(); HEIGHT as usize];
     let regs = scanline_regs_from_ram(ram);
     latches.fill(regs);
-    render_frame_with_scanline_latches(ram, screen, bg_opaque, &latches);
+    render_frame_with_scanline_latches(ram, screen, bg_opaque, &latches, palette, tile_cache);
 }
 
 /// Renders a frame using per-scanline latched LCD registers.
@@ -57,12 +354,14 @@ pub fn render_frame_with_scanline_latches(
     screen: &mut [u8],
     bg_opaque: &mut [bool],
     scanline_regs: &[ScanlineRegs],
+    palette: &Palette,
+    tile_cache: &mut TileCache,
 ) {
     debug_assert_eq!(bg_opaque.len(), WIDTH as usize * HEIGHT as usize);
     debug_assert_eq!(scanline_regs.len(), HEIGHT as usize);
 
     for pixel in screen.chunks_exact_mut(4) {
-        pixel.copy_from_slice(&GB_COLORS[0]);
+        pixel.copy_from_slice(&palette.color(0));
     }
     bg_opaque.fill(false);
 
@@ -75,8 +374,7 @@ pub fn render_frame_with_scanline_latches(
         false
     };
 
-    for screen_y in 0..HEIGHT as usize {
-        let regs = scanline_regs[screen_y];
+    for (screen_y, &regs) in scanline_regs.iter().enumerate().take(HEIGHT as usize) {
         // LCD off: line remains blank.
         if (regs.lcdc & 0x80) == 0 {
             continue;
@@ -84,19 +382,63 @@ pub fn render_frame_with_scanline_latches(
         // On DMG, LCDC bit 0 gates both BG and Window.
         if (regs.lcdc & 0x01) != 0 {
             if check_priority {
-                render_bg_line::<true>(ram, screen, bg_opaque, screen_y, regs);
-                render_window_line::<true>(ram, screen, bg_opaque, screen_y, regs);
+                render_bg_line::<true>(ram, screen, bg_opaque, screen_y, regs, palette, tile_cache);
+                render_window_line::<true>(
+                    ram, screen, bg_opaque, screen_y, regs, palette, tile_cache,
+                );
             } else {
-                render_bg_line::<false>(ram, screen, bg_opaque, screen_y, regs);
-                render_window_line::<false>(ram, screen, bg_opaque, screen_y, regs);
+                render_bg_line::<false>(
+                    ram, screen, bg_opaque, screen_y, regs, palette, tile_cache,
+                );
+                render_window_line::<false>(
+                    ram, screen, bg_opaque, screen_y, regs, palette, tile_cache,
+                );
             }
         }
     }
 
     if check_priority {
-        render_obj::<true>(ram, screen, bg_opaque);
+        render_obj::<true>(ram, screen, bg_opaque, palette, 0..HEIGHT as usize);
     } else {
-        render_obj::<false>(ram, screen, &[]);
+        render_obj::<false>(ram, screen, &[], palette, 0..HEIGHT as usize);
+    }
+}
+
+/// Renders screen line `screen_y` from the live registers in `ram`: background, window and the
+/// sprites on that line. Used by the PPU to draw each line as it is scanned out, so register
+/// writes between lines (e.g. SCX splits) take effect from the next line on.
+/// Layers cleared in `layers` are skipped.
+pub fn render_scanline(
+    ram: &[u8],
+    screen: &mut [u8],
+    bg_opaque: &mut [bool],
+    screen_y: usize,
+    palette: &Palette,
+    tile_cache: &mut TileCache,
+    layers: LayerFlags,
+) {
+    debug_assert_eq!(bg_opaque.len(), WIDTH as usize * HEIGHT as usize);
+
+    let line = screen_y * WIDTH as usize..(screen_y + 1) * WIDTH as usize;
+    for pixel in screen[line.start * 4..line.end * 4].chunks_exact_mut(4) {
+        pixel.copy_from_slice(&palette.color(0));
+    }
+    bg_opaque[line].fill(false);
+
+    let regs = scanline_regs_from_ram(ram);
+    if (regs.lcdc & 0x80) == 0 {
+        return;
+    }
+    if (regs.lcdc & 0x01) != 0 {
+        if layers.background {
+            render_bg_line::<true>(ram, screen, bg_opaque, screen_y, regs, palette, tile_cache);
+        }
+        if layers.window {
+            render_window_line::<true>(ram, screen, bg_opaque, screen_y, regs, palette, tile_cache);
+        }
+    }
+    if layers.sprites {
+        render_obj::<true>(ram, screen, bg_opaque, palette, screen_y..screen_y + 1);
     }
 }
 
@@ -108,9 +450,9 @@ fn has_visible_priority_obj(ram: &[u8], lcdc: u8) -> bool {
     let obj_height = if (lcdc & 0x04) != 0 { 16 } else { 8 };
     let mut obj_addr = 0xFE00;
     while obj_addr <= 0xFE9F {
-        let tile_y = ram[obj_addr] as i16 - 16;
-        let tile_x = ram[obj_addr + 1] as i16 - 8;
-        let attributes = ram[obj_addr + 3];
+        let tile_y = ram[obj_addr - RAM_START] as i16 - 16;
+        let tile_x = ram[obj_addr + 1 - RAM_START] as i16 - 8;
+        let attributes = ram[obj_addr + 3 - RAM_START];
         let visible = tile_y < HEIGHT as i16
             && tile_y + obj_height as i16 > 0
             && tile_x < WIDTH as i16
@@ -123,8 +465,15 @@ fn has_visible_priority_obj(ram: &[u8], lcdc: u8) -> bool {
     false
 }
 
-fn render_obj<const CHECK_PRIORITY: bool>(ram: &[u8], screen: &mut [u8], bg_opaque: &[bool]) {
-    let lcdc = ram[0xFF40];
+/// Draws sprites onto the screen rows in `lines`.
+fn render_obj<const CHECK_PRIORITY: bool>(
+    ram: &[u8],
+    screen: &mut [u8],
+    bg_opaque: &[bool],
+    palette: &Palette,
+    lines: Range<usize>,
+) {
+    let lcdc = ram[io::LCDC as usize - RAM_START];
 
     // LCDC bit 1: OBJ (sprite) enable
     if (lcdc & 0x02) == 0 {
@@ -136,22 +485,22 @@ fn render_obj<const CHECK_PRIORITY: bool>(ram: &[u8], screen: &mut [u8], bg_opaq
     let mut obj_addr = 0xFE00;
 
     while obj_addr <= 0xFE9F {
-        let tile_y = ram[obj_addr] as i16 - 16;
-        let tile_x = ram[obj_addr + 1] as i16 - 8;
-        let tile_index = ram[obj_addr + 2];
-        let attributes = ram[obj_addr + 3];
+        let tile_y = ram[obj_addr - RAM_START] as i16 - 16;
+        let tile_x = ram[obj_addr + 1 - RAM_START] as i16 - 8;
+        let tile_index = ram[obj_addr + 2 - RAM_START];
+        let attributes = ram[obj_addr + 3 - RAM_START];
         let priority = (attributes & 0x80) != 0;
         let x_flip = (attributes & 0x20) != 0;
         let y_flip = (attributes & 0x40) != 0;
         let obp = if (attributes & 0x10) != 0 {
-            ram[0xFF49]
+            ram[io::OBP1 as usize - RAM_START]
         } else {
-            ram[0xFF48]
+            ram[io::OBP0 as usize - RAM_START]
         };
 
-        // Pre-clamp row/col ranges to screen bounds — no per-pixel contains() needed.
-        let row_start = (-tile_y).max(0) as usize;
-        let row_end = (HEIGHT as i16 - tile_y).clamp(0, obj_height as i16) as usize;
+        // Pre-clamp row/col ranges to the requested lines — no per-pixel contains() needed.
+        let row_start = (lines.start as i16 - tile_y).max(0) as usize;
+        let row_end = (lines.end as i16 - tile_y).clamp(0, obj_height as i16) as usize;
         let col_start = (-tile_x).max(0) as usize;
         let col_end = (WIDTH as i16 - tile_x).clamp(0, 8) as usize;
 
@@ -166,8 +515,8 @@ fn render_obj<const CHECK_PRIORITY: bool>(ram: &[u8], screen: &mut [u8], bg_opaq
                 tile_index as usize
             };
             let tile_addr = obj_tile_base + (row_tile_index * 16);
-            let lo = ram[tile_addr + tile_row * 2];
-            let hi = ram[tile_addr + tile_row * 2 + 1];
+            let lo = ram[tile_addr + tile_row * 2 - RAM_START];
+            let hi = ram[tile_addr + tile_row * 2 + 1 - RAM_START];
 
             for col in col_start..col_end {
                 let screen_x = (tile_x + col as i16) as usize;
@@ -187,7 +536,7 @@ fn render_obj<const CHECK_PRIORITY: bool>(ram: &[u8], screen: &mut [u8], bg_opaq
 
                 let color = (obp >> (palette_index * 2)) & 0x3;
                 let offset = (screen_y * WIDTH as usize + screen_x) * 4;
-                screen[offset..offset + 4].copy_from_slice(&GB_COLORS[color as usize]);
+                screen[offset..offset + 4].copy_from_slice(&palette.color(color));
             }
         }
 
@@ -209,6 +558,8 @@ fn render_bg_line<const TRACK_OPAQUE: bool>(
     bg_opaque: &mut [bool],
     screen_y: usize,
     regs: ScanlineRegs,
+    palette: &Palette,
+    tile_cache: &mut TileCache,
 ) {
     let lcdc = regs.lcdc;
     let bgp = regs.bgp;
@@ -223,32 +574,29 @@ fn render_bg_line<const TRACK_OPAQUE: bool>(
     let tile_row = bg_y >> 3;
     let pixel_y = bg_y & 7;
 
-    // Cache tile row bytes; recomputed only when tile_col changes (every 8 pixels).
+    // Cache the decoded tile row; looked up only when tile_col changes (every 8 pixels).
     let mut current_tile_col = usize::MAX;
-    let mut lo = 0u8;
-    let mut hi = 0u8;
+    let mut row = [0u8; 8];
 
     for screen_x in 0..WIDTH as usize {
         let bg_x = (scx + screen_x) & 0xFF;
         let tile_col = bg_x >> 3;
 
         if tile_col != current_tile_col {
-            let tile_index = ram[tile_map_base + tile_row * 32 + tile_col];
+            let tile_index = ram[tile_map_base + tile_row * 32 + tile_col - RAM_START];
             let addr = tile_address(tile_index, signed_addressing);
-            lo = ram[addr + pixel_y * 2];
-            hi = ram[addr + pixel_y * 2 + 1];
+            row = tile_cache.tile(ram, addr)[pixel_y];
             current_tile_col = tile_col;
         }
 
-        let bit = 7 - (bg_x & 7);
-        let palette_index = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
-        let shade = ((bgp >> (palette_index * 2)) & 0x03) as usize;
+        let palette_index = row[bg_x & 7];
+        let shade = (bgp >> (palette_index * 2)) & 0x03;
 
         let flat = screen_y * WIDTH as usize + screen_x;
         if TRACK_OPAQUE {
             bg_opaque[flat] = palette_index != 0;
         }
-        screen[flat * 4..flat * 4 + 4].copy_from_slice(&GB_COLORS[shade]);
+        screen[flat * 4..flat * 4 + 4].copy_from_slice(&palette.color(shade));
     }
 }
 
@@ -258,6 +606,8 @@ fn render_window_line<const TRACK_OPAQUE: bool>(
     bg_opaque: &mut [bool],
     screen_y: usize,
     regs: ScanlineRegs,
+    palette: &Palette,
+    tile_cache: &mut TileCache,
 ) {
     let lcdc = regs.lcdc;
     if (lcdc & 0x20) == 0 {
@@ -280,10 +630,9 @@ fn render_window_line<const TRACK_OPAQUE: bool>(
     let tile_row = win_y >> 3;
     let pixel_y = win_y & 7;
 
-    // Cache tile row bytes; recomputed only when tile_col changes (every 8 pixels).
+    // Cache the decoded tile row; looked up only when tile_col changes (every 8 pixels).
     let mut current_tile_col = usize::MAX;
-    let mut lo = 0u8;
-    let mut hi = 0u8;
+    let mut row = [0u8; 8];
 
     for screen_x in 0..WIDTH as usize {
         if screen_x + 7 < wx {
@@ -293,31 +642,182 @@ fn render_window_line<const TRACK_OPAQUE: bool>(
         let tile_col = win_x >> 3;
 
         if tile_col != current_tile_col {
-            let tile_index = ram[tile_map_base + tile_row * 32 + tile_col];
+            let tile_index = ram[tile_map_base + tile_row * 32 + tile_col - RAM_START];
             let addr = tile_address(tile_index, signed_addressing);
-            lo = ram[addr + pixel_y * 2];
-            hi = ram[addr + pixel_y * 2 + 1];
+            row = tile_cache.tile(ram, addr)[pixel_y];
             current_tile_col = tile_col;
         }
 
-        let bit = 7 - (win_x & 7);
-        let palette_index = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
-        let shade = ((bgp >> (palette_index * 2)) & 0x03) as usize;
+        let palette_index = row[win_x & 7];
+        let shade = (bgp >> (palette_index * 2)) & 0x03;
 
         let flat = screen_y * WIDTH as usize + screen_x;
         if TRACK_OPAQUE {
             bg_opaque[flat] = palette_index != 0;
         }
-        screen[flat * 4..flat * 4 + 4].copy_from_slice(&GB_COLORS[shade]);
+        screen[flat * 4..flat * 4 + 4].copy_from_slice(&palette.color(shade));
     }
 }
 
+/// Where one CGB background/window pixel came from: its colour index within the tile and the
+/// tile's attribute byte (palette in bits 0-2, priority over sprites in bit 7).
+#[cfg(feature = "cgb")]
+#[derive(Clone, Copy, Default)]
+struct CgbBgPixel {
+    color: u8,
+    attributes: u8,
+}
+
+/// Renders screen line `screen_y` in CGB colour: BG and window tiles take their palette, tile
+/// bank and flips from the attribute map in VRAM bank 1, sprites take theirs from OAM bits 0-3.
+/// `vram_bank1` holds the 8 KiB of bank 1 (0x8000-0x9FFF); `ram` is 0x8000-0xFFFF with bank 0
+/// in place. The PPU draws CGB cartridges with this instead of `render_scanline`, so the DMG
+/// palette and layer toggles do not apply to them.
+#[cfg(feature = "cgb")]
+pub fn render_scanline_cgb(
+    ram: &[u8],
+    vram_bank1: &[u8],
+    bg_palettes: &PaletteRam,
+    obj_palettes: &PaletteRam,
+    screen: &mut [u8],
+    screen_y: usize,
+) {
+    debug_assert_eq!(vram_bank1.len(), 0x2000);
+
+    let regs = scanline_regs_from_ram(ram);
+    let row = &mut screen[screen_y * WIDTH as usize * 4..(screen_y + 1) * WIDTH as usize * 4];
+    if (regs.lcdc & 0x80) == 0 {
+        for pixel in row.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[0xFF; 4]);
+        }
+        return;
+    }
+
+    let vram_bank0 = &ram[0x8000 - RAM_START..0xA000 - RAM_START];
+    let signed_addressing = (regs.lcdc & 0x10) == 0;
+    let bg_map: usize = if (regs.lcdc & 0x08) != 0 {
+        0x9C00
+    } else {
+        0x9800
+    };
+    let window_map: usize = if (regs.lcdc & 0x40) != 0 {
+        0x9C00
+    } else {
+        0x9800
+    };
+    let wx = regs.wx as usize;
+    let window_visible = (regs.lcdc & 0x20) != 0 && screen_y >= regs.wy as usize;
+
+    let mut line = [CgbBgPixel::default(); WIDTH as usize];
+    for (screen_x, pixel) in line.iter_mut().enumerate() {
+        let (map_base, x, y) = if window_visible && screen_x + 7 >= wx {
+            (window_map, screen_x + 7 - wx, screen_y - regs.wy as usize)
+        } else {
+            (
+                bg_map,
+                (regs.scx as usize + screen_x) & 0xFF,
+                (regs.scy as usize + screen_y) & 0xFF,
+            )
+        };
+        let map_offset = map_base - 0x8000 + (y >> 3) * 32 + (x >> 3);
+        let attributes = vram_bank1[map_offset];
+        let tile = tile_address(vram_bank0[map_offset], signed_addressing) - 0x8000;
+        let bank = if (attributes & 0x08) != 0 {
+            vram_bank1
+        } else {
+            vram_bank0
+        };
+        let pixel_y = if (attributes & 0x40) != 0 {
+            7 - (y & 7)
+        } else {
+            y & 7
+        };
+        let pixel_x = if (attributes & 0x20) != 0 {
+            7 - (x & 7)
+        } else {
+            x & 7
+        };
+        *pixel = CgbBgPixel {
+            color: tile_row_pixel(bank, tile + pixel_y * 2, pixel_x),
+            attributes,
+        };
+        let offset = screen_x * 4;
+        row[offset..offset + 4].copy_from_slice(&bg_palettes.rgba(attributes, pixel.color));
+    }
+
+    if (regs.lcdc & 0x02) == 0 {
+        return;
+    }
+    let obj_height: usize = if (regs.lcdc & 0x04) != 0 { 16 } else { 8 };
+    // Lower OAM indices win on CGB, so draw from the back and let earlier sprites overwrite.
+    for obj_addr in (0xFE00..0xFEA0).step_by(4).rev() {
+        let tile_y = ram[obj_addr - RAM_START] as i16 - 16;
+        let tile_x = ram[obj_addr + 1 - RAM_START] as i16 - 8;
+        let obj_row = screen_y as i16 - tile_y;
+        if obj_row < 0 || obj_row >= obj_height as i16 {
+            continue;
+        }
+        let attributes = ram[obj_addr + 3 - RAM_START];
+        let obj_row = if (attributes & 0x40) != 0 {
+            obj_height - 1 - obj_row as usize
+        } else {
+            obj_row as usize
+        };
+        let tile_index = if obj_height == 16 {
+            (ram[obj_addr + 2 - RAM_START] & 0xFE) as usize + (obj_row >> 3)
+        } else {
+            ram[obj_addr + 2 - RAM_START] as usize
+        };
+        let bank = if (attributes & 0x08) != 0 {
+            vram_bank1
+        } else {
+            vram_bank0
+        };
+
+        for col in 0..8 {
+            let screen_x = tile_x + col;
+            if !(0..WIDTH as i16).contains(&screen_x) {
+                continue;
+            }
+            let screen_x = screen_x as usize;
+            let pixel_x = if (attributes & 0x20) != 0 {
+                7 - col
+            } else {
+                col
+            } as usize;
+            let color = tile_row_pixel(bank, tile_index * 16 + (obj_row & 7) * 2, pixel_x);
+            if color == 0 {
+                continue;
+            }
+            // LCDC bit 0 is the CGB master priority: when clear, sprites always draw on top.
+            let bg = line[screen_x];
+            let bg_wins = (regs.lcdc & 0x01) != 0
+                && bg.color != 0
+                && ((bg.attributes | attributes) & 0x80) != 0;
+            if bg_wins {
+                continue;
+            }
+            let offset = screen_x * 4;
+            row[offset..offset + 4].copy_from_slice(&obj_palettes.rgba(attributes, color));
+        }
+    }
+}
+
+/// The 2bpp colour index of pixel `x` (0 = leftmost) in the tile row starting at `offset`.
+#[cfg(feature = "cgb")]
+fn tile_row_pixel(bank: &[u8], offset: usize, x: usize) -> u8 {
+    let bit = 7 - x;
+    ((bank[offset + 1] >> bit) & 1) << 1 | ((bank[offset] >> bit) & 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const GB_COLORS: [[u8; 4]; 4] = Palette::DMG_GREEN.colors;
+
     fn blank_ram() -> Vec<u8> {
-        vec![0u8; 65536]
+        vec![0u8; 0x10000 - RAM_START]
     }
 
     fn blank_screen() -> Vec<u8> {
@@ -333,9 +833,100 @@ mod tests {
     // `rows` is 8 pairs of (lo_byte, hi_byte).
     fn write_tile(ram: &mut [u8], tile_address: usize, rows: [(u8, u8); 8]) {
         for (i, (lo, hi)) in rows.iter().enumerate() {
-            ram[tile_address + i * 2] = *lo;
-            ram[tile_address + i * 2 + 1] = *hi;
+            ram[tile_address + i * 2 - RAM_START] = *lo;
+            ram[tile_address + i * 2 + 1 - RAM_START] = *hi;
+        }
+    }
+
+    #[test]
+    fn frame_blender_averages_the_last_two_frames() {
+        let mut blender = FrameBlender::new(0.5);
+        let mut out = [0u8; 4];
+
+        blender.blend(&[200, 100, 0, 255], &mut out);
+        assert_eq!(
+            out,
+            [200, 100, 0, 255],
+            "first frame has nothing to blend with"
+        );
+
+        blender.blend(&[0, 100, 200, 255], &mut out);
+        assert_eq!(out, [100, 100, 100, 255]);
+
+        // Redrawing the same frame keeps the same blend.
+        blender.blend(&[0, 100, 200, 255], &mut out);
+        assert_eq!(out, [100, 100, 100, 255]);
+    }
+
+    #[test]
+    fn frame_blender_weight_favours_the_previous_frame() {
+        let mut blender = FrameBlender::new(0.75);
+        let mut out = [0u8; 1];
+
+        blender.blend(&[0], &mut out);
+        blender.blend(&[200], &mut out);
+        assert_eq!(out, [50]);
+        assert_eq!(FrameBlender::new(2.0).weight(), 1.0);
+    }
+
+    #[test]
+    fn scale_rgba_repeats_each_pixel_factor_times_in_both_directions() {
+        let (a, b, c, d) = (
+            [1, 2, 3, 4],
+            [5, 6, 7, 8],
+            [9, 10, 11, 12],
+            [13, 14, 15, 16],
+        );
+        let src = [a, b, c, d].concat();
+
+        let scaled = scale_rgba(&src, 2, 2, 2);
+
+        let expected = [a, a, b, b, a, a, b, b, c, c, d, d, c, c, d, d].concat();
+        assert_eq!(scaled, expected);
+        assert_eq!(scale_rgba(&src, 2, 2, 1), src);
+    }
+
+    #[test]
+    fn scale_framebuffer_multiplies_screen_dimensions() {
+        let scaled = scale_framebuffer(&blank_screen(), 3);
+        assert_eq!(scaled.len(), blank_screen().len() * 9);
+    }
+
+    #[test]
+    fn frame_hash_matches_identical_frames_and_changes_with_one_pixel() {
+        assert_eq!(frame_hash(b""), 0xCBF2_9CE4_8422_2325);
+        assert_eq!(
+            frame_hash(b"a"),
+            0xAF63_DC4C_8601_EC8C,
+            "FNV-1a reference value"
+        );
+
+        let frame = blank_screen();
+        assert_eq!(frame_hash(&frame), frame_hash(&blank_screen()));
+
+        let mut changed = blank_screen();
+        changed[(80 * WIDTH as usize + 80) * 4] = 0x01;
+        assert_ne!(frame_hash(&frame), frame_hash(&changed));
+    }
+
+    #[test]
+    fn render_ascii_draws_a_dark_frame_in_the_darkest_character() {
+        let mut ram = blank_ram();
+        ram[0xFF40 - RAM_START] = 0x91; // LCD and BG on
+        ram[0xFF47 - RAM_START] = 0xFF; // every colour index is shade 3
+
+        let ascii = render_ascii(&ram);
+
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), 18);
+        for line in lines {
+            assert_eq!(line, "#".repeat(40));
         }
+
+        ram[0xFF47 - RAM_START] = 0x00;
+        assert!(render_ascii(&ram)
+            .lines()
+            .all(|line| line.trim().is_empty()));
     }
 
     #[test]
@@ -351,6 +942,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dmg_green_palette_maps_lightest_to_darkest() {
+        let palette = Palette::DMG_GREEN;
+        assert_eq!(palette.color(0), [0x9B, 0xBC, 0x0F, 0xFF]);
+        assert_eq!(palette.color(3), [0x0F, 0x38, 0x0F, 0xFF]);
+    }
+
+    #[test]
+    fn render_uses_the_given_palette() {
+        let ram = blank_ram();
+        let mut screen = blank_screen();
+        let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
+        render_frame_with_bg_opaque(
+            &ram,
+            &mut screen,
+            &mut bg_opaque,
+            &Palette::GRAYSCALE,
+            &mut TileCache::new(),
+        );
+        assert_eq!(pixel(&screen, 0, 0), Palette::GRAYSCALE.colors[0]);
+    }
+
+    #[test]
+    fn rendered_frame_is_finished_rgba_with_opaque_alpha() {
+        let mut ram = blank_ram();
+        ram[0xFF40 - RAM_START] = 0xB3; // LCD, window map 0x9C00, BG/window, OBJ on
+        ram[0xFF47 - RAM_START] = 0xE4;
+        ram[0xFF48 - RAM_START] = 0xE4;
+        for (offset, byte) in ram[0x8000 - RAM_START..0x9800 - RAM_START]
+            .iter_mut()
+            .enumerate()
+        {
+            *byte = (offset * 37) as u8;
+        }
+        ram[0xFE00 - RAM_START] = 40; // sprite 0 on screen
+        ram[0xFE01 - RAM_START] = 40;
+        ram[0xFE02 - RAM_START] = 3;
+
+        for palette in Palette::PRESETS {
+            let mut screen = blank_screen();
+            let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
+            render_frame_with_bg_opaque(
+                &ram,
+                &mut screen,
+                &mut bg_opaque,
+                &palette,
+                &mut TileCache::new(),
+            );
+
+            assert_eq!(screen.len(), WIDTH as usize * HEIGHT as usize * 4);
+            assert!(
+                screen.chunks_exact(4).all(|pixel| pixel[3] == 0xFF),
+                "{} left a non-opaque pixel",
+                palette.name
+            );
+        }
+    }
+
+    #[test]
+    fn tile_cache_masks_out_of_range_addresses_into_vram_tile_data() {
+        let mut ram = blank_ram();
+        write_tile(&mut ram, 0x8010, [(0xFF, 0x00); 8]);
+        let mut cache = TileCache::new();
+
+        assert_eq!(
+            cache.tile(&ram, 0xA010),
+            &[[1; 8]; 8],
+            "wraps onto tile 1 at 0x8010"
+        );
+        assert_eq!(
+            cache.tile(&ram, 0x8017),
+            &[[1; 8]; 8],
+            "unaligned addresses round down"
+        );
+        for address in [0, 0x7FFF, 0x97F1, 0x9FFF, 0xFFFF, usize::MAX] {
+            cache.tile(&ram, address);
+        }
+    }
+
+    #[test]
+    fn tile_cache_hits_on_repeat_decode_and_misses_after_vram_write() {
+        let mut ram = blank_ram();
+        write_tile(&mut ram, 0x8010, [(0x80, 0x80); 8]);
+        let mut cache = TileCache::new();
+
+        assert_eq!(cache.tile(&ram, 0x8010)[0][0], 3);
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        assert_eq!(cache.tile(&ram, 0x8010)[0][0], 3);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        ram[0x8010 - RAM_START] = 0x00; // VRAM write to the cached tile
+        assert_eq!(
+            cache.tile(&ram, 0x8010)[0][0],
+            3,
+            "stale until marked dirty"
+        );
+        let mut written = DirtyTiles::default();
+        written.mark(0x8010);
+        cache.mark_dirty(written);
+        assert_eq!(cache.tile(&ram, 0x8010)[0][0], 2);
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    }
+
     #[test]
     fn tile_pixel_decode_palette_indices() {
         // Tile at index 0, address 0x9000 (signed, bit 4 of LCDC = 0).
@@ -360,8 +1055,8 @@ mod tests {
         // Pixel bit 5: lo=1, hi=0 → palette 1
         // Pixel bit 4: lo=0, hi=0 → palette 0
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x81; // LCDC: display on, BG enabled, signed tile data
-        ram[0xFF47] = 0xE4; // BGP: identity mapping (3→3, 2→2, 1→1, 0→0)
+        ram[0xFF40 - RAM_START] = 0x81; // LCDC: display on, BG enabled, signed tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping (3→3, 2→2, 1→1, 0→0)
         write_tile(
             &mut ram,
             0x9000,
@@ -389,11 +1084,11 @@ mod tests {
         // Put a solid palette-3 tile at BG map col 1 (tile index 1 in map).
         // Set SCX=8 so BG col 1 appears at screen x=0.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x81; // LCDC: display on, BG enabled, signed tile data
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0xFF43] = 8; // SCX
-        ram[0x9801] = 1; // tile map col 1 → tile index 1
-                         // Tile 1 at 0x9000 + 1*16 = 0x9010: all pixels palette 3
+        ram[0xFF40 - RAM_START] = 0x81; // LCDC: display on, BG enabled, signed tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF43 - RAM_START] = 8; // SCX
+        ram[0x9801 - RAM_START] = 1; // tile map col 1 → tile index 1
+                                     // Tile 1 at 0x9000 + 1*16 = 0x9010: all pixels palette 3
         write_tile(
             &mut ram,
             0x9010,
@@ -420,10 +1115,10 @@ mod tests {
         // Solid palette-3 tile at BG map row 1 (tile index 1 in map).
         // Set SCY=8 so BG row 1 appears at screen y=0.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x81; // LCDC: display on, BG enabled, signed tile data
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0xFF42] = 8; // SCY
-        ram[0x9820] = 1; // tile map row 1 col 0 → tile index 1
+        ram[0xFF40 - RAM_START] = 0x81; // LCDC: display on, BG enabled, signed tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF42 - RAM_START] = 8; // SCY
+        ram[0x9820 - RAM_START] = 1; // tile map row 1 col 0 → tile index 1
         write_tile(
             &mut ram,
             0x9010,
@@ -448,21 +1143,21 @@ mod tests {
     #[test]
     fn scanline_latches_allow_per_line_scx_splits() {
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x91; // LCDC: display on, BG on, unsigned tile data
-        ram[0xFF47] = 0xE4; // BGP: identity
+        ram[0xFF40 - RAM_START] = 0x91; // LCDC: display on, BG on, unsigned tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
 
         // Tile map row 0: col 0 -> tile 1 (shade 3), col 1 -> tile 2 (shade 1).
-        ram[0x9800] = 1;
-        ram[0x9801] = 2;
+        ram[0x9800 - RAM_START] = 1;
+        ram[0x9801 - RAM_START] = 2;
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: shade 3
         write_tile(&mut ram, 0x8020, [(0xFF, 0x00); 8]); // tile 2: shade 1
 
         let mut latches = [ScanlineRegs::default(); HEIGHT as usize];
         let base = ScanlineRegs {
-            lcdc: ram[0xFF40],
+            lcdc: ram[0xFF40 - RAM_START],
             scy: 0,
             scx: 0,
-            bgp: ram[0xFF47],
+            bgp: ram[0xFF47 - RAM_START],
             wy: 0,
             wx: 0,
         };
@@ -471,7 +1166,14 @@ mod tests {
 
         let mut screen = blank_screen();
         let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
-        render_frame_with_scanline_latches(&ram, &mut screen, &mut bg_opaque, &latches);
+        render_frame_with_scanline_latches(
+            &ram,
+            &mut screen,
+            &mut bg_opaque,
+            &latches,
+            &Palette::DMG_GREEN,
+            &mut TileCache::new(),
+        );
 
         assert_eq!(pixel(&screen, 0, 0), GB_COLORS[3], "line 0 uses SCX=0");
         assert_eq!(pixel(&screen, 0, 1), GB_COLORS[1], "line 1 uses SCX=8");
@@ -480,8 +1182,8 @@ mod tests {
     #[test]
     fn scanline_latches_allow_per_line_window_enable() {
         let mut ram = blank_ram();
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0x9C00] = 1; // window tile map index 0 -> tile 1
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0x9C00 - RAM_START] = 1; // window tile map index 0 -> tile 1
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: shade 3
 
         let mut latches = [ScanlineRegs::default(); HEIGHT as usize];
@@ -489,7 +1191,7 @@ mod tests {
             lcdc: 0x91, // LCD on, BG on, window off, unsigned tile data
             scy: 0,
             scx: 0,
-            bgp: ram[0xFF47],
+            bgp: ram[0xFF47 - RAM_START],
             wy: 0,
             wx: 7, // window appears from screen x=0
         };
@@ -498,7 +1200,14 @@ mod tests {
 
         let mut screen = blank_screen();
         let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
-        render_frame_with_scanline_latches(&ram, &mut screen, &mut bg_opaque, &latches);
+        render_frame_with_scanline_latches(
+            &ram,
+            &mut screen,
+            &mut bg_opaque,
+            &latches,
+            &Palette::DMG_GREEN,
+            &mut TileCache::new(),
+        );
 
         assert_eq!(
             pixel(&screen, 0, 0),
@@ -517,13 +1226,13 @@ mod tests {
         // SCX=252: bg_x starts at 252 (tile col 31), wraps to 0 (tile col 0) after 4 pixels.
         // Tests that the per-row tile cache invalidates correctly on the 256→0 wrap.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x91; // LCDC: display on, BG on, unsigned tile data
-        ram[0xFF47] = 0xE4; // BGP: identity
-        ram[0xFF43] = 252; // SCX
+        ram[0xFF40 - RAM_START] = 0x91; // LCDC: display on, BG on, unsigned tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
+        ram[0xFF43 - RAM_START] = 252; // SCX
 
         // Tile map row 0: col 31 → tile 1 (shade 3), col 0 → tile 2 (shade 1)
-        ram[0x9800 + 31] = 1;
-        ram[0x9800] = 2;
+        ram[0x9800 + 31 - RAM_START] = 1;
+        ram[0x9800 - RAM_START] = 2;
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: palette index 3
         write_tile(&mut ram, 0x8020, [(0xFF, 0x00); 8]); // tile 2: palette index 1
 
@@ -553,12 +1262,12 @@ mod tests {
         // Two different tiles side-by-side; verifies the cache switches correctly at the
         // 8-pixel tile boundary with no scroll offset.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x91; // LCDC: display on, BG on, unsigned tile data
-        ram[0xFF47] = 0xE4; // BGP: identity
+        ram[0xFF40 - RAM_START] = 0x91; // LCDC: display on, BG on, unsigned tile data
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
 
         // Tile map: col 0 → tile 1 (shade 3), col 1 → tile 2 (shade 1)
-        ram[0x9800] = 1;
-        ram[0x9801] = 2;
+        ram[0x9800 - RAM_START] = 1;
+        ram[0x9801 - RAM_START] = 2;
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: shade 3
         write_tile(&mut ram, 0x8020, [(0xFF, 0x00); 8]); // tile 2: shade 1
 
@@ -573,16 +1282,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn lcdc_bit_3_moves_the_background_to_the_other_map_but_not_the_window() {
+        let mut ram = blank_ram();
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF4A - RAM_START] = 72; // WY: window covers the lower half
+        ram[0xFF4B - RAM_START] = 7; // WX: from x=0
+        ram[0x9800 - RAM_START] = 1; // 0x9800 entry 0 -> tile 1 (shade 3)
+        ram[0x9C00 - RAM_START] = 2; // 0x9C00 entry 0 -> tile 2 (shade 1)
+        write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]);
+        write_tile(&mut ram, 0x8020, [(0xFF, 0x00); 8]);
+
+        let mut screen = blank_screen();
+        ram[0xFF40 - RAM_START] = 0xB1; // display, window, BG on; BG map 0x9800, window map 0x9800
+        render_frame(&ram, &mut screen);
+        assert_eq!(pixel(&screen, 0, 0), GB_COLORS[3], "BG from 0x9800");
+        assert_eq!(pixel(&screen, 0, 72), GB_COLORS[3], "window from 0x9800");
+
+        ram[0xFF40 - RAM_START] = 0xB9; // only bit 3 set on top: BG map 0x9C00
+        render_frame(&ram, &mut screen);
+        assert_eq!(pixel(&screen, 0, 0), GB_COLORS[1], "BG from 0x9C00");
+        assert_eq!(
+            pixel(&screen, 0, 72),
+            GB_COLORS[3],
+            "window map follows bit 6, not bit 3"
+        );
+    }
+
     // --- Window rendering ---
 
     #[test]
     fn window_disabled_leaves_background_unchanged() {
         let mut ram = blank_ram();
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0xFF40] = 0x99; // LCDC: display on, BG on, BG map=0x9C00, unsigned tile data, window off
-        ram[0xFF4A] = 0; // WY
-        ram[0xFF4B] = 7; // WX: window origin x=0 if enabled
-        ram[0x9800] = 1; // window map tile index (would be visible if window were enabled)
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF40 - RAM_START] = 0x99; // LCDC: display on, BG on, BG map=0x9C00, unsigned tile data, window off
+        ram[0xFF4A - RAM_START] = 0; // WY
+        ram[0xFF4B - RAM_START] = 7; // WX: window origin x=0 if enabled
+        ram[0x9800 - RAM_START] = 1; // window map tile index (would be visible if window were enabled)
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: shade 3
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
@@ -596,11 +1332,11 @@ mod tests {
     #[test]
     fn window_uses_wx_minus_7_and_wy() {
         let mut ram = blank_ram();
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0xFF40] = 0xB9; // LCDC: display on, BG on, window on, BG map=0x9C00, unsigned tile data
-        ram[0xFF4A] = 5; // WY: window starts at y=5
-        ram[0xFF4B] = 15; // WX: window starts at x=8 (WX-7)
-        ram[0x9800] = 1; // window map tile 0 -> tile index 1
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF40 - RAM_START] = 0xB9; // LCDC: display on, BG on, window on, BG map=0x9C00, unsigned tile data
+        ram[0xFF4A - RAM_START] = 5; // WY: window starts at y=5
+        ram[0xFF4B - RAM_START] = 15; // WX: window starts at x=8 (WX-7)
+        ram[0x9800 - RAM_START] = 1; // window map tile 0 -> tile index 1
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // tile 1: shade 3
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
@@ -623,15 +1359,15 @@ mod tests {
     fn obj_disabled_leaves_background_unchanged() {
         // LCDC bit 1 clear: sprites must not appear even if OAM has valid data.
         let mut ram = blank_ram();
-        ram[0xFF47] = 0xE4; // BGP: identity
-        ram[0xFF40] = 0xA1; // LCDC: display on, BG on, window on, OBJ off (bit 1 = 0)
-        ram[0xFF48] = 0xE4; // OBP0: identity
-                            // Place a solid sprite tile at VRAM index 1
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
+        ram[0xFF40 - RAM_START] = 0xA1; // LCDC: display on, BG on, window on, OBJ off (bit 1 = 0)
+        ram[0xFF48 - RAM_START] = 0xE4; // OBP0: identity
+                                        // Place a solid sprite tile at VRAM index 1
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]);
         // OAM entry 0: Y=24 (screen 8), X=16 (screen 8), tile 1
-        ram[0xFE00] = 24;
-        ram[0xFE01] = 16;
-        ram[0xFE02] = 1;
+        ram[0xFE00 - RAM_START] = 24;
+        ram[0xFE01 - RAM_START] = 16;
+        ram[0xFE02 - RAM_START] = 1;
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
         // Background with all-zero tile data → shade 0 everywhere
@@ -646,14 +1382,14 @@ mod tests {
     fn sprite_appears_at_oam_position() {
         // OAM Y=24 → screen y=8, OAM X=16 → screen x=8.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x93; // LCDC: display on, BG on, OBJ on (bit 1), unsigned tile data (bit 4)
-        ram[0xFF47] = 0xE4; // BGP: identity (background stays shade 0)
-        ram[0xFF48] = 0xE4; // OBP0: identity
-                            // Sprite tile 1 at 0x8010: all pixels palette index 3
+        ram[0xFF40 - RAM_START] = 0x93; // LCDC: display on, BG on, OBJ on (bit 1), unsigned tile data (bit 4)
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity (background stays shade 0)
+        ram[0xFF48 - RAM_START] = 0xE4; // OBP0: identity
+                                        // Sprite tile 1 at 0x8010: all pixels palette index 3
         write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]);
-        ram[0xFE00] = 24; // Y: screen row 8
-        ram[0xFE01] = 16; // X: screen col 8
-        ram[0xFE02] = 1; // tile index
+        ram[0xFE00 - RAM_START] = 24; // Y: screen row 8
+        ram[0xFE01 - RAM_START] = 16; // X: screen col 8
+        ram[0xFE02 - RAM_START] = 1; // tile index
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
         assert_eq!(
@@ -668,18 +1404,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn disabled_background_layer_leaves_only_sprites() {
+        let mut ram = blank_ram();
+        ram[0xFF40 - RAM_START] = 0x93; // LCDC: display on, BG on, OBJ on, unsigned addressing
+        ram[0xFF47 - RAM_START] = 0xE4;
+        ram[0xFF48 - RAM_START] = 0xE4;
+        write_tile(&mut ram, 0x8000, [(0xFF, 0x00); 8]); // BG tile 0: shade 1 everywhere
+        write_tile(&mut ram, 0x8010, [(0xFF, 0xFF); 8]); // sprite tile 1: shade 3
+        ram[0xFE00 - RAM_START] = 24; // Y: screen row 8
+        ram[0xFE01 - RAM_START] = 16; // X: screen col 8
+        ram[0xFE02 - RAM_START] = 1;
+
+        let layers = LayerFlags {
+            background: false,
+            ..LayerFlags::default()
+        };
+        let mut screen = blank_screen();
+        let mut bg_opaque = vec![false; WIDTH as usize * HEIGHT as usize];
+        let mut tile_cache = TileCache::new();
+        for y in 0..HEIGHT as usize {
+            render_scanline(
+                &ram,
+                &mut screen,
+                &mut bg_opaque,
+                y,
+                &Palette::DMG_GREEN,
+                &mut tile_cache,
+                layers,
+            );
+        }
+
+        assert!(
+            (0..HEIGHT as usize).all(|y| pixel(&screen, 0, y) == GB_COLORS[0]),
+            "no background pixels with the layer off"
+        );
+        assert_eq!(pixel(&screen, 8, 8), GB_COLORS[3], "sprite still drawn");
+    }
+
     #[test]
     fn sprite_palette_index_zero_is_transparent() {
         // A tile with all-zero data → every pixel is palette index 0 → transparent.
         // The background (shade 0) must show through.
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x93; // LCDC: display on, BG on, OBJ on, unsigned addressing
-        ram[0xFF47] = 0xE4;
-        ram[0xFF48] = 0xE4;
+        ram[0xFF40 - RAM_START] = 0x93; // LCDC: display on, BG on, OBJ on, unsigned addressing
+        ram[0xFF47 - RAM_START] = 0xE4;
+        ram[0xFF48 - RAM_START] = 0xE4;
         // Tile 1 all zeroes (already the case in blank_ram)
-        ram[0xFE00] = 24;
-        ram[0xFE01] = 16;
-        ram[0xFE02] = 1;
+        ram[0xFE00 - RAM_START] = 24;
+        ram[0xFE01 - RAM_START] = 16;
+        ram[0xFE02 - RAM_START] = 1;
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
         assert_eq!(
@@ -692,20 +1466,20 @@ mod tests {
     #[test]
     fn sprite_priority_bit_defers_to_non_zero_bg_pixel() {
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x93; // LCDC: display on, BG on, OBJ on, unsigned addressing
-        ram[0xFF47] = 0xE4; // BGP: identity
-        ram[0xFF48] = 0xE4; // OBP0: identity
+        ram[0xFF40 - RAM_START] = 0x93; // LCDC: display on, BG on, OBJ on, unsigned addressing
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
+        ram[0xFF48 - RAM_START] = 0xE4; // OBP0: identity
 
         // Background tile at screen tile row 1, col 1: solid palette index 1.
-        ram[0x9800 + 32 + 1] = 1;
+        ram[0x9800 + 32 + 1 - RAM_START] = 1;
         write_tile(&mut ram, 0x8010, [(0xFF, 0x00); 8]);
 
         // Sprite tile 2: solid palette index 3, but with priority bit set.
         write_tile(&mut ram, 0x8020, [(0xFF, 0xFF); 8]);
-        ram[0xFE00] = 24; // Y: screen row 8
-        ram[0xFE01] = 16; // X: screen col 8
-        ram[0xFE02] = 2; // tile index
-        ram[0xFE03] = 0x80; // priority behind non-zero BG
+        ram[0xFE00 - RAM_START] = 24; // Y: screen row 8
+        ram[0xFE01 - RAM_START] = 16; // X: screen col 8
+        ram[0xFE02 - RAM_START] = 2; // tile index
+        ram[0xFE03 - RAM_START] = 0x80; // priority behind non-zero BG
 
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
@@ -720,9 +1494,9 @@ mod tests {
     #[test]
     fn sprite_attribute_x_flip_mirrors_horizontally() {
         let mut ram = blank_ram();
-        ram[0xFF40] = 0x93; // LCDC: display on, BG on, OBJ on, 8x8 sprites
-        ram[0xFF47] = 0xE4; // BGP: identity
-        ram[0xFF48] = 0xE4; // OBP0: identity
+        ram[0xFF40 - RAM_START] = 0x93; // LCDC: display on, BG on, OBJ on, 8x8 sprites
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity
+        ram[0xFF48 - RAM_START] = 0xE4; // OBP0: identity
 
         // Sprite tile 1 row 0 has a single palette-3 pixel at the left edge.
         write_tile(
@@ -740,10 +1514,10 @@ mod tests {
             ],
         );
 
-        ram[0xFE00] = 24; // Y: screen row 8
-        ram[0xFE01] = 16; // X: screen col 8
-        ram[0xFE02] = 1; // tile index
-        ram[0xFE03] = 0x20; // X flip
+        ram[0xFE00 - RAM_START] = 24; // Y: screen row 8
+        ram[0xFE01 - RAM_START] = 16; // X: screen col 8
+        ram[0xFE02 - RAM_START] = 1; // tile index
+        ram[0xFE03 - RAM_START] = 0x20; // X flip
 
         let mut screen = blank_screen();
         render_frame(&ram, &mut screen);
@@ -765,9 +1539,9 @@ mod tests {
         // LCDC bit 4 = 1 → tile data at 0x8000 + index*16 (unsigned).
         // Tile index 1 → 0x8010.
         let mut ram = blank_ram();
-        ram[0xFF47] = 0xE4; // BGP: identity mapping
-        ram[0xFF40] = 0x91; // LCDC: display on, BG on, bit 4 set
-        ram[0x9800] = 1; // tile map slot 0 → tile index 1
+        ram[0xFF47 - RAM_START] = 0xE4; // BGP: identity mapping
+        ram[0xFF40 - RAM_START] = 0x91; // LCDC: display on, BG on, bit 4 set
+        ram[0x9800 - RAM_START] = 1; // tile map slot 0 → tile index 1
         write_tile(
             &mut ram,
             0x8010,
@@ -793,4 +1567,37 @@ mod tests {
             assert_eq!(pixel(&screen, col, 1), GB_COLORS[0], "col {col}");
         }
     }
+
+    #[cfg(feature = "cgb")]
+    #[test]
+    fn cgb_tile_renders_in_the_colour_written_to_its_palette() {
+        use crate::memory::{Addr, Ram};
+
+        let mut memory = Ram::new();
+        // Background palette 2, colour 1 = pure blue (BGR555 0x7C00), with auto-increment.
+        memory.write_byte(Addr(io::BCPS), 0x80 | 0x12); // palette 2 is bytes 0x10-0x17
+        memory.write_byte(Addr(io::BCPD), 0x00);
+        memory.write_byte(Addr(io::BCPD), 0x7C);
+        // Tile 0, row 0: leftmost pixel colour 1, the rest colour 0.
+        memory.write_byte(Addr(0x8000), 0x80);
+        let mut vram_bank1 = vec![0u8; 0x2000];
+        vram_bank1[0x1800] = 0x02; // attribute for map entry 0x9800: palette 2
+
+        let mut screen = blank_screen();
+        render_scanline_cgb(
+            memory.as_slice(),
+            &vram_bank1,
+            memory.bg_palettes(),
+            memory.obj_palettes(),
+            &mut screen,
+            0,
+        );
+
+        assert_eq!(pixel(&screen, 0, 0), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(
+            pixel(&screen, 1, 0),
+            memory.bg_palettes().rgba(2, 0),
+            "colour 0 of the same palette"
+        );
+    }
 }
@@ -191,7 +191,7 @@ fn load_rom_from_zip_or_err(
 ) -> Result<(Vec<u8>, String), RomLoadError> {
     #[cfg(feature = "rom-zip")]
     {
-        return load_rom_from_zip(path, bytes, entry);
+        load_rom_from_zip(path, bytes, entry)
     }
     #[cfg(not(feature = "rom-zip"))]
     {
@@ -211,7 +211,7 @@ fn load_rom_from_gzip_or_err(
 ) -> Result<(Vec<u8>, String), RomLoadError> {
     #[cfg(feature = "rom-gzip")]
     {
-        return load_rom_from_gzip(path, bytes, entry);
+        load_rom_from_gzip(path, bytes, entry)
     }
     #[cfg(not(feature = "rom-gzip"))]
     {
@@ -231,7 +231,7 @@ fn load_rom_from_7z_or_err(
 ) -> Result<(Vec<u8>, String), RomLoadError> {
     #[cfg(feature = "rom-7z")]
     {
-        return load_rom_from_7z(path, bytes, entry);
+        load_rom_from_7z(path, bytes, entry)
     }
     #[cfg(not(feature = "rom-7z"))]
     {
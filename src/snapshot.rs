@@ -0,0 +1,152 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use crate::cpu::Cpu;
+use crate::memory::{region, Addr, MemRegion};
+
+const MAGIC: &[u8; 4] = b"GBSS";
+const REGISTER_NAMES: [&str; 11] = ["A", "F", "B", "C", "D", "E", "H", "L", "SP", "PC", "IME"];
+const REGISTERS_LEN: usize = 13;
+const ADDRESS_SPACE_LEN: usize = 0x10000;
+const SNAPSHOT_LEN: usize = MAGIC.len() + REGISTERS_LEN + ADDRESS_SPACE_LEN;
+
+/// Serializes the registers and the CPU-visible address space, for comparing two runs.
+/// This is not a restorable save state: PPU, timer and mapper internals are not captured.
+///
+/// Layout: `GBSS`, A F B C D E H L, SP and PC (u16 LE), IME, then 64 KiB of memory as
+/// `Cpu::peek` sees it, so OAM DMA and PPU access blocking do not show up as differences.
+pub fn capture(cpu: &Cpu) -> Vec<u8> {
+    let r = &cpu.registers;
+    let mut bytes = Vec::with_capacity(SNAPSHOT_LEN);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&[r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l]);
+    bytes.extend_from_slice(&r.sp.to_le_bytes());
+    bytes.extend_from_slice(&r.pc.to_le_bytes());
+    bytes.push(r.ime as u8);
+
+    bytes.extend((0..=0xFFFFu16).map(|address| cpu.peek(Addr(address))));
+    bytes
+}
+
+/// What differs between two snapshots: `(register, old, new)` and `(address, old, new)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub registers: Vec<(&'static str, u16, u16)>,
+    pub memory: Vec<(u16, u8, u8)>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty() && self.memory.is_empty()
+    }
+}
+
+/// Decodes two snapshots from `capture` and lists every register and address that differs.
+/// Echo RAM is skipped since it would repeat every WRAM difference.
+pub fn diff_state(a: &[u8], b: &[u8]) -> Result<StateDiff, String> {
+    let (registers_a, memory_a) = decode(a)?;
+    let (registers_b, memory_b) = decode(b)?;
+
+    let registers = REGISTER_NAMES
+        .iter()
+        .zip(registers_a.into_iter().zip(registers_b))
+        .filter(|(_, (old, new))| old != new)
+        .map(|(name, (old, new))| (*name, old, new))
+        .collect();
+    let memory = memory_a
+        .iter()
+        .zip(memory_b)
+        .enumerate()
+        .map(|(address, (&old, &new))| (address as u16, old, new))
+        .filter(|&(address, old, new)| old != new && region(address) != MemRegion::EchoRam)
+        .collect();
+
+    Ok(StateDiff { registers, memory })
+}
+
+fn decode(bytes: &[u8]) -> Result<([u16; 11], &[u8]), String> {
+    let body = bytes
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| "missing snapshot header".to_string())?;
+    if body.len() != REGISTERS_LEN + ADDRESS_SPACE_LEN {
+        return Err(format!(
+            "snapshot is {} bytes, expected {SNAPSHOT_LEN}",
+            bytes.len()
+        ));
+    }
+
+    let (r, memory) = body.split_at(REGISTERS_LEN);
+    let mut registers = [0u16; 11];
+    for (value, &byte) in registers.iter_mut().zip(&r[..8]) {
+        *value = byte as u16;
+    }
+    registers[8] = u16::from_le_bytes([r[8], r[9]]);
+    registers[9] = u16::from_le_bytes([r[10], r[11]]);
+    registers[10] = r[12] as u16;
+    Ok((registers, memory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let cpu = Cpu::new();
+        let diff = diff_state(&capture(&cpu), &capture(&cpu)).unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_exactly_the_changed_address() {
+        let mut cpu = Cpu::new();
+        let before = capture(&cpu);
+        cpu.write_byte(Addr(0xC123), 0x42);
+        let after = capture(&cpu);
+
+        let diff = diff_state(&before, &after).unwrap();
+
+        assert_eq!(diff.memory, vec![(0xC123, 0x00, 0x42)]);
+        assert!(diff.registers.is_empty());
+    }
+
+    #[test]
+    fn capture_during_oam_dma_records_memory_not_the_blocked_bus() {
+        let mut cpu = Cpu::new();
+        cpu.write_byte(Addr(0xC123), 0x42);
+        let before = capture(&cpu);
+        cpu.write_byte(Addr(0xFF46), 0xC0); // start OAM DMA from 0xC000
+        let during = capture(&cpu);
+
+        assert_eq!(during[MAGIC.len() + REGISTERS_LEN + 0xC123], 0x42);
+        let diff = diff_state(&before, &during).unwrap();
+        assert!(
+            diff.memory.iter().all(|&(address, ..)| address == 0xFF46),
+            "{:04X?}",
+            diff.memory
+        );
+    }
+
+    #[test]
+    fn diff_reports_register_changes() {
+        let mut cpu = Cpu::new();
+        let before = capture(&cpu);
+        cpu.registers.a = 0x99;
+        cpu.registers.pc = 0x0150;
+        let after = capture(&cpu);
+
+        let diff = diff_state(&before, &after).unwrap();
+
+        assert_eq!(
+            diff.registers,
+            vec![("A", 0x01, 0x99), ("PC", 0x0100, 0x0150)]
+        );
+    }
+
+    #[test]
+    fn diff_rejects_malformed_snapshots() {
+        let good = capture(&Cpu::new());
+        assert!(diff_state(b"nope", &good).is_err());
+        assert!(diff_state(&good[..good.len() - 1], &good).is_err());
+    }
+}
@@ -648,12 +648,10 @@ fn validate_shader_contract(source: &str) -> Result<(), String> {
                     sampler_ok = true;
                 }
             }
-            2 => {
-                if global.space == AddressSpace::Uniform
-                    && uniform_struct_matches(&module, global.ty)
-                {
-                    uniform_ok = true;
-                }
+            2 if global.space == AddressSpace::Uniform
+                && uniform_struct_matches(&module, global.ty) =>
+            {
+                uniform_ok = true;
             }
             _ => {}
         }
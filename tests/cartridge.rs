@@ -1,4 +1,7 @@
-use gabalah::cartridge::{Cartridge, CartridgeHeader, CartridgeType, CgbMode, Destination};
+use gabalah::cartridge::{
+    mbc_from_type, Cartridge, CartridgeError, CartridgeHeader, CartridgeType, CgbMode, Destination,
+    Mbc,
+};
 use gabalah::cpu::Cpu;
 use gabalah::memory::Addr;
 
@@ -66,6 +69,36 @@ fn parses_valid_header_fields() {
     assert_eq!(header.global_checksum, 0xBEEF);
 }
 
+#[test]
+fn cartridge_exposes_title_and_bank_metadata() {
+    let cartridge = Cartridge::new(build_rom());
+
+    assert_eq!(cartridge.title(), "TEST GAME");
+    assert_eq!(cartridge.cgb_flag(), Some(CgbMode::GbCompatible));
+    assert_eq!(cartridge.rom_banks(), 16);
+    assert_eq!(cartridge.ram_banks(), 4);
+}
+
+#[test]
+fn title_stops_at_nul_and_masks_non_ascii_bytes() {
+    let mut rom = build_rom();
+    rom[0x0134..0x0143].copy_from_slice(b"ZELDA\xE9\0JUNK\0\0\0\0");
+
+    let cartridge = Cartridge::new(rom);
+
+    assert_eq!(cartridge.title(), "ZELDA?");
+}
+
+#[test]
+fn cartridge_without_parsable_header_has_empty_title() {
+    let cartridge = Cartridge::new(vec![0u8; 0x100]);
+
+    assert_eq!(cartridge.title(), "");
+    assert_eq!(cartridge.cgb_flag(), None);
+    assert_eq!(cartridge.rom_banks(), 1);
+    assert_eq!(cartridge.ram_banks(), 0);
+}
+
 #[test]
 fn returns_error_for_too_short_buffer() {
     let short = vec![0u8; 0x014F];
@@ -105,6 +138,62 @@ fn cpu_load_rom_exposes_parsed_cartridge_header() {
     assert_eq!(header.cartridge_type, CartridgeType::Mbc5);
 }
 
+#[test]
+fn cpu_from_rom_loads_in_memory_image() {
+    // A tiny ROM-only image in the style of an embedded `include_bytes!` buffer: the entry point
+    // jumps to 0x0150, which holds `LD A,0x42`.
+    static ENTRY: [u8; 4] = [0x00, 0xC3, 0x50, 0x01];
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&ENTRY);
+    rom[0x0134..0x0138].copy_from_slice(b"TINY");
+    rom[0x0150..0x0152].copy_from_slice(&[0x3E, 0x42]);
+
+    let mut cpu = Cpu::from_rom(rom).expect("valid header should load");
+    assert_eq!(
+        cpu.cartridge_header().map(|h| h.title.as_str()),
+        Some("TINY")
+    );
+
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.registers.a, 0x42);
+}
+
+#[test]
+fn cpu_from_rom_rejects_unparseable_header() {
+    assert!(Cpu::from_rom(vec![0u8; 0x0100]).is_err());
+
+    let mut rom = build_rom();
+    rom[0x0147] = 0xEE; // unknown cartridge type
+    let err = Cpu::from_rom(rom)
+        .err()
+        .expect("unknown cartridge type should fail");
+    assert!(err.contains("cartridge type"), "{err}");
+}
+
+#[test]
+fn mbc_from_type_maps_supported_controllers() {
+    assert_eq!(mbc_from_type(CartridgeType::Rom), Ok(Mbc::None));
+    assert_eq!(mbc_from_type(CartridgeType::RomRamBattery11), Ok(Mbc::None));
+    assert_eq!(mbc_from_type(CartridgeType::Mbc1RamBattery), Ok(Mbc::Mbc1));
+}
+
+#[test]
+fn unsupported_controller_is_an_error() {
+    assert_eq!(
+        mbc_from_type(CartridgeType::PocketCamera),
+        Err(CartridgeError::UnsupportedMbc(0xFC))
+    );
+
+    let mut rom = build_rom();
+    rom[0x0147] = 0xFF; // HuC1+RAM+BATTERY
+    let err = Cpu::from_rom(rom)
+        .err()
+        .expect("HuC1 cartridge should be rejected");
+    assert_eq!(err, CartridgeError::UnsupportedMbc(0xFF).to_string());
+}
+
 #[test]
 fn computed_header_checksum_matches_stored_value() {
     let rom = build_rom_with_valid_checksums();
@@ -135,6 +224,22 @@ fn global_checksum_detects_tampered_rom_bytes() {
     assert_ne!(compute_global_checksum(&rom), header.global_checksum);
 }
 
+#[test]
+fn cartridge_verifies_global_checksum_of_a_known_sum() {
+    let mut rom = runtime_rom(0x00, 0x00, 2); // only nonzero byte: bank 1 marker 0x01
+    rom[0x1000] = 0xFF;
+    rom[0x7FFF] = 0x02;
+    rom[0x014E] = 0x01;
+    rom[0x014F] = 0x02; // stored big-endian, and not part of its own sum
+
+    let cartridge = Cartridge::new(rom.clone());
+    assert_eq!(cartridge.global_checksum(), 0x0102);
+    assert!(cartridge.verify_global_checksum());
+
+    rom[0x2000] = 0x01;
+    assert!(!Cartridge::new(rom).verify_global_checksum());
+}
+
 fn runtime_rom(cartridge_type: u8, rom_size_code: u8, banks: usize) -> Vec<u8> {
     let mut rom = vec![0u8; banks * 16 * 1024];
     rom[0x0143] = 0x00; // DMG mode
@@ -202,6 +307,20 @@ fn runtime_mbc1_switches_switchable_and_fixed_windows() {
     );
 }
 
+#[test]
+fn cpu_reads_high_bank_of_64k_rom_after_bank_switch() {
+    let mut rom = runtime_rom(0x01, 0x01, 4); // MBC1, 64 KiB
+    rom[3 * 16 * 1024 + 0x1234] = 0xA7;
+    let mut cpu = Cpu::new();
+    cpu.load_rom(rom);
+
+    cpu.write_byte(Addr(0x2000), 0x03);
+
+    assert_eq!(cpu.read_byte(Addr(0x4000)), 0x03);
+    assert_eq!(cpu.read_byte(Addr(0x5234)), 0xA7);
+    assert_eq!(cpu.read_byte(Addr(0x0000)), 0x00, "fixed bank stays mapped");
+}
+
 #[test]
 fn battery_backed_ram_can_be_exported_and_reloaded() {
     let rom = runtime_rom_with_ram(0x03, 0x01, 4, 0x03); // MBC1+RAM+BATTERY, 4 RAM banks
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use gabalah::memory::{Addr, Ram, Registers};
+    use gabalah::memory::io::{self, io_register_name};
+    use gabalah::memory::{region, Addr, Flags, InitPattern, MemRegion, Ram, Registers, RAM_START};
 
     fn setup() -> Registers {
         Registers::default()
@@ -22,6 +23,33 @@ mod tests {
         assert_eq!(registers.f, 0xF0);
     }
 
+    #[test]
+    fn test_flags_round_trip_through_f() {
+        let mut registers = setup();
+        let flags = Flags {
+            zero: true,
+            subtraction: false,
+            half_carry: true,
+            carry: false,
+        };
+        registers.set_flags(flags);
+        assert_eq!(registers.f, 0xA0);
+        assert_eq!(registers.flags(), flags);
+
+        registers.f = 0x5F; // low nibble is not a flag
+        assert_eq!(
+            registers.flags(),
+            Flags {
+                zero: false,
+                subtraction: true,
+                half_carry: false,
+                carry: true,
+            }
+        );
+        registers.set_flags(registers.flags());
+        assert_eq!(registers.f, 0x50);
+    }
+
     // --- Joypad ---
 
     fn joypad_ram() -> Ram {
@@ -156,6 +184,147 @@ mod tests {
         assert_eq!(result & 0xC0, 0xC0, "bits 6-7 must always read as 1");
     }
 
+    // --- Power-on patterns ---
+
+    #[test]
+    fn ones_pattern_fills_ram_but_keeps_io_registers() {
+        let ram = Ram::with_pattern(InitPattern::Ones);
+        let cells = ram.as_slice();
+        assert!(cells[0x8000 - RAM_START..=0x9FFF - RAM_START]
+            .iter()
+            .all(|&b| b == 0xFF));
+        assert!(cells[0xC000 - RAM_START..=0xDFFF - RAM_START]
+            .iter()
+            .all(|&b| b == 0xFF));
+        assert!(cells[0xFE00 - RAM_START..=0xFE9F - RAM_START]
+            .iter()
+            .all(|&b| b == 0xFF));
+        assert!(cells[0xFF80 - RAM_START..=0xFFFE - RAM_START]
+            .iter()
+            .all(|&b| b == 0xFF));
+        assert_eq!(
+            cells[0xFF40 - RAM_START],
+            0x91,
+            "LCDC keeps its post-boot value"
+        );
+        assert_eq!(cells[0xFFFF - RAM_START], 0x00, "IE is not RAM");
+    }
+
+    #[test]
+    fn zero_pattern_matches_new() {
+        let ram = Ram::with_pattern(InitPattern::Zero);
+        assert_eq!(ram.as_slice(), Ram::new().as_slice());
+    }
+
+    #[test]
+    fn dmg_boot_pattern_lays_out_logo_tiles_and_map() {
+        let ram = Ram::with_pattern(InitPattern::DmgBootVram);
+        let cells = ram.as_slice();
+        // Logo byte 0xCE: nibble 0xC doubles to 0xF0, nibble 0xE to 0xFC; each row twice.
+        assert_eq!(
+            &cells[0x8010 - RAM_START..0x8018 - RAM_START],
+            &[0xF0, 0, 0xF0, 0, 0xFC, 0, 0xFC, 0]
+        );
+        assert_eq!(cells[0x8190 - RAM_START], 0x3C, "(R) tile");
+        assert_eq!(cells[0x9904 - RAM_START], 0x01);
+        assert_eq!(cells[0x990F - RAM_START], 0x0C);
+        assert_eq!(cells[0x9910 - RAM_START], 0x19);
+        assert_eq!(cells[0x9924 - RAM_START], 0x0D);
+        assert_eq!(cells[0x992F - RAM_START], 0x18);
+        assert!(cells[0xC000 - RAM_START..=0xDFFF - RAM_START]
+            .iter()
+            .all(|&b| b == 0));
+    }
+
+    // --- Addr ---
+
+    #[test]
+    fn addr_next_wraps_at_ffff() {
+        assert_eq!(Addr(0x1234).next(), Addr(0x1235));
+        assert_eq!(Addr(0xFFFF).next(), Addr(0x0000));
+    }
+
+    #[test]
+    fn addr_wrapping_add_wraps_at_ffff() {
+        assert_eq!(Addr(0xFF00).wrapping_add(0x44), Addr(0xFF44));
+        assert_eq!(Addr(0xFFFE).wrapping_add(3), Addr(0x0001));
+    }
+
+    #[test]
+    fn addr_wrapping_offset_wraps_in_both_directions() {
+        assert_eq!(Addr(0x0200).wrapping_offset(-2), Addr(0x01FE));
+        assert_eq!(Addr(0xFFFF).wrapping_offset(2), Addr(0x0001));
+        assert_eq!(Addr(0x0001).wrapping_offset(-2), Addr(0xFFFF));
+    }
+
+    #[test]
+    fn read_word_at_ffff_wraps_high_byte_to_0000() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFFFF), 0x34);
+        ram.write_byte(Addr(0x0000), 0x12);
+        assert_eq!(ram.read_word(Addr(0xFFFF)), 0x1234);
+    }
+
+    #[test]
+    fn write_word_at_ffff_wraps_high_byte_to_0000() {
+        let mut ram = Ram::new();
+        ram.write_word(Addr(0xFFFF), 0xABCD);
+        assert_eq!(ram.read_byte(Addr(0xFFFF)), 0xCD);
+        assert_eq!(ram.read_byte(Addr(0x0000)), 0xAB);
+    }
+
+    // --- I/O registers ---
+
+    #[test]
+    fn io_register_name_looks_up_known_registers() {
+        assert_eq!(io_register_name(0xFF40), Some("LCDC"));
+        assert_eq!(io_register_name(io::IF), Some("IF"));
+        assert_eq!(io_register_name(io::IE), Some("IE"));
+        assert_eq!(io_register_name(0xFF03), None);
+        assert_eq!(io_register_name(0xC000), None);
+    }
+
+    #[test]
+    fn unmapped_io_reads_ff_while_mapped_registers_keep_their_value() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF03), 0x12);
+        ram.write_byte(Addr(0xFF4D), 0x34);
+        ram.write_byte(Addr(io::BGP), 0xE4);
+
+        assert_eq!(ram.read_byte(Addr(0xFF03)), 0xFF);
+        assert_eq!(ram.read_byte(Addr(0xFF4D)), 0xFF);
+        assert_eq!(ram.read_byte(Addr(io::BGP)), 0xE4);
+        assert_eq!(ram.read_byte(Addr(0xFF80)), 0x00, "HRAM is not I/O");
+    }
+
+    // --- Regions ---
+
+    #[test]
+    fn region_classifies_addresses() {
+        assert_eq!(region(0x0000), MemRegion::Rom0);
+        assert_eq!(region(0x3FFF), MemRegion::Rom0);
+        assert_eq!(region(0x4000), MemRegion::RomX);
+        assert_eq!(region(0x9FFF), MemRegion::Vram);
+        assert_eq!(region(0xA000), MemRegion::ExtRam);
+        assert_eq!(region(0xC123), MemRegion::Wram);
+        assert_eq!(region(0xE000), MemRegion::EchoRam);
+        assert_eq!(region(0xFE9F), MemRegion::Oam);
+        assert_eq!(region(0xFEA0), MemRegion::Unusable);
+        assert_eq!(region(0xFF44), MemRegion::Io);
+        assert_eq!(region(0xFF80), MemRegion::Hram);
+        assert_eq!(region(0xFFFF), MemRegion::Ie);
+    }
+
+    #[test]
+    fn only_code_regions_are_executable() {
+        assert!(region(0x0150).is_executable());
+        assert!(region(0xC000).is_executable());
+        assert!(region(0xFF80).is_executable());
+        assert!(!region(0x8000).is_executable());
+        assert!(!region(0xFE00).is_executable());
+        assert!(!region(0xFF00).is_executable());
+    }
+
     // --- Timer ---
 
     #[test]
@@ -176,6 +345,23 @@ mod tests {
         assert_eq!(ram.read_byte(Addr(0xFF04)), 0);
     }
 
+    #[test]
+    fn tac_stores_the_low_three_bits_and_reads_the_rest_as_ones() {
+        let mut ram = Ram::new();
+        assert_eq!(ram.read_byte(Addr(io::TAC)), 0xF8, "post-boot value");
+
+        ram.write_byte(Addr(io::TAC), 0x00);
+        assert_eq!(ram.read_byte(Addr(io::TAC)), 0xF8);
+
+        ram.write_byte(Addr(io::TAC), 0x05);
+        assert_eq!(ram.read_byte(Addr(io::TAC)), 0xFD);
+
+        ram.write_byte(Addr(io::TAC), 0xFA); // unused bits are dropped
+        assert_eq!(ram.read_byte(Addr(io::TAC)), 0xFA);
+        ram.write_byte(Addr(io::TAC), 0x78);
+        assert_eq!(ram.read_byte(Addr(io::TAC)), 0xF8);
+    }
+
     #[test]
     fn tima_stays_zero_when_timer_disabled() {
         let mut ram = Ram::new();
@@ -205,6 +391,18 @@ mod tests {
         assert_eq!(ram.read_byte(Addr(0xFF05)), 0x42);
     }
 
+    #[test]
+    fn timer_register_writes_route_to_timer_not_flat_ram() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF06), 0x42);
+        ram.write_byte(Addr(0xFF07), 0x05);
+        assert_eq!(ram.timer().read(0xFF06), 0x42);
+        assert_eq!(ram.timer().read(0xFF07), 0xFD);
+        assert_eq!(ram.as_slice()[0xFF06 - RAM_START], 0);
+        assert_eq!(ram.as_slice()[0xFF07 - RAM_START], 0);
+        assert_eq!(ram.read_byte(Addr(0xFF07)), 0xFD);
+    }
+
     #[test]
     fn tima_no_overflow_returns_false() {
         let mut ram = Ram::new();
@@ -213,6 +411,34 @@ mod tests {
         assert!(!overflow);
     }
 
+    #[test]
+    fn div_write_while_selected_bit_is_set_ticks_tima() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF04), 0); // counter = 0
+        ram.write_byte(Addr(0xFF07), 0x05); // TAC: enabled, 16-cycle rate (counter bit 3)
+        ram.tick(8); // bit 3 now set, next falling edge at 16
+        assert_eq!(ram.read_byte(Addr(0xFF05)), 0);
+
+        ram.write_byte(Addr(0xFF04), 0); // reset drops bit 3: spurious tick
+        assert_eq!(ram.read_byte(Addr(0xFF05)), 1);
+
+        // The counter restarted, so the next regular tick is 16 cycles after the write.
+        ram.tick(12);
+        assert_eq!(ram.read_byte(Addr(0xFF05)), 1);
+        ram.tick(4);
+        assert_eq!(ram.read_byte(Addr(0xFF05)), 2);
+    }
+
+    #[test]
+    fn div_write_while_selected_bit_is_clear_does_not_tick_tima() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFF04), 0);
+        ram.write_byte(Addr(0xFF07), 0x05);
+        ram.tick(4); // bit 3 still clear
+        ram.write_byte(Addr(0xFF04), 0);
+        assert_eq!(ram.read_byte(Addr(0xFF05)), 0);
+    }
+
     // --- OAM DMA ---
 
     #[test]
@@ -223,11 +449,53 @@ mod tests {
             ram.write_byte(Addr(0xC000 + i as u16), i);
         }
         ram.write_byte(Addr(0xFF46), 0xC0); // trigger DMA from 0xC000
+        ram.tick(160 * 4);
         for i in 0..160u8 {
             assert_eq!(ram.read_byte(Addr(0xFE00 + i as u16)), i, "OAM byte {i}");
         }
     }
 
+    #[test]
+    fn dma_takes_160_machine_cycles_and_restricts_cpu_to_hram() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xC000), 0x42);
+        ram.write_byte(Addr(0xC09F), 0x24);
+        ram.write_byte(Addr(0xFF80), 0x99);
+
+        ram.write_byte(Addr(0xFF46), 0xC0);
+        assert!(ram.dma_active());
+        assert_eq!(ram.read_byte(Addr(0xC000)), 0xFF, "WRAM is blocked");
+        assert_eq!(
+            ram.read_byte(Addr(0xFEA0)),
+            0xFF,
+            "so is the prohibited area"
+        );
+        assert_eq!(ram.read_byte(Addr(0xFF80)), 0x99, "HRAM stays readable");
+        ram.write_byte(Addr(0xC001), 0x77); // dropped
+        ram.write_byte(Addr(0xFF81), 0x55);
+        assert_eq!(ram.read_byte(Addr(0xFF81)), 0x55);
+
+        ram.tick(4);
+        assert_eq!(
+            ram.peek(Addr(0xFE00)),
+            0x42,
+            "first byte copied after one M-cycle"
+        );
+        ram.tick(159 * 4 - 4);
+        assert!(ram.dma_active());
+        assert_eq!(ram.peek(Addr(0xFE9F)), 0x00, "last byte not copied yet");
+
+        ram.tick(4);
+        assert!(!ram.dma_active());
+        assert_eq!(ram.read_byte(Addr(0xFE9F)), 0x24);
+        assert_eq!(ram.read_byte(Addr(0xC000)), 0x42, "access restored");
+        assert_eq!(
+            ram.read_byte(Addr(0xC001)),
+            0x00,
+            "write during DMA was dropped"
+        );
+    }
+
     #[test]
     fn dma_from_oam_page_is_stable() {
         let mut ram = Ram::new();
@@ -236,6 +504,7 @@ mod tests {
         }
 
         ram.write_byte(Addr(0xFF46), 0xFE); // trigger DMA from 0xFE00 (OAM page)
+        ram.tick(160 * 4);
 
         for i in 0..160u8 {
             assert_eq!(
@@ -249,10 +518,24 @@ mod tests {
     // --- LCD IO semantics ---
 
     #[test]
-    fn ly_write_resets_to_zero() {
+    fn ly_write_is_ignored() {
         let mut ram = Ram::new();
+        ram.set_ly_raw(0x42);
         ram.write_byte(Addr(0xFF44), 0x77);
-        assert_eq!(ram.read_byte(Addr(0xFF44)), 0);
+        assert_eq!(ram.read_byte(Addr(0xFF44)), 0x42);
+        ram.write_byte(Addr(0xFF44), 0x00);
+        assert_eq!(ram.read_byte(Addr(0xFF44)), 0x42);
+    }
+
+    #[test]
+    fn stat_write_only_changes_bits_3_to_6() {
+        let mut ram = Ram::new();
+        ram.set_stat_raw(0x82); // mode=2, coincidence=0
+        ram.write_byte(Addr(0xFF41), 0xFF);
+        assert_eq!(ram.read_byte(Addr(0xFF41)), 0xFA);
+
+        ram.write_byte(Addr(0xFF41), 0x28); // mode-0 and mode-2 interrupt enables
+        assert_eq!(ram.read_byte(Addr(0xFF41)), 0xAA);
     }
 
     #[test]
@@ -269,6 +552,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn oam_reads_return_ff_during_oam_scan() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFE10), 0x42);
+
+        ram.set_stat_raw(0x82); // mode 2
+        assert_eq!(ram.read_byte(Addr(0xFE10)), 0xFF);
+
+        ram.set_stat_raw(0x80); // mode 0
+        assert_eq!(ram.read_byte(Addr(0xFE10)), 0x42);
+    }
+
+    #[test]
+    fn vram_reads_return_ff_only_during_pixel_transfer() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0x8000), 0x42);
+
+        ram.set_stat_raw(0x83); // mode 3
+        assert_eq!(ram.read_byte(Addr(0x8000)), 0xFF);
+        assert_eq!(ram.read_byte(Addr(0xFE00)), 0xFF);
+
+        ram.set_stat_raw(0x82); // mode 2
+        assert_eq!(ram.read_byte(Addr(0x8000)), 0x42);
+    }
+
+    #[test]
+    fn peek_bypasses_ppu_blocking_and_unmapped_io() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0x8000), 0x42);
+        ram.write_byte(Addr(0xFE00), 0x24);
+        ram.write_byte(Addr(0xFF03), 0x12);
+        ram.set_stat_raw(0x83); // mode 3
+
+        assert_eq!(ram.read_byte(Addr(0x8000)), 0xFF);
+        assert_eq!(ram.peek(Addr(0x8000)), 0x42);
+        assert_eq!(ram.peek(Addr(0xFE00)), 0x24);
+        assert_eq!(ram.read_byte(Addr(0xFF03)), 0xFF);
+        assert_eq!(ram.peek(Addr(0xFF03)), 0x12);
+        assert_eq!(ram.peek(Addr(0xFF44)), ram.read_byte(Addr(0xFF44)));
+    }
+
+    #[test]
+    fn lcd_off_never_blocks_oam_or_vram_reads() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0x8000), 0x12);
+        ram.write_byte(Addr(0xFE00), 0x34);
+        ram.write_byte(Addr(0xFF40), 0x11); // LCD off
+        ram.set_stat_raw(0x83);
+
+        assert_eq!(ram.read_byte(Addr(0x8000)), 0x12);
+        assert_eq!(ram.read_byte(Addr(0xFE00)), 0x34);
+    }
+
+    // --- OAM corruption bug ---
+
+    fn oam_scan_ram(rows: &[[u16; 4]]) -> Ram {
+        let mut ram = Ram::new();
+        for (row, words) in rows.iter().enumerate() {
+            for (index, word) in words.iter().enumerate() {
+                ram.write_word(Addr(0xFE00 + (row * 8 + index * 2) as u16), *word);
+            }
+        }
+        ram.set_stat_raw(0x82); // mode 2
+        ram
+    }
+
+    fn oam_row(ram: &mut Ram, row: u16) -> [u16; 4] {
+        ram.set_stat_raw(0x80); // mode 0 so OAM is readable
+        let base = 0xFE00 + row * 8;
+        [0, 1, 2, 3].map(|index| ram.read_word(Addr(base + index * 2)))
+    }
+
+    #[test]
+    fn oam_bug_write_applies_corruption_formula() {
+        let mut ram = oam_scan_ram(&[
+            [0x0000; 4],
+            [0x1234, 0x1111, 0x5678, 0x2222],
+            [0xF0F0, 0x3333, 0x4444, 0x5555],
+        ]);
+
+        ram.trigger_oam_bug_write(0xFE00, 8); // PPU scanning row 2
+
+        // a=0xF0F0, b=0x1234, c=0x5678: ((a ^ c) & (b ^ c)) ^ c
+        assert_eq!(oam_row(&mut ram, 2), [0x5270, 0x1111, 0x5678, 0x2222]);
+        assert_eq!(oam_row(&mut ram, 1), [0x1234, 0x1111, 0x5678, 0x2222]);
+    }
+
+    #[test]
+    fn oam_bug_write_with_equal_neighbour_words_copies_them() {
+        let mut ram = oam_scan_ram(&[[0xAAAA, 0x0102, 0xAAAA, 0x0304], [0x00FF, 0, 0, 0]]);
+
+        ram.trigger_oam_bug_write(0xFEFF, 4); // PPU scanning row 1
+
+        // b == c masks out every bit of a, leaving c.
+        assert_eq!(oam_row(&mut ram, 1), [0xAAAA, 0x0102, 0xAAAA, 0x0304]);
+    }
+
+    #[test]
+    fn oam_bug_write_ignored_outside_oam_scan_or_range() {
+        let rows = [[0x1111; 4], [0x2222; 4]];
+
+        let mut ram = oam_scan_ram(&rows);
+        ram.trigger_oam_bug_write(0xC000, 4);
+        assert_eq!(oam_row(&mut ram, 1), [0x2222; 4]);
+
+        let mut ram = oam_scan_ram(&rows);
+        ram.set_stat_raw(0x80); // mode 0
+        ram.trigger_oam_bug_write(0xFE00, 4);
+        assert_eq!(oam_row(&mut ram, 1), [0x2222; 4]);
+
+        let mut ram = oam_scan_ram(&rows);
+        ram.trigger_oam_bug_write(0xFE00, 0); // row 0 is never corrupted
+        assert_eq!(oam_row(&mut ram, 0), [0x1111; 4]);
+    }
+
     // --- Memory map behavior ---
 
     fn runtime_mbc1_rom_with_ram(cartridge_type: u8, ram_size_code: u8) -> Vec<u8> {
@@ -290,7 +688,7 @@ mod tests {
     }
 
     #[test]
-    fn rom_window_cells_are_backed_by_cartridge_mapping() {
+    fn rom_window_reads_come_from_cartridge_not_cells() {
         let mut rom = vec![0u8; 32 * 1024];
         rom[0x0143] = 0x00; // DMG mode
         rom[0x0147] = 0x00; // ROM only
@@ -302,10 +700,95 @@ mod tests {
 
         assert_eq!(ram.read_byte(Addr(0x4000)), 0x5A);
         assert_eq!(
-            ram.as_slice()[0x4000],
-            0x5A,
-            "visible ROM cells should mirror mapper-selected cartridge windows"
+            ram.as_slice().len(),
+            0x10000 - RAM_START,
+            "the cartridge owns the ROM; the flat array starts at VRAM"
+        );
+    }
+
+    #[test]
+    fn boot_rom_overlays_low_rom_until_ff50_latch_is_written() {
+        let mut rom = vec![0u8; 32 * 1024];
+        rom[0x0000] = 0xC3; // cartridge RST 00 vector
+        rom[0x00FF] = 0x11;
+        rom[0x0100] = 0x00;
+        let mut boot_rom = vec![0u8; 256];
+        boot_rom[0x00] = 0x31; // LD SP,d16
+        boot_rom[0xFF] = 0x50;
+
+        let mut ram = Ram::new();
+        ram.load_rom(rom);
+        assert!(ram.load_boot_rom(vec![0u8; 255]).is_err());
+        ram.load_boot_rom(boot_rom).unwrap();
+
+        assert!(ram.boot_rom_mapped());
+        assert_eq!(ram.read_byte(Addr(0x0000)), 0x31);
+        assert_eq!(ram.read_byte(Addr(0x00FF)), 0x50);
+        assert_eq!(
+            ram.read_byte(Addr(0x0100)),
+            0x00,
+            "0x0100 onwards is the cartridge"
+        );
+
+        ram.write_byte(Addr(io::BOOT), 0x00);
+        assert!(
+            ram.boot_rom_mapped(),
+            "writing zero leaves the boot ROM mapped"
         );
+
+        ram.write_byte(Addr(io::BOOT), 0x01);
+        assert!(!ram.boot_rom_mapped());
+        assert_eq!(ram.read_byte(Addr(0x0000)), 0xC3);
+        assert_eq!(ram.read_byte(Addr(0x00FF)), 0x11);
+
+        ram.write_byte(Addr(io::BOOT), 0x00);
+        ram.write_byte(Addr(io::BOOT), 0x01);
+        assert!(!ram.boot_rom_mapped(), "the latch cannot be undone");
+        assert_eq!(ram.read_byte(Addr(0x0000)), 0xC3);
+    }
+
+    #[cfg(feature = "cgb")]
+    #[test]
+    fn vbk_selects_between_two_distinct_vram_banks() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0x9800), 0x11);
+
+        ram.write_byte(Addr(io::VBK), 0x01);
+        assert_eq!(ram.read_byte(Addr(io::VBK)), 0xFF);
+        ram.write_byte(Addr(0x9800), 0x22);
+        assert_eq!(ram.read_byte(Addr(0x9800)), 0x22);
+
+        ram.write_byte(Addr(io::VBK), 0x00);
+        assert_eq!(ram.read_byte(Addr(io::VBK)), 0xFE);
+        assert_eq!(ram.read_byte(Addr(0x9800)), 0x11, "bank 0 kept its value");
+        assert_eq!(ram.vram_bank1()[0x1800], 0x22);
+    }
+
+    #[cfg(feature = "cgb")]
+    #[test]
+    fn svbk_switches_wram_banks_without_aliasing() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xC000), 0xC0);
+        for bank in 1..=7u8 {
+            ram.write_byte(Addr(io::SVBK), bank);
+            ram.write_byte(Addr(0xD123), bank * 0x10);
+        }
+
+        for bank in 1..=7u8 {
+            ram.write_byte(Addr(io::SVBK), bank);
+            assert_eq!(ram.read_byte(Addr(io::SVBK)), 0xF8 | bank);
+            assert_eq!(ram.read_byte(Addr(0xD123)), bank * 0x10, "bank {bank}");
+            assert_eq!(
+                ram.read_byte(Addr(0xF123)),
+                bank * 0x10,
+                "echo of bank {bank}"
+            );
+            assert_eq!(ram.read_byte(Addr(0xC000)), 0xC0, "bank 0 is fixed");
+        }
+
+        ram.write_byte(Addr(io::SVBK), 0x00);
+        assert_eq!(ram.read_byte(Addr(io::SVBK)), 0xF9, "bank 0 selects bank 1");
+        assert_eq!(ram.read_byte(Addr(0xD123)), 0x10);
     }
 
     #[test]
@@ -515,9 +998,21 @@ mod tests {
     }
 
     #[test]
-    fn unusable_memory_reads_ff_and_ignores_writes() {
+    fn unusable_memory_reads_zero_and_ignores_writes() {
+        let mut ram = Ram::new();
+        ram.write_byte(Addr(0xFEA0), 0x12);
+        ram.write_byte(Addr(0xFEFF), 0x34);
+        assert_eq!(ram.read_byte(Addr(0xFEA0)), 0x00);
+        assert_eq!(ram.read_byte(Addr(0xFEFF)), 0x00);
+    }
+
+    #[test]
+    fn unusable_memory_reads_ff_while_oam_is_blocked() {
         let mut ram = Ram::new();
         ram.write_byte(Addr(0xFEA0), 0x12);
+        ram.set_stat_raw(0x82); // mode 2
         assert_eq!(ram.read_byte(Addr(0xFEA0)), 0xFF);
+        ram.set_stat_raw(0x83); // mode 3
+        assert_eq!(ram.read_byte(Addr(0xFEC0)), 0xFF);
     }
 }
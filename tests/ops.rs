@@ -1,10 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use gabalah::cpu::{Cpu, Instruction, Location, Mnemonic};
+    use gabalah::cpu::{Cpu, CycleSpec, Instruction, Location, Mnemonic, StackImbalance};
     use gabalah::cpu::{
         CARRY_FLAG_BITMASK, HALF_CARRY_FLAG_BITMASK, SUBTRACTION_FLAG_BITMASK, ZERO_FLAG_BITMASK,
     };
-    use gabalah::memory::Addr;
+    use gabalah::memory::{Addr, Button};
 
     fn setup() -> Cpu {
         let mut cpu = Cpu::new();
@@ -15,23 +15,19 @@ mod tests {
     #[test]
     fn test_ld_immediate() {
         let mut cpu = setup();
-        let instruction = Instruction::new(
-            Mnemonic::Ld8(Location::A.imm(), Location::Const8.imm()),
-            2,
-            8,
-        );
-        cpu.write_byte(Addr(0x100), 0x42);
+        cpu.write_byte(Addr(cpu.registers.pc), 0x3E); // LD A,d8
         cpu.write_byte(Addr(cpu.registers.pc + 1), 0x42);
-        cpu.execute(&instruction);
+        assert_eq!(cpu.execute_opcode_at_pc(), 8);
         assert_eq!(cpu.registers.a, 0x42);
+        assert_eq!(cpu.registers.pc, 0x102);
     }
 
     #[test]
     fn test_inc() {
         let mut cpu = setup();
         cpu.registers.a = 0x10;
-        let instruction = Instruction::new(Mnemonic::Inc8(Location::A.imm()), 1, 4);
-        cpu.execute(&instruction);
+        cpu.write_byte(Addr(cpu.registers.pc), 0x3C); // INC A
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x11, "unexpected INC result");
         assert_eq!(cpu.registers.f, 0, "unexpected flags");
     }
@@ -40,8 +36,8 @@ mod tests {
     fn test_inc_wrap() {
         let mut cpu = setup();
         cpu.registers.a = 0xFF;
-        let instruction = Instruction::new(Mnemonic::Inc8(Location::A.imm()), 1, 4);
-        cpu.execute(&instruction);
+        cpu.write_byte(Addr(cpu.registers.pc), 0x3C); // INC A
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x00, "unexpected INC result");
         assert_eq!(
             cpu.registers.f,
@@ -54,8 +50,8 @@ mod tests {
     fn test_dec() {
         let mut cpu = setup();
         cpu.registers.a = 0x10;
-        let instruction = Instruction::new(Mnemonic::Dec8(Location::A.imm()), 1, 4);
-        cpu.execute(&instruction);
+        cpu.write_byte(Addr(cpu.registers.pc), 0x3D); // DEC A
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x0F, "unexpected DEC result");
         assert_eq!(
             cpu.registers.f,
@@ -68,8 +64,8 @@ mod tests {
     fn test_dec_zero() {
         let mut cpu = setup();
         cpu.registers.a = 0x01;
-        let instruction = Instruction::new(Mnemonic::Dec8(Location::A.imm()), 1, 4);
-        cpu.execute(&instruction);
+        cpu.write_byte(Addr(cpu.registers.pc), 0x3D); // DEC A
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x00, "unexpected DEC result");
         assert_eq!(
             cpu.registers.f,
@@ -84,8 +80,8 @@ mod tests {
         cpu.registers.a = 0x10;
         let instruction = Instruction::new(
             Mnemonic::Add8(Location::A.imm(), Location::Const8.imm()),
-            1,
-            4,
+            2,
+            8,
         );
         cpu.write_byte(Addr(cpu.registers.pc + 1), 0x05);
         cpu.execute(&instruction);
@@ -98,8 +94,8 @@ mod tests {
         cpu.registers.a = 0xFF;
         let instruction = Instruction::new(
             Mnemonic::Add8(Location::A.imm(), Location::Const8.imm()),
-            1,
-            4,
+            2,
+            8,
         );
         cpu.write_byte(Addr(cpu.registers.pc + 1), 0x01);
         cpu.execute(&instruction);
@@ -114,13 +110,9 @@ mod tests {
     fn test_sub() {
         let mut cpu = setup();
         cpu.registers.a = 0x10;
-        let instruction = Instruction::new(
-            Mnemonic::Sub8(Location::A.imm(), Location::Const8.imm()),
-            1,
-            4,
-        );
+        cpu.write_byte(Addr(cpu.registers.pc), 0xD6); // SUB d8
         cpu.write_byte(Addr(cpu.registers.pc + 1), 0x05);
-        cpu.execute(&instruction);
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x0B, "unexpected result");
         assert_eq!(
             cpu.registers.f,
@@ -133,13 +125,9 @@ mod tests {
     fn test_sub_zero() {
         let mut cpu = setup();
         cpu.registers.a = 0x10;
-        let instruction = Instruction::new(
-            Mnemonic::Sub8(Location::A.imm(), Location::Const8.imm()),
-            1,
-            4,
-        );
+        cpu.write_byte(Addr(cpu.registers.pc), 0xD6); // SUB d8
         cpu.write_byte(Addr(cpu.registers.pc + 1), 0x10);
-        cpu.execute(&instruction);
+        cpu.execute_opcode_at_pc();
         assert_eq!(cpu.registers.a, 0x00);
         assert_eq!(
             cpu.registers.f,
@@ -147,6 +135,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inc16_dec16_leave_all_flags_unchanged() {
+        let mut cpu = setup();
+        let all_flags = ZERO_FLAG_BITMASK
+            | SUBTRACTION_FLAG_BITMASK
+            | HALF_CARRY_FLAG_BITMASK
+            | CARRY_FLAG_BITMASK;
+        cpu.registers.f = all_flags;
+        cpu.registers.set_de(0x00FF);
+        cpu.registers.set_hl(0x0000);
+        cpu.write_byte(Addr(0x100), 0x13); // INC DE
+        cpu.write_byte(Addr(0x101), 0x2B); // DEC HL
+
+        cpu.step();
+        assert_eq!(cpu.registers.de(), 0x0100);
+        assert_eq!(cpu.registers.f, all_flags);
+
+        cpu.registers.f = 0;
+        cpu.step();
+        assert_eq!(cpu.registers.hl(), 0xFFFF);
+        assert_eq!(cpu.registers.f, 0);
+    }
+
     #[test]
     fn test_jr() {
         let mut cpu = setup();
@@ -187,6 +198,26 @@ mod tests {
         assert_eq!(cpu.registers.pc, 0x100 + 2);
     }
 
+    #[test]
+    fn test_jr_minus_128_at_0000_wraps_to_high_memory() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0x0000;
+        let instruction = Instruction::new(Mnemonic::Jr(Location::Const8.imm()), 2, 12);
+        cpu.write_byte(Addr(0x0001), 0x80); // -128
+        cpu.execute(&instruction);
+        assert_eq!(cpu.registers.pc, 0xFF82);
+    }
+
+    #[test]
+    fn test_jr_plus_127_at_fff0_wraps_to_low_memory() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0xFFF0;
+        let instruction = Instruction::new(Mnemonic::Jr(Location::Const8.imm()), 2, 12);
+        cpu.write_byte(Addr(0xFFF1), 0x7F); // +127
+        cpu.execute(&instruction);
+        assert_eq!(cpu.registers.pc, 0x0071);
+    }
+
     #[test]
     fn test_call_pushes_address_of_next_instruction() {
         let mut cpu = setup();
@@ -201,6 +232,42 @@ mod tests {
         assert_eq!(cpu.read_word(Addr(0xFFFC)), 0x103);
     }
 
+    #[test]
+    fn test_ld_hl_d16_reads_little_endian_immediate() {
+        let mut cpu = setup();
+        cpu.write_byte(Addr(0x100), 0x21); // LD HL,d16
+        cpu.write_byte(Addr(0x101), 0x34); // low byte
+        cpu.write_byte(Addr(0x102), 0x12); // high byte
+        cpu.step();
+
+        assert_eq!(cpu.registers.hl(), 0x1234);
+        assert_eq!(cpu.registers.pc, 0x103);
+    }
+
+    #[test]
+    fn test_jp_a16_reads_little_endian_immediate() {
+        let mut cpu = setup();
+        cpu.write_byte(Addr(0x100), 0xC3); // JP a16
+        cpu.write_byte(Addr(0x101), 0xCD); // low byte
+        cpu.write_byte(Addr(0x102), 0xAB); // high byte
+        cpu.step();
+
+        assert_eq!(cpu.registers.pc, 0xABCD);
+    }
+
+    #[test]
+    fn test_jp_hl_jumps_to_value_of_hl() {
+        // Despite the `JP (HL)` spelling, 0xE9 does not read memory at HL.
+        let mut cpu = setup();
+        cpu.registers.set_hl(0x4000);
+        cpu.write_byte(Addr(0x100), 0xE9); // JP (HL)
+
+        let cycles = cpu.step();
+
+        assert_eq!(cpu.registers.pc, 0x4000);
+        assert_eq!(cycles, 4);
+    }
+
     #[test]
     fn test_rst_pushes_address_of_next_instruction() {
         let mut cpu = setup();
@@ -213,6 +280,29 @@ mod tests {
         assert_eq!(cpu.read_word(Addr(0xFFFC)), 0x201);
     }
 
+    #[test]
+    fn test_rst_pushes_a_return_address_that_ret_reads_back() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0xC123;
+        cpu.registers.sp = 0xD000;
+        cpu.write_byte(Addr(0xC123), 0xCF); // RST 08
+        cpu.write_byte(Addr(0x0008), 0xC9); // RET
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x0008);
+        assert_eq!(cpu.registers.sp, 0xCFFE);
+        assert_eq!(
+            cpu.read_byte(Addr(0xCFFE)),
+            0x24,
+            "low byte at the lower address"
+        );
+        assert_eq!(cpu.read_byte(Addr(0xCFFF)), 0xC1);
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0xC124);
+        assert_eq!(cpu.registers.sp, 0xD000);
+    }
+
     #[test]
     fn test_add_immediate_opcode_c6() {
         let mut cpu = setup();
@@ -280,6 +370,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inc_hl_indirect_sets_z_h_and_preserves_carry() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0xC000);
+        cpu.registers.f = CARRY_FLAG_BITMASK;
+        cpu.write_byte(Addr(0xC000), 0xFF);
+        cpu.write_byte(Addr(0x100), 0x34); // INC (HL)
+        cpu.step();
+
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x00);
+        assert_eq!(
+            cpu.registers.f,
+            ZERO_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK
+        );
+    }
+
+    #[test]
+    fn test_dec_hl_indirect_sets_z_n_and_preserves_carry() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0xC000);
+        cpu.registers.f = CARRY_FLAG_BITMASK;
+        cpu.write_byte(Addr(0xC000), 0x01);
+        cpu.write_byte(Addr(0x100), 0x35); // DEC (HL)
+        cpu.step();
+
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x00);
+        assert_eq!(
+            cpu.registers.f,
+            ZERO_FLAG_BITMASK | SUBTRACTION_FLAG_BITMASK | CARRY_FLAG_BITMASK
+        );
+    }
+
     #[test]
     fn test_ld_hli_a() {
         let mut cpu = setup();
@@ -347,6 +469,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_sp_and_ldhl_with_negative_offset_from_ffff() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xFFFF;
+        cpu.write_byte(Addr(0x100), 0xF8); // LD HL,SP-1
+        cpu.write_byte(Addr(0x101), 0xFF);
+        cpu.write_byte(Addr(0x102), 0xE8); // ADD SP,-1
+        cpu.write_byte(Addr(0x103), 0xFF);
+
+        cpu.step();
+        assert_eq!(cpu.registers.hl(), 0xFFFE);
+        assert_eq!(
+            cpu.registers.f,
+            HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK
+        );
+
+        cpu.step();
+        assert_eq!(cpu.registers.sp, 0xFFFE);
+        assert_eq!(
+            cpu.registers.f,
+            HALF_CARRY_FLAG_BITMASK | CARRY_FLAG_BITMASK
+        );
+    }
+
     #[test]
     fn test_scf_clears_n_and_h() {
         let mut cpu = setup();
@@ -447,6 +593,22 @@ mod tests {
         assert_eq!(cpu.total_cycles, 12);
     }
 
+    #[test]
+    fn test_every_cb_opcode_takes_its_table_cycles() {
+        for opcode in 0..=0xFFu8 {
+            let mut cpu = setup();
+            cpu.registers.set_hl(0xC000);
+            cpu.write_byte(Addr(0x100), 0xCB);
+            cpu.write_byte(Addr(0x101), opcode);
+            let CycleSpec::Fixed(expected) = gabalah::cpu::cb_opcode_info(opcode).cycles else {
+                panic!("CB {opcode:02X} has branch timing");
+            };
+
+            assert_eq!(cpu.execute_opcode_at_pc(), expected, "CB {opcode:02X}");
+            assert_eq!(cpu.registers.pc, 0x102, "CB {opcode:02X}");
+        }
+    }
+
     #[test]
     fn test_cb_bit_cycle_matrix_register_and_hl() {
         let mut cpu = setup();
@@ -470,6 +632,43 @@ mod tests {
         assert_eq!(cpu.total_cycles, 32);
     }
 
+    #[test]
+    fn test_cb_step_cycles_bit_register_bit_hl_set_hl() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0xC000);
+        for (offset, cb) in [0x47u8, 0x46, 0xC6].into_iter().enumerate() {
+            cpu.write_byte(Addr(0x100 + 2 * offset as u16), 0xCB);
+            cpu.write_byte(Addr(0x101 + 2 * offset as u16), cb);
+        }
+
+        assert_eq!(cpu.step(), 8, "BIT 0,A");
+        assert_eq!(cpu.step(), 12, "BIT 0,(HL)");
+        assert_eq!(cpu.step(), 16, "SET 0,(HL)");
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x01);
+    }
+
+    #[test]
+    fn test_hl_read_modify_write_and_store_immediate_take_12_cycles() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0xC000);
+        cpu.write_byte(Addr(0xC000), 0x41);
+        cpu.write_byte(Addr(0x100), 0x34); // INC (HL)
+        cpu.write_byte(Addr(0x101), 0x35); // DEC (HL)
+        cpu.write_byte(Addr(0x102), 0x35); // DEC (HL)
+        cpu.write_byte(Addr(0x103), 0x36); // LD (HL),0x99
+        cpu.write_byte(Addr(0x104), 0x99);
+
+        assert_eq!(cpu.step(), 12, "INC (HL)");
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x42);
+        assert_eq!(cpu.step(), 12, "DEC (HL)");
+        assert_eq!(cpu.step(), 12, "DEC (HL)");
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x40);
+        assert_eq!(cpu.step(), 12, "LD (HL),n");
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0x99);
+        assert_eq!(cpu.registers.pc, 0x105);
+        assert_eq!(cpu.total_cycles, 48);
+    }
+
     #[test]
     fn test_conditional_jr_cycle_selection() {
         let mut cpu = setup();
@@ -486,6 +685,194 @@ mod tests {
         assert_eq!(cpu.total_cycles, 20);
     }
 
+    #[test]
+    fn test_interrupt_state_reports_vblank() {
+        let mut cpu = setup();
+        cpu.registers.ime = true;
+        cpu.write_byte(Addr(0xFFFF), 0x01); // IE: VBlank
+        cpu.write_byte(Addr(0xFF0F), 0x01); // IF: VBlank
+
+        let state = cpu.interrupt_state();
+        assert!(state.ime);
+        assert!(state.enabled.vblank);
+        assert!(state.requested.vblank);
+        assert!(!state.enabled.timer);
+        assert!(!state.requested.joypad);
+    }
+
+    #[test]
+    fn test_ei_delays_interrupt_dispatch_by_one_instruction() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xFFFE;
+        cpu.write_byte(Addr(0xFFFF), 0x04); // IE: timer
+        cpu.write_byte(Addr(0xFF0F), 0x04); // IF: timer pending
+        cpu.write_byte(Addr(0x100), 0xFB); // EI
+        cpu.write_byte(Addr(0x101), 0x00); // NOP
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x101, "no dispatch right after EI");
+        assert!(!cpu.registers.ime);
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x0050, "dispatched after the NOP");
+        assert_eq!(cpu.read_word(Addr(0xFFFC)), 0x102);
+    }
+
+    #[test]
+    fn test_di_right_after_ei_keeps_interrupts_disabled() {
+        let mut cpu = setup();
+        cpu.write_byte(Addr(0xFFFF), 0x04); // IE: timer
+        cpu.write_byte(Addr(0xFF0F), 0x04); // IF: timer pending
+        cpu.write_byte(Addr(0x100), 0xFB); // EI
+        cpu.write_byte(Addr(0x101), 0xF3); // DI
+        cpu.write_byte(Addr(0x102), 0x00); // NOP
+        cpu.set_call_stack_tracking(true);
+
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x103);
+        assert!(!cpu.registers.ime);
+        assert_eq!(cpu.get_if() & 0x04, 0x04, "timer request left pending");
+        assert!(cpu.call_stack().is_empty(), "no interrupt was serviced");
+    }
+
+    #[test]
+    fn test_step_debug_reports_nop() {
+        let mut cpu = setup();
+        cpu.write_byte(Addr(0x100), 0x00); // NOP
+
+        let result = cpu.step_debug();
+
+        assert_eq!(result.pc, 0x100);
+        assert!(matches!(result.instruction.mnemonic, Mnemonic::Nop));
+        assert_eq!(result.cycles, 4);
+        assert!(!result.interrupt_serviced);
+        assert!(!result.frame_completed);
+        assert_eq!(cpu.registers.pc, 0x101);
+    }
+
+    #[test]
+    fn test_step_debug_reports_serviced_interrupt() {
+        let mut cpu = setup();
+        cpu.registers.ime = true;
+        cpu.write_byte(Addr(0xFFFF), 0x04); // IE: timer
+        cpu.write_byte(Addr(0xFF0F), 0x04); // IF: timer pending
+        cpu.write_byte(Addr(0x100), 0x00); // NOP
+
+        let result = cpu.step_debug();
+
+        assert!(result.interrupt_serviced);
+        assert_eq!(result.cycles, 24);
+        assert_eq!(cpu.registers.pc, 0x0050);
+    }
+
+    #[test]
+    fn test_stop_skips_second_byte_and_stops() {
+        let mut cpu = setup();
+        cpu.write_byte(Addr(0x100), 0x10); // STOP
+        cpu.write_byte(Addr(0x101), 0x00);
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x102);
+        assert!(cpu.stopped);
+
+        cpu.step();
+        assert_eq!(cpu.registers.pc, 0x102, "a stopped CPU does not fetch");
+
+        cpu.set_button(Button::Start, true);
+        assert!(!cpu.stopped, "a button press ends STOP");
+    }
+
+    #[test]
+    fn test_word_through_const16_indirect_is_little_endian() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0x100;
+        cpu.write_word(Addr(0x101), 0xC123);
+        cpu.registers.sp = 0xBEEF;
+        let store = Instruction::new(
+            Mnemonic::Ld16(Location::Const16.ind(), Location::SP.imm()),
+            3,
+            20,
+        );
+        cpu.execute(&store);
+
+        assert_eq!(cpu.read_byte(Addr(0xC123)), 0xEF);
+        assert_eq!(cpu.read_byte(Addr(0xC124)), 0xBE);
+
+        cpu.registers.pc = 0x100;
+        let load = Instruction::new(
+            Mnemonic::Ld16(Location::HL.imm(), Location::Const16.ind()),
+            3,
+            20,
+        );
+        cpu.execute(&load);
+        assert_eq!(cpu.registers.hl(), 0xBEEF);
+    }
+
+    #[test]
+    fn test_push_pop_round_trip_through_stack() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xFFFE;
+        cpu.registers.set_bc(0x1234);
+        cpu.write_byte(Addr(0x100), 0xC5); // PUSH BC
+        cpu.write_byte(Addr(0x101), 0xD1); // POP DE
+
+        cpu.step();
+        assert_eq!(cpu.registers.sp, 0xFFFC);
+        assert_eq!(cpu.read_byte(Addr(0xFFFD)), 0x12);
+        assert_eq!(cpu.read_byte(Addr(0xFFFC)), 0x34);
+
+        cpu.step();
+        assert_eq!(cpu.registers.de(), 0x1234);
+        assert_eq!(cpu.registers.sp, 0xFFFE);
+    }
+
+    #[test]
+    fn test_pop_af_masks_low_nibble_of_f() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xC000;
+        cpu.registers.set_bc(0xABFF);
+        cpu.write_byte(Addr(0x100), 0xC5); // PUSH BC
+        cpu.write_byte(Addr(0x101), 0xF1); // POP AF
+
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.registers.a, 0xAB);
+        assert_eq!(cpu.registers.f, 0xF0);
+    }
+
+    #[test]
+    fn test_push_word_and_pop_word_wrap_sp() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0x0001;
+        cpu.push_word(0xBEEF);
+        assert_eq!(cpu.registers.sp, 0xFFFF);
+
+        assert_eq!(cpu.pop_word(), 0xBEEF);
+        assert_eq!(cpu.registers.sp, 0x0001);
+    }
+
+    #[test]
+    fn test_conditional_ret_cycle_selection() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xFFFC;
+        cpu.write_word(Addr(0xFFFC), 0x0200);
+
+        cpu.write_byte(Addr(0x100), 0xD8); // RET C
+        cpu.registers.f = 0; // C false
+        cpu.step();
+        assert_eq!(cpu.total_cycles, 8);
+        assert_eq!(cpu.registers.pc, 0x101);
+
+        cpu.write_byte(Addr(0x101), 0xD8); // RET C
+        cpu.registers.f = CARRY_FLAG_BITMASK; // C true
+        cpu.step();
+        assert_eq!(cpu.total_cycles, 28);
+        assert_eq!(cpu.registers.pc, 0x200);
+        assert_eq!(cpu.registers.sp, 0xFFFE);
+    }
+
     #[test]
     fn test_halt_bug_duplicates_next_opcode_for_immediate_read() {
         let mut cpu = setup();
@@ -501,8 +888,14 @@ mod tests {
         assert_eq!(cpu.registers.pc, 0x101);
 
         cpu.step(); // LD B,d8 with duplicated opcode byte
-        assert_eq!(cpu.registers.b, 0x06, "opcode byte should be read as immediate");
-        assert_eq!(cpu.registers.pc, 0x102, "PC should advance by one fewer byte");
+        assert_eq!(
+            cpu.registers.b, 0x06,
+            "opcode byte should be read as immediate"
+        );
+        assert_eq!(
+            cpu.registers.pc, 0x102,
+            "PC should advance by one fewer byte"
+        );
     }
 
     #[test]
@@ -519,10 +912,501 @@ mod tests {
         cpu.step(); // HALT (bugged path)
         cpu.step(); // INC B (first time)
         assert_eq!(cpu.registers.b, 1);
-        assert_eq!(cpu.registers.pc, 0x101, "1-byte opcode should be fetched twice");
+        assert_eq!(
+            cpu.registers.pc, 0x101,
+            "1-byte opcode should be fetched twice"
+        );
 
         cpu.step(); // INC B (second time)
         assert_eq!(cpu.registers.b, 2);
         assert_eq!(cpu.registers.pc, 0x102);
     }
+
+    #[test]
+    fn test_halt_bug_only_fires_when_interrupt_is_already_pending() {
+        let mut cpu = setup();
+        cpu.registers.a = 0;
+        cpu.write_byte(Addr(0xFFFF), 0x01); // IE: VBlank enabled
+        cpu.write_byte(Addr(0xFF0F), 0x00); // IF: nothing pending yet
+
+        cpu.write_byte(Addr(0x100), 0x76); // HALT
+        cpu.write_byte(Addr(0x101), 0x3C); // INC A
+        cpu.write_byte(Addr(0x102), 0x00); // NOP
+
+        cpu.step(); // HALT with nothing pending: a real halt
+        assert!(cpu.halted);
+        assert_eq!(cpu.registers.pc, 0x101);
+
+        cpu.raise_if(0x01); // wakes the CPU; IME=0 so no dispatch
+        cpu.step();
+        assert!(!cpu.halted);
+        cpu.step(); // INC A, fetched once
+        assert_eq!(cpu.registers.a, 1);
+        assert_eq!(
+            cpu.registers.pc, 0x102,
+            "waking from HALT does not trigger the bug"
+        );
+    }
+
+    #[test]
+    fn test_step_services_pending_interrupt_with_20_cycles() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xFFFE;
+        cpu.registers.ime = true;
+        cpu.write_byte(Addr(0xC000), 0x00); // NOP
+        cpu.write_byte(Addr(0xFFFF), 0x04); // IE: timer
+        cpu.raise_if(0x04); // IF: timer pending
+
+        let cycles = cpu.step();
+
+        assert_eq!(cycles, 24, "NOP plus 20-cycle interrupt dispatch");
+        assert!(!cpu.registers.ime);
+        assert_eq!(cpu.registers.pc, 0x0050);
+        assert_eq!(cpu.registers.sp, 0xFFFC);
+        assert_eq!(cpu.read_word(Addr(0xFFFC)), 0xC001);
+        assert_eq!(cpu.get_if() & 0x04, 0);
+    }
+
+    #[test]
+    fn test_frame_ready_raised_once_per_70224_cycles() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+
+        let mut cycles = 0;
+        let mut frames = 0;
+        while cycles < 70224 {
+            cycles += cpu.step();
+            if cpu.frame_ready() {
+                frames += 1;
+            }
+        }
+
+        assert_eq!(cycles, 70224);
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn test_call_stack_balanced_by_ret() {
+        let mut cpu = setup();
+        cpu.set_call_stack_tracking(true);
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.write_byte(Addr(0xC000), 0xCD); // CALL 0xC100
+        cpu.write_word(Addr(0xC001), 0xC100);
+        cpu.write_byte(Addr(0xC100), 0xC9); // RET
+
+        cpu.step();
+        assert_eq!(cpu.call_stack(), &[0xC003]);
+        cpu.step();
+        assert!(cpu.call_stack().is_empty());
+    }
+
+    #[test]
+    fn test_call_stack_keeps_unmatched_call() {
+        let mut cpu = setup();
+        cpu.set_call_stack_tracking(true);
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.write_byte(Addr(0xC000), 0xCD); // CALL 0xC100
+        cpu.write_word(Addr(0xC001), 0xC100);
+        cpu.write_byte(Addr(0xC100), 0xC7); // RST 00h
+
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.call_stack(), &[0xC003, 0xC101]);
+    }
+
+    #[test]
+    fn test_call_stack_drops_outermost_calls_past_the_limit() {
+        let mut cpu = setup();
+        cpu.set_call_stack_tracking(true);
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        // A chain of 300 CALLs, each to the next one.
+        for i in 0..300u16 {
+            let address = 0xC000 + 3 * i;
+            cpu.write_byte(Addr(address), 0xCD);
+            cpu.write_word(Addr(address + 1), address + 3);
+        }
+
+        for _ in 0..300 {
+            cpu.step();
+        }
+        let expected: Vec<u16> = (44..300u16).map(|i| 0xC003 + 3 * i).collect();
+        assert_eq!(cpu.call_stack(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_call_stack_is_empty_unless_tracking_is_on() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.write_byte(Addr(0xC000), 0xCD); // CALL 0xC100
+        cpu.write_word(Addr(0xC001), 0xC100);
+
+        cpu.step();
+        assert!(cpu.call_stack().is_empty());
+    }
+
+    #[test]
+    fn test_stack_balance_check_reports_push_without_pop() {
+        let mut cpu = setup();
+        cpu.set_stack_balance_check(true);
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.write_byte(Addr(0xC000), 0xCD); // CALL 0xC100
+        cpu.write_word(Addr(0xC001), 0xC100);
+        cpu.write_byte(Addr(0xC100), 0xC5); // PUSH BC
+        cpu.write_byte(Addr(0xC101), 0xC9); // RET
+
+        cpu.step();
+        cpu.step();
+        cpu.step();
+
+        assert_eq!(
+            cpu.stack_imbalances(),
+            &[StackImbalance {
+                pc: 0xC101,
+                return_address: 0xC003,
+                expected_sp: 0xDFFC,
+                actual_sp: 0xDFFA,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stack_balance_check_accepts_balanced_call() {
+        let mut cpu = setup();
+        cpu.set_stack_balance_check(true);
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.write_byte(Addr(0xC000), 0xCD); // CALL 0xC100
+        cpu.write_word(Addr(0xC001), 0xC100);
+        cpu.write_byte(Addr(0xC100), 0xC5); // PUSH BC
+        cpu.write_byte(Addr(0xC101), 0xC1); // POP BC
+        cpu.write_byte(Addr(0xC102), 0xC9); // RET
+
+        for _ in 0..4 {
+            cpu.step();
+        }
+
+        assert_eq!(cpu.registers.pc, 0xC003);
+        assert!(cpu.stack_imbalances().is_empty());
+    }
+
+    #[test]
+    fn test_step_counts_instructions_and_cycles() {
+        let mut cpu = setup();
+        cpu.registers.pc = 0xC000;
+        for offset in 0..10 {
+            cpu.write_byte(Addr(0xC000 + offset), 0x00); // NOP
+        }
+
+        for _ in 0..10 {
+            cpu.step();
+        }
+
+        assert_eq!(cpu.instructions_executed(), 10);
+        assert_eq!(cpu.total_cycles, 40);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "shorter than its operands need")]
+    fn test_instruction_shorter_than_its_immediate_is_rejected() {
+        Instruction::new(
+            Mnemonic::Ld8(Location::A.imm(), Location::Const8.imm()),
+            1,
+            8,
+        );
+    }
+
+    #[test]
+    fn test_min_bytes_accounts_for_immediates() {
+        assert_eq!(Mnemonic::Nop.min_bytes(), 1);
+        assert_eq!(
+            Mnemonic::Ld8(Location::A.imm(), Location::Const8.high()).min_bytes(),
+            2
+        );
+        assert_eq!(
+            Mnemonic::Ld8(Location::Const16.ind(), Location::A.imm()).min_bytes(),
+            3
+        );
+        assert_eq!(Mnemonic::Call(Location::Const16.imm()).min_bytes(), 3);
+    }
+
+    #[test]
+    fn test_run_frame_stops_at_vblank_and_returns_rgba_frame() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+
+        let frame_len = cpu.run_frame().len();
+
+        assert_eq!(frame_len, 160 * 144 * 4);
+        assert!(cpu.frame_ready());
+        assert_eq!(cpu.read_byte(Addr(0xFF44)), 144);
+    }
+
+    #[test]
+    fn test_step_frame_runs_one_frame_between_vblanks() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+
+        cpu.step_frame(); // up to the first VBlank, from wherever power-on left LY
+        let cycles = cpu.step_frame();
+
+        assert!(
+            (70224..70224 + 4).contains(&cycles),
+            "one frame is 70224 cycles, got {cycles}"
+        );
+        assert!(cpu.frame_ready());
+        assert_eq!(cpu.read_byte(Addr(0xFF44)), 144);
+    }
+
+    #[test]
+    fn test_step_scanline_advances_ly_by_one_line() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+        cpu.step_scanline(); // to the start of a line, from wherever power-on left LY
+
+        let ly = cpu.read_byte(Addr(0xFF44));
+        let cycles = cpu.step_scanline();
+
+        assert_eq!(cpu.read_byte(Addr(0xFF44)), (ly + 1) % 154);
+        assert!(
+            (456..456 + 4).contains(&cycles),
+            "one line is 456 cycles, got {cycles}"
+        );
+    }
+
+    #[test]
+    fn test_frame_hash_is_stable_across_identical_runs() {
+        let run = |bgp: u8| {
+            let mut cpu = setup();
+            cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+            cpu.write_byte(Addr(0xFF47), bgp);
+            cpu.step_frame();
+            cpu.step_frame();
+            cpu.frame_hash()
+        };
+
+        assert_eq!(run(0xE4), run(0xE4));
+        assert_ne!(run(0xE4), run(0xFF), "a different frame hashes differently");
+    }
+
+    #[test]
+    fn test_step_frame_with_lcd_off_stops_after_one_frame_of_cycles() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+        cpu.write_byte(Addr(0xFF40), 0x00); // LCD off
+
+        let cycles = cpu.step_frame();
+
+        assert!((70224..70224 + 4).contains(&cycles), "got {cycles}");
+        assert!(!cpu.frame_ready());
+    }
+
+    #[test]
+    fn test_frame_buffer_holds_latest_completed_frame() {
+        let mut cpu = setup();
+        cpu.load_rom(vec![0x00; 0x8000]); // NOP sled
+        cpu.run_frame();
+        let first = cpu.frame_buffer().to_vec();
+        assert_eq!(first.len(), 160 * 144 * 4);
+
+        cpu.write_byte(Addr(0xFF47), 0xFF); // BGP: every shade dark
+        while cpu.read_byte(Addr(0xFF44)) != 10 {
+            cpu.step();
+        }
+        assert_eq!(
+            cpu.frame_buffer(),
+            first.as_slice(),
+            "lines of the frame in progress are not visible yet"
+        );
+
+        cpu.run_frame();
+        assert_ne!(cpu.frame_buffer(), first.as_slice());
+    }
+
+    #[test]
+    fn test_set_button_reflects_in_joypad_register() {
+        let mut cpu = setup();
+        cpu.clear_if(0x10);
+
+        cpu.set_button(Button::A, true);
+        cpu.write_byte(Addr(0xFF00), 0x10); // select action buttons
+        assert_eq!(cpu.read_byte(Addr(0xFF00)) & 0x0F, 0x0E);
+        assert_ne!(
+            cpu.get_if() & 0x10,
+            0,
+            "press should request joypad interrupt"
+        );
+
+        cpu.write_byte(Addr(0xFF00), 0x20); // select direction buttons
+        assert_eq!(cpu.read_byte(Addr(0xFF00)) & 0x0F, 0x0F);
+
+        cpu.set_button(Button::A, false);
+        cpu.write_byte(Addr(0xFF00), 0x10);
+        assert_eq!(cpu.read_byte(Addr(0xFF00)) & 0x0F, 0x0F);
+    }
+
+    #[test]
+    fn test_set_button_interrupts_only_on_press_edge() {
+        let mut cpu = setup();
+        cpu.set_button(Button::Start, true);
+        cpu.clear_if(0x10);
+
+        cpu.set_button(Button::Start, true);
+        assert_eq!(cpu.get_if() & 0x10, 0, "held button must not re-request");
+        cpu.set_button(Button::Start, false);
+        assert_eq!(cpu.get_if() & 0x10, 0, "release must not request");
+    }
+
+    #[test]
+    fn test_ld_sp_hl_copies_hl_into_sp() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0xC123);
+        cpu.write_byte(Addr(0x100), 0xF9); // LD SP,HL
+
+        let cycles = cpu.step();
+
+        assert_eq!(cpu.registers.sp, 0xC123);
+        assert_eq!(cpu.registers.hl(), 0xC123);
+        assert_eq!(cpu.registers.pc, 0x101);
+        assert_eq!(cycles, 8);
+    }
+
+    #[test]
+    fn test_add_hl_sp_reads_sp() {
+        let mut cpu = setup();
+        cpu.registers.set_hl(0x0FFF);
+        cpu.registers.sp = 0x0001;
+        cpu.registers.f = ZERO_FLAG_BITMASK;
+        cpu.write_byte(Addr(0x100), 0x39); // ADD HL,SP
+
+        cpu.step();
+
+        assert_eq!(cpu.registers.hl(), 0x1000);
+        assert_eq!(cpu.registers.sp, 0x0001);
+        assert_eq!(
+            cpu.registers.f,
+            ZERO_FLAG_BITMASK | HALF_CARRY_FLAG_BITMASK,
+            "Z preserved, H from bit 11, N and C clear"
+        );
+    }
+
+    #[test]
+    fn test_ld_a16_sp_stores_sp_little_endian() {
+        let mut cpu = setup();
+        cpu.registers.sp = 0xBEEF;
+        cpu.write_byte(Addr(0x100), 0x08); // LD (a16),SP
+        cpu.write_word(Addr(0x101), 0xC000);
+
+        let cycles = cpu.step();
+
+        assert_eq!(cpu.read_byte(Addr(0xC000)), 0xEF);
+        assert_eq!(cpu.read_byte(Addr(0xC001)), 0xBE);
+        assert_eq!(cpu.registers.pc, 0x103);
+        assert_eq!(cycles, 20);
+    }
+
+    #[test]
+    fn test_set_registers_round_trips_the_whole_register_file() {
+        let mut cpu = setup();
+        let mut snapshot = cpu.registers().clone();
+        snapshot.a = 0x12;
+        snapshot.f = 0xB0;
+        snapshot.set_bc(0x3456);
+        snapshot.set_de(0x789A);
+        snapshot.set_hl(0xBCDE);
+        snapshot.sp = 0xDFF0;
+        snapshot.pc = 0x4321;
+        snapshot.ime = true;
+
+        cpu.set_registers(snapshot.clone());
+        assert_eq!(cpu.registers(), &snapshot);
+
+        cpu.registers_mut().a = 0x00;
+        assert_eq!(cpu.registers.a, 0x00);
+
+        snapshot.f = 0xFF;
+        cpu.set_registers(snapshot);
+        assert_eq!(cpu.registers().f, 0xF0, "the low nibble of F is not stored");
+    }
+
+    #[test]
+    fn test_opcode_info_decodes_without_a_cpu() {
+        let nop = gabalah::cpu::opcode_info(0x00);
+        assert!(matches!(nop.mnemonic, Mnemonic::Nop));
+        assert_eq!(nop.bytes, 1);
+        assert!(matches!(nop.cycles, CycleSpec::Fixed(4)));
+
+        let bit = gabalah::cpu::cb_opcode_info(0x7E); // BIT 7,(HL)
+        assert!(matches!(bit.mnemonic, Mnemonic::Bit(7, _)));
+        assert_eq!(bit.bytes, 2);
+        assert!(matches!(bit.cycles, CycleSpec::Fixed(12)));
+        assert!(matches!(
+            gabalah::cpu::cb_opcode_info(0x37).mnemonic,
+            Mnemonic::Swap(_)
+        ));
+    }
+
+    #[test]
+    fn test_adc_and_sbc_opcodes_only_take_8_bit_operands() {
+        let mut carrying = Vec::new();
+        for opcode in 0..=255u8 {
+            let (Mnemonic::Adc8(dst, src) | Mnemonic::Sbc8(dst, src)) =
+                gabalah::cpu::opcode_info(opcode).mnemonic
+            else {
+                continue;
+            };
+            assert_eq!(dst.target_size(), 1, "opcode {opcode:02X} destination");
+            assert_eq!(src.target_size(), 1, "opcode {opcode:02X} source");
+            carrying.push(opcode);
+        }
+        // ADC A,r / ADC A,d8 and SBC A,r / SBC A,d8; there is no 16-bit form.
+        let expected: Vec<u8> = (0x88..=0x8F)
+            .chain(0x98..=0x9F)
+            .chain([0xCE, 0xDE])
+            .collect();
+        assert_eq!(carrying, expected);
+    }
+
+    #[test]
+    fn test_opcode_table_byte_lengths_match_decode_consumption() {
+        for opcode in 0..=255u8 {
+            let (instruction, consumed) = gabalah::cpu::decode(&[opcode, 0x00, 0x00]).unwrap();
+            // 0xCB is the prefix and the rest of the invalid opcodes lock up the CPU.
+            if opcode == 0xCB || matches!(instruction.mnemonic, Mnemonic::Invalid(_)) {
+                continue;
+            }
+            assert_eq!(
+                instruction.bytes as usize, consumed,
+                "opcode {opcode:02X} ({:?})",
+                instruction.mnemonic
+            );
+            assert!(
+                gabalah::cpu::decode(&[opcode, 0x00, 0x00][..consumed - 1]).is_none(),
+                "opcode {opcode:02X} decodes from a truncated slice"
+            );
+        }
+        for opcode in 0..=255u8 {
+            let (instruction, consumed) = gabalah::cpu::decode(&[0xCB, opcode]).unwrap();
+            assert_eq!(instruction.bytes, 2, "CB {opcode:02X}");
+            assert_eq!(consumed, 2, "CB {opcode:02X}");
+        }
+        assert!(gabalah::cpu::decode(&[0xCB]).is_none());
+    }
+
+    #[test]
+    fn test_execute_opcode_at_pc_decodes_cb_prefixed_opcodes() {
+        let mut cpu = setup();
+        cpu.registers.a = 0x12;
+        cpu.write_byte(Addr(cpu.registers.pc), 0xCB);
+        cpu.write_byte(Addr(cpu.registers.pc + 1), 0x37); // SWAP A
+        assert_eq!(cpu.execute_opcode_at_pc(), 8);
+        assert_eq!(cpu.registers.a, 0x21);
+        assert_eq!(cpu.registers.pc, 0x102);
+    }
 }
@@ -0,0 +1,142 @@
+use gabalah::cpu::{Cpu, Timeout, TraceDivergence};
+use gabalah::memory::Addr;
+
+/// Upper bound on cycles before a test program is considered stuck.
+const CYCLE_LIMIT: usize = 100_000;
+
+/// Sums 1..=10 into A, counting B down to zero, then halts.
+const SUM_PROGRAM: [u8; 9] = [
+    0x3E, 0x00, // 0x0100: LD A,0
+    0x06, 0x0A, // 0x0102: LD B,10
+    0x80, // 0x0104: ADD A,B
+    0x05, // 0x0105: DEC B
+    0x20, 0xFC, // 0x0106: JR NZ,-4 → 0x0104
+    0x76, // 0x0108: HALT
+];
+
+/// Gameboy Doctor log of `TRACE_PROGRAM` from the post-boot register state.
+const TRACE_REFERENCE: &str = "\
+A:01 F:00 B:FF C:13 D:00 E:C1 H:84 L:03 SP:FFFE PC:0100 PCMEM:3E,05,3C,00
+A:05 F:00 B:FF C:13 D:00 E:C1 H:84 L:03 SP:FFFE PC:0102 PCMEM:3C,00,00,00
+A:06 F:00 B:FF C:13 D:00 E:C1 H:84 L:03 SP:FFFE PC:0103 PCMEM:00,00,00,00
+";
+
+/// LD A,5; INC A; NOP
+const TRACE_PROGRAM: [u8; 4] = [0x3E, 0x05, 0x3C, 0x00];
+
+fn load_program(program: &[u8]) -> Cpu {
+    let mut cpu = Cpu::new();
+    for (offset, &byte) in program.iter().enumerate() {
+        cpu.write_byte(Addr(0x0100 + offset as u16), byte);
+    }
+    cpu.registers.pc = 0x0100;
+    cpu
+}
+
+/// Loads `program` at 0x0100 and steps until HALT, returning the halted CPU.
+fn run_until_halt(program: &[u8]) -> Cpu {
+    let mut cpu = load_program(program);
+    if let Err(timeout) = cpu.run_until(|cpu| cpu.halted, CYCLE_LIMIT) {
+        panic!("program did not halt: {timeout}");
+    }
+    cpu
+}
+
+#[test]
+fn sum_loop_runs_to_halt() {
+    let cpu = run_until_halt(&SUM_PROGRAM);
+
+    assert_eq!(cpu.registers.a, 55);
+    assert_eq!(cpu.registers.b, 0);
+    // HALT has been fetched, so PC points just past it.
+    assert_eq!(cpu.registers.pc, 0x0109);
+    // 2 loads, 10 ADD/DEC/JR iterations and the HALT.
+    assert_eq!(cpu.instructions_executed(), 2 + 10 * 3 + 1);
+}
+
+#[test]
+fn run_until_stops_at_the_cycle_cap_in_an_infinite_loop() {
+    let mut cpu = load_program(&[0x18, 0xFE]); // JR -2
+
+    let result = cpu.run_until(|cpu| cpu.registers.pc == 0x0200, 1_000);
+
+    // 12-cycle JRs overshoot the cap by at most one instruction.
+    assert_eq!(result, Err(Timeout { cycles: 1_008 }));
+    assert_eq!(cpu.registers.pc, 0x0100);
+}
+
+#[test]
+fn run_until_returns_once_the_predicate_holds() {
+    let mut cpu = load_program(&SUM_PROGRAM);
+
+    assert_eq!(
+        cpu.run_until(|cpu| cpu.registers.pc == 0x0108, CYCLE_LIMIT),
+        Ok(())
+    );
+    assert_eq!(cpu.registers.a, 55);
+    assert!(!cpu.halted);
+}
+
+#[test]
+fn opcode_coverage_marks_exactly_the_executed_opcodes() {
+    let mut cpu = load_program(&[0x00, 0x3C, 0x00, 0xCB, 0x37]); // NOP; INC A; NOP; SWAP A
+    assert!(!cpu.executed_opcodes().contains(&true), "tracking is off");
+    cpu.set_opcode_coverage(true);
+
+    cpu.run_until(|cpu| cpu.registers.pc == 0x0103, CYCLE_LIMIT)
+        .unwrap();
+    let executed: Vec<usize> = (0..256).filter(|&op| cpu.executed_opcodes()[op]).collect();
+    assert_eq!(executed, [0x00, 0x3C]);
+    assert!(!cpu.executed_cb_opcodes().contains(&true));
+
+    cpu.step();
+    assert!(cpu.executed_opcodes()[0xCB]);
+    let executed_cb: Vec<usize> = (0..256)
+        .filter(|&op| cpu.executed_cb_opcodes()[op])
+        .collect();
+    assert_eq!(executed_cb, [0x37]);
+}
+
+#[test]
+fn cheat_codes_patch_rom_reads_and_write_ram_every_frame() {
+    let mut cpu = load_program(&[0x00]);
+    cpu.add_genie_code("3C1-00F").unwrap(); // 0x0100 := 0x3C (INC A)
+    cpu.add_shark_code("014200C1").unwrap(); // 0xC100 := 0x42
+    assert_eq!(cpu.read_byte(Addr(0x0100)), 0x3C);
+
+    cpu.write_byte(Addr(0xC100), 0x00);
+    cpu.step_frame();
+    assert_eq!(cpu.read_byte(Addr(0xC100)), 0x42);
+
+    assert!(cpu.add_genie_code("not a code").is_err());
+}
+
+#[test]
+fn compare_trace_accepts_a_matching_reference() {
+    let mut cpu = load_program(&TRACE_PROGRAM);
+
+    assert_eq!(cpu.compare_trace(TRACE_REFERENCE), None);
+    assert_eq!(cpu.registers.pc, 0x0103);
+}
+
+#[test]
+fn replay_and_compare_reports_the_first_diverging_line() {
+    let diverging = TRACE_REFERENCE.replace("A:06", "A:07");
+    let path = std::env::temp_dir().join(format!("gabalah-trace-{}.log", std::process::id()));
+    std::fs::write(&path, diverging).unwrap();
+
+    let mut cpu = load_program(&TRACE_PROGRAM);
+    let result = cpu.replay_and_compare(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        result.unwrap(),
+        Some(TraceDivergence {
+            line: 3,
+            expected: "A:07 F:00 B:FF C:13 D:00 E:C1 H:84 L:03 SP:FFFE PC:0103 PCMEM:00,00,00,00"
+                .to_string(),
+            actual: "A:06 F:00 B:FF C:13 D:00 E:C1 H:84 L:03 SP:FFFE PC:0103 PCMEM:00,00,00,00"
+                .to_string(),
+        })
+    );
+}
@@ -0,0 +1,51 @@
+use std::sync::Mutex;
+
+use gabalah::cpu::Cpu;
+use gabalah::memory::Addr;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct CaptureLogger {
+    lines: Mutex<Vec<(String, String)>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Trace
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines
+            .lock()
+            .unwrap()
+            .push((record.target().to_string(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(Vec::new()),
+};
+
+#[test]
+fn step_emits_trace_line_per_instruction() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut cpu = Cpu::new();
+    cpu.write_byte(Addr(0x100), 0x3E); // LD A,d8
+    cpu.write_byte(Addr(0x101), 0x42);
+    cpu.step();
+
+    let lines = LOGGER.lines.lock().unwrap();
+    let (target, line) = lines
+        .iter()
+        .find(|(target, _)| target.starts_with("gabalah::cpu"))
+        .expect("a trace line from the CPU");
+    assert_eq!(target, "gabalah::cpu::core");
+    assert!(
+        line.starts_with("0100 3E Ld8("),
+        "unexpected trace line: {line}"
+    );
+    assert!(line.ends_with(&format!("F={:02X}", cpu.registers.f)));
+}